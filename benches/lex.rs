@@ -0,0 +1,96 @@
+//! Lexer throughput over inputs made mostly of one kind of token, run with
+//! `cargo bench`, or `cargo bench -- <name>` for a single input.
+//!
+//! Inputs are lexed by `ripc --print-tokens`. The time it takes on an empty
+//! input, starting the process, is subtracted from every measurement.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How many times each input is lexed, the fastest run is reported.
+const RUNS: usize = 10;
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+
+    let inputs = [
+        ("strings", strings()),
+        ("identifiers", identifiers()),
+        ("escapes", escapes()),
+    ];
+
+    let dir = std::env::temp_dir().join(format!("ripc-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create a temporary directory");
+
+    let empty = dir.join("empty.rp");
+    std::fs::write(&empty, "").expect("failed to write input");
+    let startup = fastest(&empty);
+
+    for (name, source) in inputs.iter() {
+        if filter
+            .as_ref()
+            .is_some_and(|filter| !name.contains(filter.as_str()))
+        {
+            continue;
+        }
+
+        let path = dir.join(format!("{}.rp", name));
+        std::fs::write(&path, source).expect("failed to write input");
+
+        let elapsed = fastest(&path).saturating_sub(startup);
+        let throughput = source.len() as f64 / elapsed.as_secs_f64().max(1e-9) / 1e6;
+        println!(
+            "{:<12} {:>9} bytes {:>10.2?} {:>8.1} MB/s",
+            name,
+            source.len(),
+            elapsed,
+            throughput
+        );
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// The fastest of `RUNS` runs of the lexer over the file at `path`.
+fn fastest(path: &Path) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let status = Command::new(ripc())
+                .arg("--print-tokens")
+                .arg(path)
+                .stdout(Stdio::null())
+                .status()
+                .expect("failed to run ripc");
+            let elapsed = start.elapsed();
+
+            assert!(status.success(), "lexing '{}' failed", path.display());
+            elapsed
+        })
+        .min()
+        .unwrap()
+}
+
+fn ripc() -> PathBuf {
+    PathBuf::from(env!("CARGO_BIN_EXE_ripc"))
+}
+
+/// Long string literals, where nearly every byte is inside a string.
+fn strings() -> String {
+    let line = format!("\"{}\"\n", "the quick brown fox ".repeat(50));
+    line.repeat(4_000)
+}
+
+/// Identifiers with underscores and digits, separated by operators.
+fn identifiers() -> String {
+    (0..200_000)
+        .map(|i| format!("snake_case_{} + _x{}\n", i, i % 10))
+        .collect()
+}
+
+/// String literals made entirely of escapes.
+fn escapes() -> String {
+    let line = format!("\"{}\"\n", "\\n\\\"\\\\\\t".repeat(100));
+    line.repeat(4_000)
+}
@@ -0,0 +1,21 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .note.GNU-stack,"",@progbits
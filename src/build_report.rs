@@ -0,0 +1,165 @@
+use crate::SourceMap;
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// What a single invocation of the compiler did, written as JSON next to the
+/// output by `--build-report=json`.
+#[derive(Default)]
+pub struct BuildReport {
+    /// How long each phase of the build took, in order.
+    phases: Vec<(&'static str, Duration)>,
+    /// The external tools that were run, in order.
+    commands: Vec<Invocation>,
+    /// The files written by the build, other than intermediates.
+    artifacts: Vec<PathBuf>,
+}
+
+struct Invocation {
+    /// The program followed by its arguments.
+    argv: Vec<String>,
+    /// The exit code, if the tool ran and exited normally.
+    status: Option<i32>,
+    time: Duration,
+}
+
+impl BuildReport {
+    pub fn phase(&mut self, name: &'static str, time: Duration) {
+        self.phases.push((name, time));
+    }
+
+    pub fn command(&mut self, command: &Command, status: Option<i32>, time: Duration) {
+        let argv = std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+
+        self.commands.push(Invocation { argv, status, time });
+    }
+
+    pub fn artifact(&mut self, path: &Path) {
+        self.artifacts.push(path.to_owned());
+    }
+
+    /// Renders the report, for a build that failed with `errors` errors if
+    /// any. Artifacts are hashed with 64-bit FNV-1a.
+    pub fn json(&self, sources: &SourceMap, errors: usize, total: Duration) -> String {
+        let mut out = String::from("{\n");
+
+        let _ = writeln!(out, "  \"input\": {},", string(&sources.main().name));
+
+        let files = sources
+            .files()
+            .iter()
+            .map(|file| {
+                let path = match file.path {
+                    Some(ref path) => string(&path.to_string_lossy()),
+                    None => "null".to_owned(),
+                };
+
+                format!("{{\"name\": {}, \"path\": {}}}", string(&file.name), path)
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "  \"sources\": {},", list(&files));
+
+        let _ = writeln!(out, "  \"success\": {},", errors == 0);
+        let _ = writeln!(out, "  \"diagnostics\": {{\"errors\": {}}},", errors);
+
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| {
+                let argv = command
+                    .argv
+                    .iter()
+                    .map(|arg| string(arg))
+                    .collect::<Vec<_>>();
+                let status = match command.status {
+                    Some(code) => code.to_string(),
+                    None => "null".to_owned(),
+                };
+
+                format!(
+                    "{{\"argv\": [{}], \"status\": {}, \"time_ms\": {}}}",
+                    argv.join(", "),
+                    status,
+                    millis(command.time)
+                )
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "  \"commands\": {},", list(&commands));
+
+        let artifacts = self
+            .artifacts
+            .iter()
+            .map(|path| {
+                let (size, hash) = match std::fs::read(path) {
+                    Ok(data) => (data.len().to_string(), format!("\"{:016x}\"", fnv1a(&data))),
+                    Err(_) => ("null".to_owned(), "null".to_owned()),
+                };
+
+                format!(
+                    "{{\"path\": {}, \"size\": {}, \"hash\": {}}}",
+                    string(&path.to_string_lossy()),
+                    size,
+                    hash
+                )
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "  \"artifacts\": {},", list(&artifacts));
+
+        let mut phases = self
+            .phases
+            .iter()
+            .map(|(name, time)| format!("{}: {}", string(name), millis(*time)))
+            .collect::<Vec<_>>();
+        phases.push(format!("\"total\": {}", millis(total)));
+        let _ = writeln!(out, "  \"time_ms\": {{{}}}", phases.join(", "));
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// A JSON array of already rendered values, one per line.
+fn list(values: &[String]) -> String {
+    if values.is_empty() {
+        return "[]".to_owned();
+    }
+
+    format!("[\n    {}\n  ]", values.join(",\n    "))
+}
+
+/// A JSON string literal.
+fn string(value: &str) -> String {
+    let mut out = String::from("\"");
+
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            ch if ch.is_control() => {
+                let _ = write!(out, "\\u{:04x}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+fn millis(time: Duration) -> String {
+    format!("{:.3}", time.as_secs_f64() * 1000.0)
+}
+
+/// A hash that, unlike `DefaultHasher`, is stable across Rust releases.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
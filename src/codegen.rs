@@ -1,113 +1,1848 @@
-use crate::parse::{Ast, BinaryExpr, BinaryOp, Call, Expr, ExprKind, Lit};
-use crate::{Report, Reporter, Span, Spanned, WithSpan};
+use crate::debug;
+use crate::format::{Piece, Spec};
+use crate::parse::{
+    Arm, Ast, BinaryExpr, BinaryOp, Block, Builtin, Call, Cast, Expr, ExprKind, FieldExpr, FnDef,
+    Function, If, Index, Let, Lit, Match, Repeat, Slice, StructLit, UnaryExpr, UnaryOp, While,
+};
+use crate::resolve::Items;
+use crate::sema;
+use crate::source::SourceMap;
+use crate::ty::{align_to, Ty};
+use crate::{Failure, Report, Span, Spanned, WithSpan};
 
+use std::collections::HashSet;
 use std::io::Write;
+use std::mem;
 
-pub struct Codegen<W> {
+pub struct Codegen<'a, W> {
     out: W,
+    sources: &'a SourceMap,
+    labels: usize,
+    /// The number of 8-byte values currently pushed onto the stack.
+    depth: usize,
+    /// NUL-terminated strings for the runtime, such as the locations of
+    /// panics.
+    strings: Vec<String>,
+    /// String literals, emitted with their length.
+    strs: Vec<String>,
+    /// Constant integer arrays, emitted into .rodata.
+    arrays: Vec<Vec<i64>>,
+    functions: &'a [Function],
+    /// The symbol of each function, the C name of an extern function.
+    symbols: Vec<String>,
+    vars: Vec<Slot<'a>>,
+    /// (break, continue) labels of the enclosing loops.
+    loops: Vec<(usize, usize)>,
+    /// The label at the end of the function being generated, which `return`
+    /// jumps to.
+    ret: Option<usize>,
+    /// The prefix of the labels of the function being generated, which are
+    /// local to it.
+    scope: String,
+    /// Where the function being generated saved %rbx, if it is called from
+    /// C.
+    saved_rbx: Option<usize>,
+    /// Whether to precede every statement with its source as a comment.
+    comments: bool,
+    /// Whether to call functions of other objects through the PLT.
+    pic: bool,
+    /// Whether to generate `_start`.
+    entry: bool,
+    /// The function `_start` calls instead of the program.
+    entry_fn: Option<String>,
+    /// Whether to check the frame for a canary before returning.
+    stack_protector: bool,
+    /// Whether to check that array indices are in bounds at runtime.
+    bounds_checks: bool,
+    /// Whether to count how many times the program and each label are
+    /// reached, reporting the counts when the program returns.
+    profile: bool,
+    /// The names of the profile counters, as indices into `strings`.
+    profile_counters: Vec<usize>,
+    /// Whether to count how many times each statement runs, writing the
+    /// counts to `out.cov` when the program returns.
+    coverage: bool,
+    /// The line and file of the statement of each coverage counter, as
+    /// indices into `strings`.
+    coverage_counters: Vec<usize>,
+    /// Whether to describe source lines and variables to debuggers.
+    debug: bool,
+    /// How much to optimize the generated code, from 0 to 2.
+    opt_level: u8,
 }
 
-impl<W> Codegen<W>
+/// The syntax of the generated assembly.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Syntax {
+    /// AT&T syntax, `mov $1, %eax`, which the GNU assembler reads by default.
+    #[default]
+    Att,
+}
+
+impl Syntax {
+    /// The syntax called `name`, if it can be generated.
+    pub fn from_name(name: &str) -> Option<Syntax> {
+        match name {
+            "att" => Some(Syntax::Att),
+            _ => None,
+        }
+    }
+}
+
+/// The architecture to generate code for.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    #[default]
+    X86_64,
+}
+
+impl Target {
+    /// The target called `name`, if code can be generated for it.
+    pub fn from_name(name: &str) -> Option<Target> {
+        match name {
+            "x86_64" | "x86-64" => Some(Target::X86_64),
+            _ => None,
+        }
+    }
+}
+
+/// Configures a `Codegen`, see `Codegen::builder`.
+pub struct Builder<'a> {
+    sources: &'a SourceMap,
+    syntax: Syntax,
+    target: Target,
+    comments: bool,
+    pic: bool,
+    entry: bool,
+    entry_fn: Option<String>,
+    stack_protector: bool,
+    bounds_checks: bool,
+    profile: bool,
+    coverage: bool,
+    debug: bool,
+    opt_level: u8,
+}
+
+impl<'a> Builder<'a> {
+    /// The syntax of the generated assembly, AT&T by default.
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// The architecture to generate code for, x86-64 by default.
+    pub fn target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Precedes the code of every statement with its source lines, as
+    /// assembler comments. Disabled by default.
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Calls functions defined outside of the program through the PLT, for
+    /// position independent executables. Disabled by default.
+    pub fn pic(mut self, pic: bool) -> Self {
+        self.pic = pic;
+        self
+    }
+
+    /// Generates `_start`, which calls the program and exits with its
+    /// status. Objects linked into another program don't need it, and
+    /// export the program as `ripc_main` instead. Enabled by default.
+    pub fn entry(mut self, entry: bool) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    /// Makes `_start` call another function rather than the program, for
+    /// startup code that runs first and then calls the program itself, as
+    /// `ripc_main`. Profile and coverage counts are still reported when that
+    /// function returns. `_start` calls the program by default.
+    pub fn entry_fn(mut self, entry_fn: Option<String>) -> Self {
+        self.entry_fn = entry_fn;
+        self
+    }
+
+    /// Stores a random canary at the top of the frame, below the return
+    /// address, and aborts if it was overwritten when the program returns.
+    /// Disabled by default.
+    pub fn stack_protector(mut self, stack_protector: bool) -> Self {
+        self.stack_protector = stack_protector;
+        self
+    }
+
+    /// Checks every index that isn't a constant against the length of the
+    /// array, exiting with the index and its location if it is out of
+    /// bounds. Disabled by default.
+    pub fn bounds_checks(mut self, bounds_checks: bool) -> Self {
+        self.bounds_checks = bounds_checks;
+        self
+    }
+
+    /// Counts how many times the program and each function run, each loop
+    /// iterates and each label is reached, and prints the counts to standard
+    /// error when the program returns. Disabled by default.
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Counts how many times each statement and match arm runs, and writes
+    /// the counts to `out.cov` when the program returns, along with the
+    /// line each starts on. Disabled by default.
+    pub fn coverage(mut self, coverage: bool) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    /// Maps every statement to its source line, and describes the variables
+    /// of the program, so that debuggers can step through the source and
+    /// print variables by name. Disabled by default.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// How much to optimize the generated code. At 2, matches with dense
+    /// arms jump through a table rather than comparing every arm. 0 by
+    /// default.
+    pub fn opt_level(mut self, opt_level: u8) -> Self {
+        self.opt_level = opt_level;
+        self
+    }
+
+    pub fn build<W: Write>(self, out: W) -> Codegen<'a, W> {
+        // the only syntax and target there are, so far
+        let (Syntax::Att, Target::X86_64) = (self.syntax, self.target);
+
+        Codegen {
+            out,
+            sources: self.sources,
+            labels: 0,
+            depth: 0,
+            strings: Vec::new(),
+            strs: Vec::new(),
+            arrays: Vec::new(),
+            functions: &[],
+            symbols: Vec::new(),
+            vars: Vec::new(),
+            loops: Vec::new(),
+            ret: None,
+            scope: String::new(),
+            saved_rbx: None,
+            comments: self.comments,
+            pic: self.pic,
+            entry: self.entry,
+            entry_fn: self.entry_fn,
+            stack_protector: self.stack_protector,
+            bounds_checks: self.bounds_checks,
+            profile: self.profile,
+            profile_counters: Vec::new(),
+            coverage: self.coverage,
+            coverage_counters: Vec::new(),
+            debug: self.debug,
+            opt_level: self.opt_level,
+        }
+    }
+}
+
+impl<'a> Codegen<'a, ()> {
+    pub fn builder(sources: &'a SourceMap) -> Builder<'a> {
+        Builder {
+            sources,
+            syntax: Syntax::default(),
+            target: Target::default(),
+            comments: false,
+            pic: false,
+            entry: true,
+            entry_fn: None,
+            stack_protector: false,
+            bounds_checks: false,
+            profile: false,
+            coverage: false,
+            debug: false,
+            opt_level: 0,
+        }
+    }
+}
+
+impl<'a, W> Codegen<'a, W>
 where
     W: Write,
 {
-    pub fn new(out: W) -> Self {
-        Self { out }
+    pub fn write(mut self, ast: &'a Ast) -> Result<(), Error> {
+        self.functions = &ast.functions;
+
+        let items = Items {
+            modules: &ast.modules,
+            structs: &ast.structs,
+            functions: &ast.functions,
+            prelude: ast.prelude,
+        };
+
+        self.symbols = ast
+            .functions
+            .iter()
+            .map(|func| match func.is_c() {
+                true => func.name.value.clone(),
+                false => symbol(&items.qualify(func.module, &func.name.value)),
+            })
+            .collect();
+
+        self.vars = ast
+            .vars
+            .iter()
+            .map(|var| Slot {
+                offset: 0,
+                ty: &var.ty,
+            })
+            .collect();
+
+        // the variables of functions live in their own frames
+        let locals = ast
+            .defs
+            .iter()
+            .flat_map(|def| def.vars.clone())
+            .collect::<HashSet<_>>();
+        let frame = self.frame((0..ast.vars.len()).filter(|i| !locals.contains(i)));
+
+        self.entry()?;
+        self.prologue(MAIN, frame, self.exports_main())?;
+        self.count("main")?;
+
+        let mut line = 0;
+        for expr in &ast.exprs {
+            if self.comments {
+                line = self.comment(expr.span, line)?;
+            }
+
+            self.loc(expr)?;
+            self.hit(expr)?;
+            self.expr(expr)?;
+        }
+
+        // the value of the final expression is the exit status of the
+        // program, if it is an integer
+        if ast.exprs.last().map(|expr| &expr.ty) != Some(&Ty::Int) {
+            asm!(self, "mov $0, %eax\n\t");
+        }
+
+        self.epilogue()?;
+
+        if self.debug {
+            asm!(self, ".L{}_end:\n", MAIN);
+        }
+
+        for def in &ast.defs {
+            self.function(def)?;
+        }
+
+        if self.debug {
+            asm!(self, ".Letext0:\n");
+        }
+
+        self.data()?;
+
+        if self.profile {
+            let counters = mem::take(&mut self.profile_counters);
+            self.counters("profile", &counters)?;
+        }
+
+        if self.coverage {
+            let counters = mem::take(&mut self.coverage_counters);
+            self.counters("coverage", &counters)?;
+        }
+
+        if self.debug {
+            let var = |i: usize| debug::Var {
+                name: ast.symbols.get(ast.vars[i].name),
+                offset: self.vars[i].offset,
+                ty: self.vars[i].ty,
+            };
+
+            let mut functions = vec![debug::Function {
+                symbol: MAIN,
+                global: self.exports_main(),
+                params: Vec::new(),
+                vars: (0..ast.vars.len())
+                    .filter(|i| !locals.contains(i))
+                    .map(var)
+                    .collect(),
+            }];
+
+            for def in &ast.defs {
+                functions.push(debug::Function {
+                    symbol: &self.symbols[def.func],
+                    global: false,
+                    params: def.params.iter().map(|&i| var(i)).collect(),
+                    vars: def
+                        .vars
+                        .clone()
+                        .filter(|i| !def.params.contains(i))
+                        .map(var)
+                        .collect(),
+                });
+            }
+
+            debug::write(&mut self.out, self.sources, &functions).map_err(Error::io)?;
+        }
+
+        // the stack is never executed, which the linker otherwise assumes
+        // it is for an object that doesn't say so
+        asm!(self, ".section .note.GNU-stack,\"\",@progbits\n");
+
+        Ok(())
+    }
+
+    /// Writes the source lines of a span as comments, skipping lines up to
+    /// `after`, which have already been written. Returns the last line of
+    /// the span.
+    fn comment(&mut self, span: Span, after: usize) -> Result<usize, Error> {
+        let (file, start, _) = self.sources.location(span);
+        let (end, _) = self.sources.end_location(span);
+
+        for line in start.max(after + 1)..=end {
+            asm!(self, "# {}:{}: {}\n\t", file.name, line, file.line(line));
+        }
+
+        Ok(end)
+    }
+
+    /// Whether `ripc_main` is called from outside of this object.
+    fn exports_main(&self) -> bool {
+        !self.entry || self.entry_fn.is_some()
+    }
+
+    fn entry(&mut self) -> Result<(), Error> {
+        asm!(self, ".text\n");
+
+        if self.debug {
+            for (i, file) in self.sources.files().iter().enumerate() {
+                asm!(self, ".file {} \"{}\"\n", i + 1, escape(&file.name));
+            }
+
+            asm!(self, ".Ltext0:\n");
+        }
+
+        asm!(self, ".lcomm .Largc, 8\n");
+        asm!(self, ".lcomm .Largv, 8\n");
+
+        if self.exports_main() {
+            asm!(self, ".global {}\n", MAIN);
+        }
+
+        if !self.entry {
+            return Ok(());
+        }
+
+        asm!(self, ".global _start\n");
+
+        asm!(self, "_start:\n\t");
+        asm!(self, "xor %ebp, %ebp\n\t");
+
+        // the kernel leaves argc on top of the stack, followed by argv
+        asm!(self, "mov (%rsp), %rax\n\t");
+        asm!(self, "mov %rax, .Largc(%rip)\n\t");
+        asm!(self, "lea 8(%rsp), %rax\n\t");
+        asm!(self, "mov %rax, .Largv(%rip)\n\t");
+
+        if self.stack_protector {
+            self.call_extern("ripc_stack_guard_init")?;
+        }
+
+        match self.entry_fn.take() {
+            Some(entry_fn) => self.call_extern(&entry_fn)?,
+            None => asm!(self, "call {}\n\t", MAIN),
+        }
+
+        if self.profile {
+            asm!(self, "mov %eax, %ebx\n\t");
+            asm!(self, "lea .Lprofile(%rip), %rdi\n\t");
+            self.call_extern("ripc_profile_report")?;
+            asm!(self, "mov %ebx, %eax\n\t");
+        }
+
+        if self.coverage {
+            asm!(self, "mov %eax, %ebx\n\t");
+            asm!(self, "lea .Lcoverage(%rip), %rdi\n\t");
+            self.call_extern("ripc_coverage_dump")?;
+            asm!(self, "mov %ebx, %eax\n\t");
+        }
+
+        asm!(self, "mov %eax, %edi\n\t");
+        self.call_extern("exit")?;
+
+        Ok(())
+    }
+
+    /// Lays out variables in a frame, growing down from %rbp, below the
+    /// canary, which an overflowing variable overwrites before it reaches
+    /// the return address. Returns the size of the frame.
+    fn frame(&mut self, vars: impl IntoIterator<Item = usize>) -> usize {
+        let mut frame = if self.stack_protector { 8 } else { 0 };
+
+        for i in vars {
+            let ty = self.vars[i].ty;
+            frame = align_to(frame + ty.size(), ty.align());
+            self.vars[i].offset = frame;
+        }
+
+        frame
+    }
+
+    /// Emits a function defined in the program, whose parameters are
+    /// passed as they are to C functions. Exported functions are visible to
+    /// C, and take and return C strings.
+    fn function(&mut self, def: &FnDef) -> Result<(), Error> {
+        const REGISTERS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+        const VECTOR_REGISTERS: usize = 8;
+
+        let func = &self.functions[def.func];
+        let symbol = self.symbols[def.func].clone();
+        let frame = self.frame(def.vars.clone());
+
+        if func.export {
+            asm!(self, ".global {}\n", symbol);
+        }
+
+        self.prologue(&symbol, frame, func.export)?;
+
+        let (file, line, column) = self.sources.location(func.name.span);
+        let name = format!("fn {} ({}:{}:{})", func.name.value, file.name, line, column);
+        self.count(&name)?;
+
+        let (mut ints, mut floats, mut stack) = (0, 0, 0);
+
+        for &var in &def.params {
+            let Slot { offset, ty } = self.vars[var];
+
+            if *ty == Ty::Float && floats < VECTOR_REGISTERS {
+                asm!(self, "movq %xmm{}, %rax\n\t", floats);
+                floats += 1;
+            } else if *ty != Ty::Float && ints < REGISTERS.len() {
+                asm!(self, "mov %{}, %rax\n\t", REGISTERS[ints]);
+                ints += 1;
+            } else {
+                // above the saved %rbp and the return address
+                asm!(self, "mov {}(%rbp), %rax\n\t", 16 + stack * 8);
+                stack += 1;
+            }
+
+            self.store(ty, &format!("-{}(%rbp)", offset))?;
+        }
+
+        // once every argument is stored, as the conversions clobber the
+        // registers of the rest
+        if func.export {
+            for &var in &def.params {
+                let Slot { offset, ty } = self.vars[var];
+
+                if *ty == Ty::Str {
+                    asm!(self, "mov -{}(%rbp), %rdi\n\t", offset);
+                    self.call_aligned("ripc_str_from_c")?;
+                    asm!(self, "mov %rax, -{}(%rbp)\n\t", offset);
+                }
+            }
+        }
+
+        let ret = self.label();
+        self.ret = Some(ret);
+        self.scope = format!("{}.", symbol);
+
+        match func.ret {
+            Some(WithSpan {
+                value: Ty::Tuple(_),
+                ..
+            }) => self.pair(&def.body)?,
+            _ => self.expr(&def.body)?,
+        }
+
+        self.ret = None;
+        self.scope.clear();
+
+        asm!(self, ".L{}:\n\t", ret);
+        match func.ret.as_ref().map(|ret| &ret.value) {
+            Some(Ty::Float) => asm!(self, "movq %rax, %xmm0\n\t"),
+            Some(Ty::Str) if func.export => {
+                asm!(self, "mov %rax, %rdi\n\t");
+                self.call_aligned("ripc_str_to_c")?;
+            }
+            _ => {}
+        }
+
+        self.epilogue()?;
+
+        if self.debug {
+            asm!(self, ".L{}_end:\n", symbol);
+        }
+
+        Ok(())
+    }
+
+    /// Sets up the frame of a function. A function `called_from_c` saves
+    /// %rbx below its variables, as the generated code uses it as a scratch
+    /// register but C expects it to be preserved.
+    fn prologue(&mut self, symbol: &str, frame: usize, called_from_c: bool) -> Result<(), Error> {
+        asm!(self, "{}:\n\t", symbol);
+        asm!(self, "push %rbp\n\t");
+        asm!(self, "mov %rsp, %rbp\n\t");
+
+        let frame = match called_from_c {
+            true => {
+                let slot = align_to(frame, 8) + 8;
+                self.saved_rbx = Some(slot);
+                slot
+            }
+            false => frame,
+        };
+
+        // reserve the frame for locals up front, so temporaries pushed while
+        // evaluating expressions can't clobber them, keeping %rsp 16-byte aligned
+        let frame = align_to(frame, 16);
+        if frame > 0 {
+            asm!(self, "sub ${}, %rsp\n\t", frame);
+        }
+
+        // %r11 holds no arguments, which are still to be stored
+        if self.stack_protector {
+            asm!(self, "mov ripc_stack_guard(%rip), %r11\n\t");
+            asm!(self, "mov %r11, -8(%rbp)\n\t");
+        }
+
+        if let Some(slot) = self.saved_rbx {
+            asm!(self, "mov %rbx, -{}(%rbp)\n\t", slot);
+        }
+
+        Ok(())
+    }
+
+    fn epilogue(&mut self) -> Result<(), Error> {
+        if let Some(slot) = self.saved_rbx.take() {
+            asm!(self, "mov -{}(%rbp), %rbx\n\t", slot);
+        }
+
+        if self.stack_protector {
+            let ok = self.label();
+            asm!(self, "mov -8(%rbp), %rcx\n\t");
+            asm!(self, "xor ripc_stack_guard(%rip), %rcx\n\t");
+            asm!(self, "je .L{}\n\t", ok);
+            self.call_extern("ripc_stack_chk_fail")?;
+            asm!(self, ".L{}:\n\t", ok);
+        }
+
+        asm!(self, "mov %rbp, %rsp\n\t");
+        asm!(self, "pop %rbp\n\t");
+        asm!(self, "ret\n");
+
+        Ok(())
+    }
+
+    fn data(&mut self) -> Result<(), Error> {
+        // a string is a pointer to its length and the address of its bytes,
+        // which follow, and a literal's address needs a relocation in
+        // position independent code
+        if !self.strs.is_empty() {
+            asm!(self, ".section .data.rel.ro\n");
+        }
+
+        for (i, str) in self.strs.iter().enumerate() {
+            asm!(self, ".p2align 3\n");
+            asm!(self, ".LT{}:\n\t", i);
+            asm!(self, ".quad .LTE{} - .LTB{}\n\t", i, i);
+            asm!(self, ".quad .LTB{}\n", i);
+            asm!(self, ".LTB{}:\n\t", i);
+            asm!(self, ".ascii \"{}\"\n", str);
+            asm!(self, ".LTE{}:\n\t", i);
+            asm!(self, ".byte 0\n");
+        }
+
+        if self.strings.is_empty() && self.arrays.is_empty() {
+            return Ok(());
+        }
+
+        asm!(self, ".section .rodata\n");
+
+        for (i, str) in self.strings.iter().enumerate() {
+            asm!(self, ".LS{}:\n\t", i);
+            asm!(self, ".string \"{}\"\n", str);
+        }
+
+        for (i, array) in self.arrays.iter().enumerate() {
+            asm!(self, ".p2align 2\n");
+            asm!(self, ".LA{}:\n\t", i);
+
+            let values = array.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            asm!(self, ".long {}\n", values.join(", "));
+        }
+
+        Ok(())
+    }
+
+    /// Marks the start of a statement in the line table, if debug info is
+    /// enabled.
+    fn loc(&mut self, stmt: &Expr) -> Result<(), Error> {
+        if !self.debug {
+            return Ok(());
+        }
+
+        let (file, line, column) = self.sources.location(stmt.span);
+        let files = self.sources.files();
+        let i = files.iter().position(|f| f.start == file.start).unwrap();
+        asm!(self, ".loc {} {} {}\n\t", i + 1, line, column);
+
+        Ok(())
+    }
+
+    /// Increments a new profile counter, if profiling is enabled.
+    fn count(&mut self, name: &str) -> Result<(), Error> {
+        if !self.profile {
+            return Ok(());
+        }
+
+        let name = self.string(&escape(name));
+        let offset = self.profile_counters.len() * 16;
+        asm!(self, "incq .Lprofile+{}(%rip)\n\t", offset);
+        self.profile_counters.push(name);
+
+        Ok(())
+    }
+
+    /// Increments a new coverage counter for a statement, if coverage is
+    /// enabled. Labels don't run, and jumps to them would skip the counter.
+    fn hit(&mut self, stmt: &Expr) -> Result<(), Error> {
+        if !self.coverage || matches!(stmt.kind, ExprKind::Label(_)) {
+            return Ok(());
+        }
+
+        let (file, line, _) = self.sources.location(stmt.span);
+        let name = format!("{} {}", line, file.name);
+
+        let name = self.string(&escape(&name));
+        let offset = self.coverage_counters.len() * 16;
+        asm!(self, "incq .Lcoverage+{}(%rip)\n\t", offset);
+        self.coverage_counters.push(name);
+
+        Ok(())
+    }
+
+    /// A table of counters, a count and name each, ending with a null name.
+    /// Names are stored as offsets from themselves, which need no
+    /// relocation when a position independent executable is loaded.
+    fn counters(&mut self, table: &str, names: &[usize]) -> Result<(), Error> {
+        asm!(self, ".data\n");
+        asm!(self, ".p2align 3\n");
+        asm!(self, ".L{}:\n", table);
+
+        for name in names {
+            asm!(self, "\t.quad 0\n");
+            asm!(self, "\t.quad .LS{} - .\n", name);
+        }
+
+        asm!(self, "\t.quad 0\n");
+        asm!(self, "\t.quad 0\n");
+        Ok(())
+    }
+
+    fn push(&mut self, reg: &str) -> Result<(), Error> {
+        asm!(self, "push %{}\n\t", reg);
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    fn pop(&mut self, reg: &str) -> Result<(), Error> {
+        asm!(self, "pop %{}\n\t", reg);
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<(), Error> {
+        match expr.kind {
+            ExprKind::Lit(WithSpan {
+                value: Lit::Num(num),
+                ..
+            }) => asm!(self, "mov ${}, %eax\n\t", num),
+            // floats are kept in %rax as their bits between operations
+            ExprKind::Lit(WithSpan {
+                value: Lit::Float(value),
+                ..
+            }) => asm!(self, "movabs $0x{:x}, %rax\n\t", value.to_bits()),
+            ExprKind::Lit(WithSpan {
+                value: Lit::Bool(value),
+                ..
+            }) => asm!(self, "mov ${}, %eax\n\t", value as u8),
+            ExprKind::Lit(WithSpan {
+                value: Lit::String(ref str),
+                ..
+            }) => {
+                let i = match self.strs.iter().position(|s| **s == **str) {
+                    Some(i) => i,
+                    None => {
+                        self.strs.push(str.to_string());
+                        self.strs.len() - 1
+                    }
+                };
+
+                asm!(self, "lea .LT{}(%rip), %rax\n\t", i);
+            }
+            ExprKind::Var(i) => {
+                let Slot { offset, ty } = self.vars[i];
+                self.load(ty, &format!("-{}(%rbp)", offset))?;
+            }
+            ExprKind::Binary(ref expr) => self.binary_op(expr)?,
+            ExprKind::Unary(ref unary) => self.unary_op(unary, &expr.ty)?,
+            ExprKind::Call(ref call) => self.call(call)?,
+            ExprKind::Match(ref expr) => self.match_expr(expr)?,
+            ExprKind::If(ref expr) => self.if_expr(expr)?,
+            ExprKind::While(ref inner) => self.while_expr(inner, expr.span)?,
+            ExprKind::Break => {
+                let &(brk, _) = self.loops.last().expect("`break` outside of loop");
+                asm!(self, "jmp .L{}\n\t", brk);
+            }
+            ExprKind::Continue => {
+                let &(_, cont) = self.loops.last().expect("`continue` outside of loop");
+                asm!(self, "jmp .L{}\n\t", cont);
+            }
+            ExprKind::Label(ref label) => {
+                asm!(self, ".L.{}{}:\n\t", self.scope, label);
+
+                let (file, line, column) = self.sources.location(expr.span);
+                self.count(&format!("{} ({}:{}:{})", label, file.name, line, column))?;
+            }
+            ExprKind::Goto(ref label) => asm!(self, "jmp .L.{}{}\n\t", self.scope, label),
+            ExprKind::Return(ref value) => {
+                match value {
+                    Some(value) if matches!(value.ty, Ty::Tuple(_)) => self.pair(value)?,
+                    Some(value) => self.expr(value)?,
+                    None => {}
+                }
+
+                let ret = self.ret.expect("`return` outside of function");
+                asm!(self, "jmp .L{}\n\t", ret);
+            }
+            ExprKind::Let(ref expr) => self.let_expr(expr)?,
+            ExprKind::Array(_) | ExprKind::Repeat(_) | ExprKind::Struct(_) | ExprKind::Tuple(_) => {
+                unreachable!("aggregate expression outside of `let`")
+            }
+            ExprKind::Index(ref index) => {
+                self.index(index)?;
+
+                match index.base.ty {
+                    // the bytes of a string are zero extended
+                    Ty::Str => asm!(self, "movzbl (%rax), %eax\n\t"),
+                    _ => self.load(&expr.ty, "(%rax)")?,
+                }
+            }
+            ExprKind::Slice(ref slice) => self.slice(slice)?,
+            ExprKind::Cast(ref cast) => self.cast(cast)?,
+            ExprKind::Block(ref block) => self.block(block, Self::expr)?,
+            ExprKind::SizeOf(ref of) => asm!(self, "mov ${}, %eax\n\t", of.size()),
+            // a returned tuple is only ever in registers
+            ExprKind::Field(ref field) if matches!(field.base.kind, ExprKind::Call(_)) => {
+                self.pair(&field.base)?;
+
+                let Ty::Tuple(ref ty) = field.base.ty else {
+                    unreachable!("field of call returning {}", field.base.ty)
+                };
+
+                let i = ty.fields.iter().position(|f| f.name == field.field.value);
+                if i == Some(1) {
+                    asm!(self, "mov %rdx, %rax\n\t");
+                }
+            }
+            ExprKind::Field(ref field) => {
+                self.field(field)?;
+                self.load(&expr.ty, "(%rax)")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Emits the statements of a block, followed by its value, which is
+    /// evaluated by `value`.
+    fn block(
+        &mut self,
+        block: &Block,
+        value: fn(&mut Self, &Expr) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        for stmt in &block.stmts {
+            self.loc(stmt)?;
+            self.hit(stmt)?;
+            self.expr(stmt)?;
+        }
+
+        if let Some(ref expr) = block.value {
+            self.loc(expr)?;
+            self.hit(expr)?;
+            value(self, expr)?;
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a tuple of up to two scalars into %rax and %rdx, which is
+    /// how functions return them.
+    fn pair(&mut self, expr: &Expr) -> Result<(), Error> {
+        let Ty::Tuple(ref ty) = expr.ty else {
+            unreachable!("pair of {}", expr.ty)
+        };
+
+        match expr.kind {
+            ExprKind::Tuple(ref elems) if elems.len() <= 2 => {
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        self.push("rax")?;
+                    }
+
+                    self.expr(elem)?;
+                }
+
+                if elems.len() == 2 {
+                    asm!(self, "mov %rax, %rdx\n\t");
+                    self.pop("rax")?;
+                }
+            }
+            ExprKind::Call(ref call) => self.call(call)?,
+            ExprKind::Block(ref block) => self.block(block, Self::pair)?,
+            ExprKind::Var(_) | ExprKind::Index(_) | ExprKind::Field(_) | ExprKind::Unary(_)
+                if ty.fields.len() <= 2 =>
+            {
+                self.address(expr)?;
+                asm!(self, "mov %rax, %rsi\n\t");
+
+                if let Some(second) = ty.fields.get(1) {
+                    self.load(&second.ty, &format!("{}(%rsi)", second.offset))?;
+                    asm!(self, "mov %rax, %rdx\n\t");
+                }
+
+                if let Some(first) = ty.fields.first() {
+                    self.load(&first.ty, &format!("{}(%rsi)", first.offset))?;
+                }
+            }
+            _ => {
+                let unsupported = ErrorKind::Unsupported(Unsupported::Tuple);
+                return Err(Error::new(unsupported, expr.span));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads a value of the given type from `addr` into %rax. Arrays,
+    /// structs and tuples evaluate to their address.
+    fn load(&mut self, ty: &Ty, addr: &str) -> Result<(), Error> {
+        match ty {
+            Ty::Int => asm!(self, "mov {}, %eax\n\t", addr),
+            Ty::Bool => asm!(self, "movzbl {}, %eax\n\t", addr),
+            Ty::Float | Ty::Str | Ty::Ptr(_) => asm!(self, "mov {}, %rax\n\t", addr),
+            Ty::Array(..) | Ty::Struct(_) | Ty::Tuple(_) => asm!(self, "lea {}, %rax\n\t", addr),
+            Ty::Void | Ty::Infer => unreachable!("load of {}", ty),
+        }
+
+        Ok(())
+    }
+
+    /// Stores the value of the given type in %rax to `addr`.
+    fn store(&mut self, ty: &Ty, addr: &str) -> Result<(), Error> {
+        match ty {
+            Ty::Int => asm!(self, "mov %eax, {}\n\t", addr),
+            Ty::Bool => asm!(self, "mov %al, {}\n\t", addr),
+            Ty::Float | Ty::Str | Ty::Ptr(_) => asm!(self, "mov %rax, {}\n\t", addr),
+            _ => unreachable!("store of {}", ty),
+        }
+
+        Ok(())
+    }
+
+    /// Computes the address of a place expression into %rax.
+    fn address(&mut self, expr: &Expr) -> Result<(), Error> {
+        match expr.kind {
+            ExprKind::Var(i) => asm!(self, "lea -{}(%rbp), %rax\n\t", self.vars[i].offset),
+            ExprKind::Index(ref index) => self.index(index)?,
+            ExprKind::Field(ref field) => self.field(field)?,
+            ExprKind::Unary(UnaryExpr {
+                op:
+                    WithSpan {
+                        value: UnaryOp::Deref,
+                        ..
+                    },
+                expr: ref ptr,
+            }) => self.expr(ptr)?,
+            _ => unreachable!("address of temporary"),
+        }
+
+        Ok(())
+    }
+
+    fn cast(&mut self, cast: &Cast) -> Result<(), Error> {
+        self.expr(&cast.expr)?;
+
+        match (&cast.expr.ty, &cast.ty.value) {
+            (Ty::Int, Ty::Float) => {
+                asm!(self, "cvtsi2sd %eax, %xmm0\n\t");
+                asm!(self, "movq %xmm0, %rax\n\t");
+            }
+            (Ty::Float, Ty::Int) => {
+                asm!(self, "movq %rax, %xmm0\n\t");
+                asm!(self, "cvttsd2si %xmm0, %eax\n\t");
+            }
+            (Ty::Bool, Ty::Int) => asm!(self, "movzbl %al, %eax\n\t"),
+            (Ty::Bool, Ty::Float) => {
+                asm!(self, "movzbl %al, %eax\n\t");
+                asm!(self, "cvtsi2sd %eax, %xmm0\n\t");
+                asm!(self, "movq %xmm0, %rax\n\t");
+            }
+            (from, to) => debug_assert_eq!(from, to),
+        }
+
+        Ok(())
+    }
+
+    fn unary_op(&mut self, expr: &UnaryExpr, ty: &Ty) -> Result<(), Error> {
+        match expr.op.value {
+            UnaryOp::AddrOf => self.address(&expr.expr)?,
+            UnaryOp::Deref => {
+                self.expr(&expr.expr)?;
+                self.load(ty, "(%rax)")?;
+            }
+            UnaryOp::Neg => {
+                self.expr(&expr.expr)?;
+
+                match ty {
+                    // flip the sign bit
+                    Ty::Float => asm!(self, "btc $63, %rax\n\t"),
+                    _ => asm!(self, "neg %eax\n\t"),
+                }
+            }
+            UnaryOp::Not => {
+                self.expr(&expr.expr)?;
+                asm!(self, "cmp $0, %al\n\t");
+                asm!(self, "sete %al\n\t");
+                asm!(self, "movzbl %al, %eax\n\t");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Computes the address of an array element, or of a byte of a string,
+    /// into %rax.
+    fn index(&mut self, expr: &Index) -> Result<(), Error> {
+        match expr.base.kind {
+            ExprKind::Index(ref base) if expr.base.ty != Ty::Str => self.index(base)?,
+            // arrays evaluate to their address
+            _ => self.expr(&expr.base)?,
+        }
+
+        self.push("rax")?;
+        self.expr(&expr.index)?;
+        asm!(self, "movslq %eax, %rax\n\t");
+
+        let (elem, len) = match expr.base.ty {
+            Ty::Array(ref elem, len) => (elem.size(), len),
+            Ty::Str => {
+                self.pop("rbx")?;
+
+                if self.bounds_checks {
+                    self.str_bounds_check(expr.index.span, "jb")?;
+                }
+
+                asm!(self, "add 8(%rbx), %rax\n\t");
+                return Ok(());
+            }
+            _ => unreachable!("index into non-array"),
+        };
+
+        // constant indices are checked by sema, a negative index is out of
+        // bounds as an unsigned comparison
+        if self.bounds_checks && sema::const_int(&expr.index).is_none() {
+            let location = self.location(expr.index.span);
+
+            let ok = self.label();
+            asm!(self, "cmp ${}, %rax\n\t", len);
+            asm!(self, "jb .L{}\n\t", ok);
+            asm!(self, "mov %eax, %edi\n\t");
+            asm!(self, "mov ${}, %esi\n\t", len);
+            asm!(self, "lea .LS{}(%rip), %rdx\n\t", location);
+            self.call_aligned("ripc_bounds_fail")?;
+            asm!(self, ".L{}:\n\t", ok);
+        }
+
+        asm!(self, "imul ${}, %rax\n\t", elem);
+        self.pop("rbx")?;
+        asm!(self, "add %rbx, %rax\n\t");
+        Ok(())
+    }
+
+    /// Checks the index in %rax against the length of the string in %rbx,
+    /// jumping over the failure with `jump`, `jb` for an index or `jbe` for
+    /// the bound of a slice.
+    fn str_bounds_check(&mut self, span: Span, jump: &str) -> Result<(), Error> {
+        let location = self.location(span);
+
+        // a negative index is out of bounds as an unsigned comparison
+        let ok = self.label();
+        asm!(self, "cmp (%rbx), %rax\n\t");
+        asm!(self, "{} .L{}\n\t", jump, ok);
+        asm!(self, "mov %eax, %edi\n\t");
+        asm!(self, "mov (%rbx), %esi\n\t");
+        asm!(self, "lea .LS{}(%rip), %rdx\n\t", location);
+        self.call_aligned("ripc_str_bounds_fail")?;
+        asm!(self, ".L{}:\n\t", ok);
+
+        Ok(())
+    }
+
+    /// Evaluates a slice of a string into %rax, which points into the bytes
+    /// of the string rather than copying them.
+    fn slice(&mut self, expr: &Slice) -> Result<(), Error> {
+        self.expr(&expr.base)?;
+        self.push("rax")?;
+
+        match expr.start {
+            Some(ref start) => {
+                self.expr(start)?;
+                asm!(self, "movslq %eax, %rax\n\t");
+
+                if self.bounds_checks {
+                    asm!(self, "mov (%rsp), %rbx\n\t");
+                    self.str_bounds_check(start.span, "jbe")?;
+                }
+            }
+            None => asm!(self, "xor %eax, %eax\n\t"),
+        }
+
+        self.push("rax")?;
+
+        let end = match expr.end {
+            Some(ref end) => end,
+            // the length of the string
+            None => {
+                asm!(self, "mov 8(%rsp), %rax\n\t");
+                asm!(self, "mov (%rax), %rax\n\t");
+                return self.slice_call();
+            }
+        };
+
+        self.expr(end)?;
+        asm!(self, "movslq %eax, %rax\n\t");
+
+        if self.bounds_checks {
+            asm!(self, "mov 8(%rsp), %rbx\n\t");
+            self.str_bounds_check(end.span, "jbe")?;
+
+            let location = self.location(end.span);
+            let ok = self.label();
+            asm!(self, "cmp (%rsp), %rax\n\t");
+            asm!(self, "jge .L{}\n\t", ok);
+            asm!(self, "mov (%rsp), %edi\n\t");
+            asm!(self, "mov %eax, %esi\n\t");
+            asm!(self, "lea .LS{}(%rip), %rdx\n\t", location);
+            self.call_aligned("ripc_slice_fail")?;
+            asm!(self, ".L{}:\n\t", ok);
+        }
+
+        self.slice_call()
+    }
+
+    /// Slices the string below the start on the stack, up to the end in %rax.
+    fn slice_call(&mut self) -> Result<(), Error> {
+        asm!(self, "mov %rax, %rdx\n\t");
+        self.pop("rsi")?;
+        self.pop("rdi")?;
+        self.call_aligned("ripc_str_slice")?;
+        Ok(())
+    }
+
+    /// Adds the `file:line:column` of a span to the string table, for the
+    /// runtime to report.
+    fn location(&mut self, span: Span) -> usize {
+        let (file, line, column) = self.sources.location(span);
+        let location = format!("{}:{}:{}", file.name, line, column);
+        self.string(&escape(&location))
+    }
+
+    /// Computes the address of a struct or tuple field into %rax.
+    fn field(&mut self, expr: &FieldExpr) -> Result<(), Error> {
+        self.address(&expr.base)?;
+
+        let offset = match expr.base.ty {
+            Ty::Struct(ref ty) | Ty::Tuple(ref ty) => {
+                ty.field(&expr.field.value).expect("unknown field").offset
+            }
+            _ => unreachable!("field of non-struct"),
+        };
+
+        if offset != 0 {
+            asm!(self, "add ${}, %rax\n\t", offset);
+        }
+
+        Ok(())
+    }
+
+    fn let_expr(&mut self, expr: &Let) -> Result<(), Error> {
+        let Slot { offset, ty } = self.vars[expr.var];
+
+        debug_assert_eq!(*ty, expr.init.ty);
+        self.init(&expr.init, offset)
+    }
+
+    /// Initializes the variable at `-offset(%rbp)`.
+    fn init(&mut self, expr: &Expr, offset: usize) -> Result<(), Error> {
+        match expr.kind {
+            ExprKind::Repeat(ref repeat) => self.repeat(repeat, offset),
+            ExprKind::Array(ref elems) => self.array(elems, offset),
+            ExprKind::Struct(ref lit) => self.struct_lit(lit, offset),
+            ExprKind::Tuple(ref elems) => self.tuple(elems, &expr.ty, offset),
+            ExprKind::Call(_) if matches!(expr.ty, Ty::Tuple(_)) => self.returned(expr, offset),
+            _ => {
+                let unsupported = match expr.ty {
+                    Ty::Array(..) => Some(Unsupported::Array),
+                    Ty::Struct(_) => Some(Unsupported::Struct),
+                    Ty::Tuple(_) => Some(Unsupported::Tuple),
+                    _ => None,
+                };
+
+                if let Some(unsupported) = unsupported {
+                    return Err(Error::new(ErrorKind::Unsupported(unsupported), expr.span));
+                }
+
+                self.expr(expr)?;
+                self.store(&expr.ty, &format!("-{}(%rbp)", offset))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Initializes the array at `-offset(%rbp)` from the elements of a literal.
+    fn array(&mut self, elems: &[Expr], offset: usize) -> Result<(), Error> {
+        // constant integer arrays are copied from .rodata
+        if let Some(values) = const_array(elems) {
+            asm!(self, "lea .LA{}(%rip), %rsi\n\t", self.arrays.len());
+            asm!(self, "lea -{}(%rbp), %rdi\n\t", offset);
+            asm!(self, "mov ${}, %rcx\n\t", values.len() * Ty::Int.size());
+            asm!(self, "rep movsb\n\t");
+            self.arrays.push(values);
+            return Ok(());
+        }
+
+        for (i, elem) in elems.iter().enumerate() {
+            self.init(elem, offset - i * elem.ty.size())?;
+        }
+
+        Ok(())
+    }
+
+    /// Initializes the struct at `-offset(%rbp)` from the fields of a literal.
+    fn struct_lit(&mut self, lit: &StructLit, offset: usize) -> Result<(), Error> {
+        for (name, value) in &lit.fields {
+            let field = lit.ty.field(&name.value).expect("unknown field");
+            self.init(value, offset - field.offset)?;
+        }
+
+        Ok(())
     }
 
-    pub fn write(mut self, ast: &Ast) -> Result<(), Error> {
-        self.entry();
-        self.start_main();
+    /// Initializes the tuple at `-offset(%rbp)` from the elements of a literal.
+    fn tuple(&mut self, elems: &[Expr], ty: &Ty, offset: usize) -> Result<(), Error> {
+        let Ty::Tuple(ref ty) = *ty else {
+            unreachable!("tuple literal of type {}", ty)
+        };
+
+        for (elem, field) in elems.iter().zip(&ty.fields) {
+            self.init(elem, offset - field.offset)?;
+        }
+
+        Ok(())
+    }
+
+    /// Initializes the tuple at `-offset(%rbp)` from a call, which returns it
+    /// in %rax and %rdx.
+    fn returned(&mut self, expr: &Expr, offset: usize) -> Result<(), Error> {
+        let Ty::Tuple(ref ty) = expr.ty else {
+            unreachable!("tuple returned as {}", expr.ty)
+        };
+
+        self.pair(expr)?;
+
+        if let Some(second) = ty.fields.get(1) {
+            asm!(self, "mov %rax, %rcx\n\t");
+            asm!(self, "mov %rdx, %rax\n\t");
+            self.store(&second.ty, &format!("-{}(%rbp)", offset - second.offset))?;
+            asm!(self, "mov %rcx, %rax\n\t");
+        }
+
+        if let Some(first) = ty.fields.first() {
+            self.store(&first.ty, &format!("-{}(%rbp)", offset - first.offset))?;
+        }
+
+        Ok(())
+    }
+
+    /// Fills the array at `-offset(%rbp)` with copies of a value.
+    fn repeat(&mut self, expr: &Repeat, offset: usize) -> Result<(), Error> {
+        if expr.len.value == 0 {
+            return Ok(());
+        }
+
+        let size = expr.value.ty.size();
+
+        // initialize the first element of a nested array, then copy it
+        // forward over the rest one byte at a time, each copy becoming the
+        // source of the next
+        if !expr.value.ty.is_scalar() {
+            self.init(&expr.value, offset)?;
+
+            if expr.len.value > 1 {
+                asm!(self, "lea -{}(%rbp), %rsi\n\t", offset);
+                asm!(self, "lea -{}(%rbp), %rdi\n\t", offset - size);
+                asm!(self, "mov ${}, %rcx\n\t", size * (expr.len.value - 1));
+                asm!(self, "rep movsb\n\t");
+            }
+
+            return Ok(());
+        }
+
+        self.expr(&expr.value)?;
+
+        let start = self.label();
+        asm!(self, "lea -{}(%rbp), %rdi\n\t", offset);
+        asm!(self, "mov ${}, %rcx\n", expr.len.value);
+        asm!(self, ".L{}:\n\t", start);
+        self.store(&expr.value.ty, "(%rdi)")?;
+        asm!(self, "add ${}, %rdi\n\t", size);
+        asm!(self, "dec %rcx\n\t");
+        asm!(self, "jnz .L{}\n\t", start);
+        Ok(())
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels += 1;
+        self.labels
+    }
+
+    fn match_expr(&mut self, expr: &Match) -> Result<(), Error> {
+        self.expr(&expr.scrutinee)?;
+
+        let arms = expr.arms.iter().map(|_| self.label()).collect::<Vec<_>>();
+        let default = self.label();
+        let end = self.label();
+
+        if self.opt_level >= 2 && is_dense(&expr.arms) {
+            self.jump_table(&expr.arms, &arms, default)?;
+        } else {
+            for (arm, label) in expr.arms.iter().zip(&arms) {
+                asm!(self, "cmp ${}, %eax\n\t", arm.value.value);
+                asm!(self, "je .L{}\n\t", label);
+            }
+
+            asm!(self, "jmp .L{}\n", default);
+        }
+
+        for (arm, label) in expr.arms.iter().zip(arms) {
+            asm!(self, ".L{}:\n\t", label);
+            self.hit(&arm.body)?;
+            self.expr(&arm.body)?;
+            asm!(self, "jmp .L{}\n", end);
+        }
+
+        asm!(self, ".L{}:\n\t", default);
+        match expr.default {
+            Some(ref body) => {
+                self.hit(body)?;
+                self.expr(body)?;
+            }
+            None => asm!(self, "mov $0, %eax\n\t"),
+        }
+
+        asm!(self, ".L{}:\n\t", end);
+        Ok(())
+    }
+
+    /// Jumps to the label of the arm matching %eax, through a table of
+    /// relative offsets indexed by the value less the smallest arm.
+    fn jump_table(&mut self, arms: &[Arm], labels: &[usize], default: usize) -> Result<(), Error> {
+        let min = arms.iter().map(|arm| arm.value.value).min().unwrap();
+        let max = arms.iter().map(|arm| arm.value.value).max().unwrap();
+        let table = self.label();
+
+        // values below the smallest arm wrap around to above the largest
+        if min != 0 {
+            asm!(self, "sub ${}, %eax\n\t", min);
+        }
+        asm!(self, "cmp ${}, %eax\n\t", max - min);
+        asm!(self, "ja .L{}\n\t", default);
+        asm!(self, "lea .L{}(%rip), %rdx\n\t", table);
+        asm!(self, "movslq (%rdx,%rax,4), %rax\n\t");
+        asm!(self, "add %rdx, %rax\n\t");
+        asm!(self, "jmp *%rax\n");
+
+        let entries = (min..=max)
+            .map(|value| {
+                let label = arms
+                    .iter()
+                    .position(|arm| arm.value.value == value)
+                    .map_or(default, |i| labels[i]);
+                format!(".long .L{} - .L{}", label, table)
+            })
+            .collect::<Vec<_>>();
+
+        asm!(self, ".L{}:\n\t", table);
+        asm!(self, "{}\n", entries.join("\n\t"));
+        Ok(())
+    }
+
+    fn if_expr(&mut self, expr: &If) -> Result<(), Error> {
+        let otherwise = self.label();
+        let end = self.label();
+
+        self.expr(&expr.cond)?;
+        asm!(self, "cmp $0, %al\n\t");
+        asm!(self, "je .L{}\n\t", otherwise);
+
+        self.expr(&expr.then)?;
+        asm!(self, "jmp .L{}\n", end);
+
+        asm!(self, ".L{}:\n\t", otherwise);
+        if let Some(ref otherwise) = expr.otherwise {
+            self.expr(otherwise)?;
+        }
+
+        asm!(self, ".L{}:\n\t", end);
+        Ok(())
+    }
+
+    fn while_expr(&mut self, expr: &While, span: Span) -> Result<(), Error> {
+        let cond = self.label();
+        let end = self.label();
+        let step = match expr.step {
+            Some(_) => self.label(),
+            None => cond,
+        };
+
+        asm!(self, ".L{}:\n\t", cond);
+        self.expr(&expr.cond)?;
+        asm!(self, "cmp $0, %al\n\t");
+        asm!(self, "je .L{}\n\t", end);
+
+        // counts iterations
+        let (file, line, column) = self.sources.location(span);
+        self.count(&format!("loop ({}:{}:{})", file.name, line, column))?;
+
+        self.loops.push((end, step));
+        self.expr(&expr.body)?;
+        self.loops.pop();
+
+        if let Some(ref next) = expr.step {
+            asm!(self, ".L{}:\n\t", step);
+            self.expr(next)?;
+        }
+
+        asm!(self, "jmp .L{}\n", cond);
+        asm!(self, ".L{}:\n\t", end);
+        Ok(())
+    }
+
+    fn call(&mut self, call: &Call) -> Result<(), Error> {
+        const REGISTERS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+        const VECTOR_REGISTERS: usize = 8;
+
+        if let Some(builtin) = call.builtin {
+            return self.builtin(builtin, call);
+        }
+
+        let i = call.func.expect("unresolved call");
+        let func = &self.functions[i];
+
+        // omitted trailing arguments are filled in with their defaults
+        let defaults = func.params.iter().skip(call.args.len());
+        let defaults = defaults.filter_map(|param| param.default.as_ref());
+        let args = call.args.iter().chain(defaults).collect::<Vec<_>>();
+
+        // floats are passed in %xmm0-7 and everything else in the general
+        // purpose registers, arguments that don't fit in either are passed
+        // on the stack
+        let (mut ints, mut floats) = (0, 0);
+        let mut stack = Vec::new();
+        let mut registers = Vec::new();
+
+        for (i, arg) in args.iter().enumerate() {
+            if arg.ty == Ty::Float && floats < VECTOR_REGISTERS {
+                registers.push((i, format!("xmm{}", floats)));
+                floats += 1;
+            } else if arg.ty != Ty::Float && ints < REGISTERS.len() {
+                registers.push((i, REGISTERS[ints].to_owned()));
+                ints += 1;
+            } else {
+                stack.push(i);
+            }
+        }
+
+        // the ABI requires %rsp to be 16-byte aligned at the call, account for
+        // the argument slots and stack arguments up front
+        let pad = (self.depth + args.len() + stack.len()) % 2 == 1;
+        if pad {
+            asm!(self, "sub $8, %rsp\n\t");
+            self.depth += 1;
+        }
 
-        for expr in &ast.exprs {
-            self.expr(expr)?;
+        // evaluate every argument into a stack slot before loading any
+        // registers, so that calls nested in later arguments can't clobber
+        // the earlier ones
+        for arg in &args {
+            self.expr(arg)?;
+
+            // C functions expect a NUL-terminated string
+            if arg.ty == Ty::Str && func.is_c() {
+                asm!(self, "mov %rax, %rdi\n\t");
+                self.call_aligned("ripc_str_to_c")?;
+            }
+
+            self.push("rax")?;
+        }
+
+        let slot = |i: usize| (args.len() - 1 - i) * 8;
+
+        for (i, reg) in registers {
+            let mov = if reg.starts_with("xmm") {
+                "movq"
+            } else {
+                "mov"
+            };
+            asm!(self, "{} {}(%rsp), %{}\n\t", mov, slot(i), reg);
+        }
+
+        // copy the stack arguments above the slots in reverse order, so that
+        // the first of them ends up on top
+        for (pushed, &i) in stack.iter().rev().enumerate() {
+            asm!(self, "pushq {}(%rsp)\n\t", slot(i) + pushed * 8);
+            self.depth += 1;
+        }
+
+        // variadic functions expect the number of vector registers used in %al
+        if func.variadic {
+            asm!(self, "mov ${}, %eax\n\t", floats);
+        }
+
+        match func.external {
+            true => self.call_extern(&func.name.value)?,
+            false => asm!(self, "call {}\n\t", self.symbols[i]),
+        }
+
+        if let Some(Ty::Float) = func.ret.as_ref().map(|ret| &ret.value) {
+            asm!(self, "movq %xmm0, %rax\n\t");
+        }
+
+        let slots = args.len() + stack.len() + pad as usize;
+        if slots > 0 {
+            asm!(self, "add ${}, %rsp\n\t", slots * 8);
+            self.depth -= slots;
         }
 
-        self.end_main();
+        if let (Some(Ty::Str), true) = (func.ret.as_ref().map(|ret| &ret.value), func.is_c()) {
+            asm!(self, "mov %rax, %rdi\n\t");
+            self.call_aligned("ripc_str_from_c")?;
+        }
 
         Ok(())
     }
 
-    fn entry(&mut self) {
-        asm!(self, ".text\n\t");
-        asm!(self, ".global _start\n");
+    fn builtin(&mut self, builtin: Builtin, call: &Call) -> Result<(), Error> {
+        let args = &call.args;
 
-        asm!(self, "_start:\n\t");
-        asm!(self, "xor %ebp, %ebp\n\t");
-        asm!(self, "call main\n\t");
-        asm!(self, "mov $1, %edi\n\t");
-        asm!(self, "call exit\n");
-    }
+        match builtin {
+            Builtin::Len => match args[0].ty {
+                // not evaluated, like sizeof
+                Ty::Array(_, len) => asm!(self, "mov ${}, %eax\n\t", len),
+                _ => {
+                    self.expr(&args[0])?;
+                    asm!(self, "mov (%rax), %eax\n\t");
+                }
+            },
+            Builtin::Print => self.print(args, false)?,
+            Builtin::Println => self.print(args, true)?,
+            Builtin::Argc => asm!(self, "mov .Largc(%rip), %eax\n\t"),
+            Builtin::Argv => {
+                self.expr(&args[0])?;
+                asm!(self, "movslq %eax, %rax\n\t");
+                asm!(self, "mov .Largv(%rip), %rbx\n\t");
+                asm!(self, "mov (%rbx, %rax, 8), %rdi\n\t");
+                self.call_aligned("ripc_str_from_c")?;
+            }
+            Builtin::ReadInt => self.call_aligned("ripc_read_int")?,
+            Builtin::Assert => {
+                let cond = &args[0];
+                let (file, line, column) = self.sources.location(cond.span);
+                let message = format!(
+                    "[panic]: assertion failed: {}\n --> {}:{}:{}",
+                    self.sources.slice(cond.span).unwrap_or_default(),
+                    file.name,
+                    line,
+                    column
+                );
+                let message = self.string(&escape(&message));
 
-    fn start_main(&mut self) {
-        asm!(self, "main:\n\t");
-        asm!(self, "push %rbp\n\t");
-        asm!(self, "mov %rsp, %rbp\n\t");
-    }
+                self.expr(cond)?;
+                let ok = self.label();
+                asm!(self, "test %al, %al\n\t");
+                asm!(self, "jnz .L{}\n\t", ok);
+                asm!(self, "lea .LS{}(%rip), %rdi\n\t", message);
+                self.call_aligned("ripc_abort")?;
+                asm!(self, ".L{}:\n\t", ok);
+            }
+            Builtin::Panic => {
+                let location = self.location(call.path.span());
 
-    fn end_main(&mut self) {
-        asm!(self, "mov %rbp, %rsp\n\t");
-        asm!(self, "pop %rbp\n\t");
-        asm!(self, "ret\n");
+                self.expr(&args[0])?;
+                asm!(self, "mov %rax, %rdi\n\t");
+                asm!(self, "lea .LS{}(%rip), %rsi\n\t", location);
+                self.call_aligned("ripc_panic")?;
+            }
+        }
+
+        Ok(())
     }
 
-    fn expr(&mut self, expr: &Expr) -> Result<(), Error> {
-        match expr.kind {
-            ExprKind::Lit(WithSpan {
-                value: Lit::Num(num),
-                ..
-            }) => asm!(self, "mov ${}, %eax\n\t", num),
-            ExprKind::Lit(..) => unimplemented!(),
-            ExprKind::Var(i) => asm!(self, "mov -{}(%rbp), %eax\n\t", (i + 1) * 4),
-            ExprKind::Binary(ref expr) => self.binary_op(expr)?,
-            ExprKind::Call(ref call) => self.call(call)?,
+    /// Prints each argument with the runtime helper for its type.
+    fn print(&mut self, args: &[Expr], newline: bool) -> Result<(), Error> {
+        if let Some((format, args)) = args.split_first() {
+            if let Some(pieces) = sema::format_string(format).expect("invalid format string") {
+                return self.printf(&pieces, args, newline);
+            }
+        }
+
+        // evaluate every argument before printing any of them
+        for arg in args {
+            self.expr(arg)?;
+            self.push("rax")?;
+        }
+
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                asm!(self, "mov ${}, %edi\n\t", b' ');
+                self.call_aligned("ripc_print_char")?;
+            }
+
+            let slot = (args.len() - 1 - i) * 8;
+            let helper = match arg.ty {
+                Ty::Int => "ripc_print_int",
+                Ty::Float => "ripc_print_float",
+                Ty::Bool => "ripc_print_bool",
+                Ty::Str => "ripc_print_str",
+                Ty::Ptr(_) => "ripc_print_ptr",
+                _ => unreachable!("print of {}", arg.ty),
+            };
+
+            if arg.ty == Ty::Float {
+                asm!(self, "movq {}(%rsp), %xmm0\n\t", slot);
+            } else {
+                asm!(self, "mov {}(%rsp), %rdi\n\t", slot);
+            }
+
+            self.call_aligned(helper)?;
+        }
+
+        if newline {
+            asm!(self, "mov ${}, %edi\n\t", b'\n');
+            self.call_aligned("ripc_print_char")?;
+        }
+
+        if !args.is_empty() {
+            asm!(self, "add ${}, %rsp\n\t", args.len() * 8);
+            self.depth -= args.len();
         }
 
         Ok(())
     }
 
-    fn call(&mut self, call: &Call) -> Result<(), Error> {
-        const REGISTERS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+    /// Prints the arguments of a format string with a single call to
+    /// `printf`.
+    fn printf(&mut self, pieces: &[Piece], args: &[Expr], newline: bool) -> Result<(), Error> {
+        // %rdi holds the format string
+        const REGISTERS: [&str; 5] = ["rsi", "rdx", "rcx", "r8", "r9"];
+        const VECTOR_REGISTERS: usize = 8;
+
+        let mut format = String::new();
+        let mut args_left = args.iter();
+
+        for piece in pieces {
+            let (spec, arg) = match piece {
+                Piece::Text(text) => {
+                    format.push_str(&text.replace('%', "%%"));
+                    continue;
+                }
+                Piece::Arg(spec, _) => (spec, args_left.next().expect("missing argument")),
+            };
+
+            let conversion = match (spec, &arg.ty) {
+                (Spec::Display, Ty::Int) => "%d".to_owned(),
+                (Spec::Display, Ty::Float) => "%f".to_owned(),
+                (Spec::Display, Ty::Bool) => "%s".to_owned(),
+                // the length of the string, then the address of its bytes
+                (Spec::Display, Ty::Str) => "%.*s".to_owned(),
+                (Spec::Display, Ty::Ptr(_)) => "%p".to_owned(),
+                (Spec::Hex, _) => "%x".to_owned(),
+                (Spec::Char, _) => "%c".to_owned(),
+                (Spec::Precision(digits), _) => format!("%.{}f", digits),
+                (spec, ty) => unreachable!("{} of {}", spec, ty),
+            };
+
+            format.push_str(&conversion);
+        }
+
+        if newline {
+            format.push_str("\\n");
+        }
+
+        // whether each value passed to printf is a float, a string being
+        // passed as two values
+        let values = args
+            .iter()
+            .flat_map(|arg| match arg.ty {
+                Ty::Str => &[false, false][..],
+                Ty::Float => &[true][..],
+                _ => &[false][..],
+            })
+            .copied()
+            .collect::<Vec<_>>();
+
+        let (mut ints, mut floats) = (0, 0);
+        let mut stack = Vec::new();
+        let mut registers = Vec::new();
+
+        for (i, &float) in values.iter().enumerate() {
+            if float && floats < VECTOR_REGISTERS {
+                registers.push((i, format!("xmm{}", floats)));
+                floats += 1;
+            } else if !float && ints < REGISTERS.len() {
+                registers.push((i, REGISTERS[ints].to_owned()));
+                ints += 1;
+            } else {
+                stack.push(i);
+            }
+        }
 
-        for i in 1..call.args.len() {
-            asm!(self, "push %{}\n\t", REGISTERS[i]);
+        let pad = (self.depth + values.len() + stack.len()) % 2 == 1;
+        if pad {
+            asm!(self, "sub $8, %rsp\n\t");
+            self.depth += 1;
         }
 
-        for arg in &call.args {
+        for arg in args {
             self.expr(arg)?;
-            asm!(self, "push %rax\n\t");
+
+            match arg.ty {
+                Ty::Bool => {
+                    let (t, f) = (self.string("true"), self.string("false"));
+                    asm!(self, "test %al, %al\n\t");
+                    asm!(self, "lea .LS{}(%rip), %rax\n\t", f);
+                    asm!(self, "lea .LS{}(%rip), %rcx\n\t", t);
+                    asm!(self, "cmovnz %rcx, %rax\n\t");
+                }
+                Ty::Str => {
+                    asm!(self, "mov %rax, %rbx\n\t");
+                    asm!(self, "mov (%rbx), %rax\n\t");
+                    self.push("rax")?;
+                    asm!(self, "mov 8(%rbx), %rax\n\t");
+                }
+                _ => {}
+            }
+
+            self.push("rax")?;
+        }
+
+        let slot = |i: usize| (values.len() - 1 - i) * 8;
+
+        for (i, reg) in registers {
+            let mov = if reg.starts_with("xmm") {
+                "movq"
+            } else {
+                "mov"
+            };
+            asm!(self, "{} {}(%rsp), %{}\n\t", mov, slot(i), reg);
         }
 
-        for i in 0..call.args.len() {
-            asm!(self, "pop %{}\n\t", REGISTERS[i]);
+        for (pushed, &i) in stack.iter().rev().enumerate() {
+            asm!(self, "pushq {}(%rsp)\n\t", slot(i) + pushed * 8);
+            self.depth += 1;
         }
 
-        asm!(self, "mov $0, %eax\n\t");
-        asm!(self, "call {}\n\t", call.name);
+        let format = self.string(&format);
+        asm!(self, "lea .LS{}(%rip), %rdi\n\t", format);
+        asm!(self, "mov ${}, %eax\n\t", floats);
+        self.call_extern("printf")?;
 
-        for i in 1..call.args.len() {
-            asm!(self, "pop %{}\n\t", REGISTERS[i]);
+        let slots = values.len() + stack.len() + pad as usize;
+        if slots > 0 {
+            asm!(self, "add ${}, %rsp\n\t", slots * 8);
+            self.depth -= slots;
         }
 
         Ok(())
     }
 
+    /// Adds a string to .rodata, returning its label.
+    fn string(&mut self, str: &str) -> usize {
+        match self.strings.iter().position(|s| s == str) {
+            Some(i) => i,
+            None => {
+                self.strings.push(str.to_owned());
+                self.strings.len() - 1
+            }
+        }
+    }
+
     fn binary_op(&mut self, expr: &BinaryExpr) -> Result<(), Error> {
+        if let (BinaryOp::Assign, ExprKind::Tuple(ref places)) = (expr.op.value, &expr.left.kind) {
+            return self.destructure(places, &expr.right);
+        }
+
         if let BinaryOp::Assign = expr.op.value {
             self.expr(&expr.right)?;
 
             match expr.left.kind {
                 ExprKind::Var(i) => {
-                    asm!(self, "mov %eax, -{}(%rbp)\n\t", (i + 1) * 4)
+                    let Slot { offset, ty } = self.vars[i];
+                    self.store(ty, &format!("-{}(%rbp)", offset))?;
                 }
                 _ => {
-                    return Err(Error::new(ErrorKind::ExpectedIdent, expr.left.span));
+                    self.push("rax")?;
+                    self.address(&expr.left)?;
+                    asm!(self, "mov %rax, %rdi\n\t");
+                    self.pop("rax")?;
+                    self.store(&expr.left.ty, "(%rdi)")?;
                 }
             }
 
             return Ok(());
         }
 
+        if expr.op.value.is_comparison() {
+            return self.compare(expr);
+        }
+
+        if expr.op.value.is_logical() {
+            return self.logical(expr);
+        }
+
+        if let (Ty::Ptr(_), _) | (_, Ty::Ptr(_)) = (&expr.left.ty, &expr.right.ty) {
+            return self.pointer_op(expr);
+        }
+
+        if expr.left.ty == Ty::Float {
+            return self.float_op(expr);
+        }
+
+        if expr.left.ty == Ty::Str {
+            return self.concat(expr);
+        }
+
         let op = match expr.op.value {
             BinaryOp::Sub => "sub",
             BinaryOp::Add => "add",
@@ -117,18 +1852,24 @@ where
         };
 
         self.expr(&expr.left)?;
-        asm!(self, "push %rax\n\t");
+        self.push("rax")?;
         self.expr(&expr.right)?;
 
         match expr.op.value {
             BinaryOp::Div => {
                 asm!(self, "mov %eax, %ebx\n\t");
-                asm!(self, "pop %rax\n\t");
-                asm!(self, "mov $0, %edx\n\t");
+                self.pop("rax")?;
+                asm!(self, "cltd\n\t");
                 asm!(self, "idiv %ebx\n\t");
             }
+            // not commutative, the left operand must end up in %eax
+            BinaryOp::Sub => {
+                asm!(self, "mov %eax, %ebx\n\t");
+                self.pop("rax")?;
+                asm!(self, "sub %ebx, %eax\n\t");
+            }
             _ => {
-                asm!(self, "pop %rbx\n\t");
+                self.pop("rbx")?;
                 asm!(self, "{} %ebx, %eax\n\t", op);
             }
         }
@@ -136,6 +1877,270 @@ where
         Ok(())
     }
 
+    /// Assigns each element of a tuple to a place, `x, y = pair`.
+    ///
+    /// All of the elements are pushed before any place is assigned, so that
+    /// `a, b = (b, a)` swaps.
+    fn destructure(&mut self, places: &[Expr], value: &Expr) -> Result<(), Error> {
+        match value.kind {
+            ExprKind::Tuple(ref elems) => {
+                for elem in elems {
+                    self.expr(elem)?;
+                    self.push("rax")?;
+                }
+            }
+            ExprKind::Var(_) | ExprKind::Index(_) | ExprKind::Field(_) | ExprKind::Unary(_) => {
+                let Ty::Tuple(ref ty) = value.ty else {
+                    unreachable!("destructuring {}", value.ty)
+                };
+
+                self.address(value)?;
+                asm!(self, "mov %rax, %rsi\n\t");
+
+                for field in &ty.fields {
+                    self.load(&field.ty, &format!("{}(%rsi)", field.offset))?;
+                    self.push("rax")?;
+                }
+            }
+            // returned tuples, in %rax and %rdx
+            _ => {
+                self.pair(value)?;
+                self.push("rax")?;
+
+                if places.len() == 2 {
+                    self.push("rdx")?;
+                }
+            }
+        }
+
+        let n = places.len();
+        for (i, place) in places.iter().enumerate() {
+            let slot = format!("{}(%rsp)", (n - 1 - i) * 8);
+
+            match place.kind {
+                ExprKind::Var(v) => {
+                    let Slot { offset, ty } = self.vars[v];
+                    asm!(self, "mov {}, %rax\n\t", slot);
+                    self.store(ty, &format!("-{}(%rbp)", offset))?;
+                }
+                _ => {
+                    self.address(place)?;
+                    asm!(self, "mov %rax, %rdi\n\t");
+                    asm!(self, "mov {}, %rax\n\t", slot);
+                    self.store(&place.ty, "(%rdi)")?;
+                }
+            }
+        }
+
+        asm!(self, "add ${}, %rsp\n\t", n * 8);
+        self.depth -= n;
+
+        Ok(())
+    }
+
+    /// Evaluates `&&` or `||`, jumping over the right operand when the left
+    /// one decides the result, which is then already in %al.
+    fn logical(&mut self, expr: &BinaryExpr) -> Result<(), Error> {
+        let end = self.label();
+
+        self.expr(&expr.left)?;
+        asm!(self, "cmp $0, %al\n\t");
+
+        match expr.op.value {
+            BinaryOp::And => asm!(self, "je .L{}\n\t", end),
+            _ => asm!(self, "jne .L{}\n\t", end),
+        }
+
+        self.expr(&expr.right)?;
+        asm!(self, ".L{}:\n\t", end);
+        Ok(())
+    }
+
+    /// Compares the operands, producing a boolean.
+    fn compare(&mut self, expr: &BinaryExpr) -> Result<(), Error> {
+        self.expr(&expr.left)?;
+        self.push("rax")?;
+        self.expr(&expr.right)?;
+
+        // (signed, unsigned) condition codes
+        let (signed, unsigned) = match expr.op.value {
+            BinaryOp::Eq => ("e", "e"),
+            BinaryOp::Ne => ("ne", "ne"),
+            BinaryOp::Lt => ("l", "b"),
+            BinaryOp::Le => ("le", "be"),
+            BinaryOp::Gt => ("g", "a"),
+            BinaryOp::Ge => ("ge", "ae"),
+            _ => return Err(Error::new(ErrorKind::InvalidOperator, expr.op.span)),
+        };
+
+        match expr.left.ty {
+            Ty::Float => {
+                asm!(self, "movq %rax, %xmm1\n\t");
+                self.pop("rax")?;
+                asm!(self, "movq %rax, %xmm0\n\t");
+
+                // unordered comparisons (against NaN) set ZF, PF and CF, so
+                // only `above` and explicit parity checks are false for NaN,
+                // `<` and `<=` are flipped to use them
+                match expr.op.value {
+                    BinaryOp::Lt | BinaryOp::Le => {
+                        asm!(self, "ucomisd %xmm0, %xmm1\n\t");
+                        let cond = if let BinaryOp::Lt = expr.op.value {
+                            "a"
+                        } else {
+                            "ae"
+                        };
+                        asm!(self, "set{} %al\n\t", cond);
+                    }
+                    BinaryOp::Eq => {
+                        asm!(self, "ucomisd %xmm1, %xmm0\n\t");
+                        asm!(self, "sete %al\n\t");
+                        asm!(self, "setnp %bl\n\t");
+                        asm!(self, "and %bl, %al\n\t");
+                    }
+                    BinaryOp::Ne => {
+                        asm!(self, "ucomisd %xmm1, %xmm0\n\t");
+                        asm!(self, "setne %al\n\t");
+                        asm!(self, "setp %bl\n\t");
+                        asm!(self, "or %bl, %al\n\t");
+                    }
+                    _ => {
+                        asm!(self, "ucomisd %xmm1, %xmm0\n\t");
+                        asm!(self, "set{} %al\n\t", unsigned);
+                    }
+                }
+            }
+            Ty::Str => {
+                asm!(self, "mov %rax, %rsi\n\t");
+                self.pop("rdi")?;
+                self.call_aligned("ripc_str_cmp")?;
+                asm!(self, "cmp $0, %eax\n\t");
+                asm!(self, "set{} %al\n\t", signed);
+            }
+            Ty::Ptr(_) => {
+                asm!(self, "mov %rax, %rbx\n\t");
+                self.pop("rax")?;
+                asm!(self, "cmp %rbx, %rax\n\t");
+                asm!(self, "set{} %al\n\t", unsigned);
+            }
+            _ => {
+                asm!(self, "mov %eax, %ebx\n\t");
+                self.pop("rax")?;
+                asm!(self, "cmp %ebx, %eax\n\t");
+                asm!(self, "set{} %al\n\t", signed);
+            }
+        }
+
+        asm!(self, "movzbl %al, %eax\n\t");
+        Ok(())
+    }
+
+    fn concat(&mut self, expr: &BinaryExpr) -> Result<(), Error> {
+        self.expr(&expr.left)?;
+        self.push("rax")?;
+        self.expr(&expr.right)?;
+        asm!(self, "mov %rax, %rsi\n\t");
+        self.pop("rdi")?;
+
+        self.call_aligned("ripc_concat")?;
+        Ok(())
+    }
+
+    /// Calls a function defined outside of the program.
+    fn call_extern(&mut self, name: &str) -> Result<(), Error> {
+        if self.pic {
+            asm!(self, "call {}@PLT\n\t", name);
+        } else {
+            asm!(self, "call {}\n\t", name);
+        }
+
+        Ok(())
+    }
+
+    /// Calls a function with arguments that have already been loaded into
+    /// registers, keeping %rsp 16-byte aligned.
+    fn call_aligned(&mut self, name: &str) -> Result<(), Error> {
+        let pad = self.depth % 2 == 1;
+        if pad {
+            asm!(self, "sub $8, %rsp\n\t");
+        }
+
+        self.call_extern(name)?;
+
+        if pad {
+            asm!(self, "add $8, %rsp\n\t");
+        }
+
+        Ok(())
+    }
+
+    fn float_op(&mut self, expr: &BinaryExpr) -> Result<(), Error> {
+        let op = match expr.op.value {
+            BinaryOp::Sub => "subsd",
+            BinaryOp::Add => "addsd",
+            BinaryOp::Mul => "mulsd",
+            BinaryOp::Div => "divsd",
+            _ => return Err(Error::new(ErrorKind::InvalidOperator, expr.op.span)),
+        };
+
+        self.expr(&expr.left)?;
+        self.push("rax")?;
+        self.expr(&expr.right)?;
+        asm!(self, "movq %rax, %xmm1\n\t");
+        self.pop("rax")?;
+        asm!(self, "movq %rax, %xmm0\n\t");
+        asm!(self, "{} %xmm1, %xmm0\n\t", op);
+        asm!(self, "movq %xmm0, %rax\n\t");
+        Ok(())
+    }
+
+    /// Pointer offsets and differences, scaled by the size of the pointee.
+    fn pointer_op(&mut self, expr: &BinaryExpr) -> Result<(), Error> {
+        let pointee = |ty: &Ty| match ty {
+            Ty::Ptr(ty) => Some(ty.size()),
+            _ => None,
+        };
+
+        let (left, right) = (pointee(&expr.left.ty), pointee(&expr.right.ty));
+
+        self.expr(&expr.left)?;
+        if let (None, Some(size)) = (left, right) {
+            self.scale(size)?;
+        }
+
+        self.push("rax")?;
+        self.expr(&expr.right)?;
+        if let (Some(size), None) = (left, right) {
+            self.scale(size)?;
+        }
+
+        asm!(self, "mov %rax, %rbx\n\t");
+        self.pop("rax")?;
+
+        match expr.op.value {
+            BinaryOp::Add => asm!(self, "add %rbx, %rax\n\t"),
+            BinaryOp::Sub => asm!(self, "sub %rbx, %rax\n\t"),
+            _ => return Err(Error::new(ErrorKind::InvalidOperator, expr.op.span)),
+        }
+
+        // the difference of two pointers is in elements
+        if let (Some(size), Some(_)) = (left, right) {
+            asm!(self, "cqo\n\t");
+            asm!(self, "mov ${}, %rbx\n\t", size);
+            asm!(self, "idiv %rbx\n\t");
+        }
+
+        Ok(())
+    }
+
+    /// Sign-extends the integer in %eax and multiplies it by `size`.
+    fn scale(&mut self, size: usize) -> Result<(), Error> {
+        asm!(self, "movslq %eax, %rax\n\t");
+        asm!(self, "imul ${}, %rax\n\t", size);
+
+        Ok(())
+    }
+
     // fn string(&mut self, str: &str) -> Result<(), Error> {
     //     asm!(self, "\t.data\n");
     //     asm!(self, ".mydata:\n\nt");
@@ -154,6 +2159,69 @@ where
     // }
 }
 
+/// The frame slot of a variable.
+#[derive(Clone, Copy)]
+struct Slot<'a> {
+    /// The offset below %rbp.
+    offset: usize,
+    ty: &'a Ty,
+}
+
+/// Whether the arms of a match cover enough of the values between the
+/// smallest and largest of them to jump through a table.
+fn is_dense(arms: &[Arm]) -> bool {
+    const MIN_ARMS: usize = 4;
+
+    let values = arms.iter().map(|arm| arm.value.value);
+    match (values.clone().min(), values.max()) {
+        (Some(min), Some(max)) => arms.len() >= MIN_ARMS && max - min < 2 * arms.len(),
+        _ => false,
+    }
+}
+
+/// Flattens an array literal whose elements are all integer constants.
+fn const_array(elems: &[Expr]) -> Option<Vec<i64>> {
+    let mut values = Vec::new();
+
+    for elem in elems {
+        match elem.kind {
+            ExprKind::Array(ref elems) => values.extend(const_array(elems)?),
+            _ => values.push(sema::const_int(elem)?),
+        }
+    }
+
+    Some(values)
+}
+
+/// Escapes text for use in a `.string` directive.
+pub fn escape(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+
+    for ch in str.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// The symbol of the top-level code of the program, called by `_start`, or
+/// by whatever the object is linked into. It is named like the helpers of the
+/// runtime, unlike the functions of the program, see `symbol`.
+const MAIN: &str = "ripc_main";
+
+/// The symbol of a function defined in the program, its path prefixed with
+/// `ripc.`, `ripc.math.abs`, which can't collide with C functions or the
+/// `ripc_` symbols of the runtime.
+fn symbol(path: &str) -> String {
+    format!("ripc.{}", path.replace("::", "."))
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Error {
     pub kind: ErrorKind,
@@ -164,6 +2232,16 @@ impl Error {
     fn new(kind: ErrorKind, span: Span) -> Self {
         Self { kind, span }
     }
+
+    /// An error writing the output, which isn't tied to any source.
+    pub fn io(err: std::io::Error) -> Self {
+        Self::new(ErrorKind::Io(err.kind()), Span::EOF)
+    }
+
+    /// The assembler or linker exiting with an error.
+    pub fn tool(tool: &'static str) -> Self {
+        Self::new(ErrorKind::Tool(tool), Span::EOF)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -171,6 +2249,44 @@ pub enum ErrorKind {
     ExpectedIntExpr,
     ExpectedIdent,
     InvalidOperator,
+    Unsupported(Unsupported),
+    Io(std::io::ErrorKind),
+    Tool(&'static str),
+}
+
+/// A construct that passes type checking, but that codegen can't compile
+/// yet.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Unsupported {
+    /// Initializing a variable from an existing array, rather than a
+    /// literal.
+    Array,
+    /// Initializing a variable from an existing struct, rather than a
+    /// literal.
+    Struct,
+    /// Initializing a variable from an existing tuple, or returning or
+    /// destructuring a tuple that is neither a literal, a call nor stored in
+    /// a variable.
+    Tuple,
+}
+
+impl Unsupported {
+    fn name(&self) -> &'static str {
+        match self {
+            Unsupported::Array => "Copying an array",
+            Unsupported::Struct => "Copying a struct",
+            Unsupported::Tuple => "Copying a tuple",
+        }
+    }
+
+    /// What to write instead.
+    fn instead(&self) -> &'static str {
+        match self {
+            Unsupported::Array | Unsupported::Struct | Unsupported::Tuple => {
+                "take a pointer to it with '&' instead"
+            }
+        }
+    }
 }
 
 impl Spanned for Error {
@@ -179,20 +2295,50 @@ impl Spanned for Error {
     }
 }
 
-impl<W: Write> Report<W> for Error {
-    fn report(&self, f: &mut Reporter<'_, W>) -> std::io::Result<()> {
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::ExpectedIntExpr => "E0601",
+            ErrorKind::ExpectedIdent => "E0602",
+            ErrorKind::InvalidOperator => "E0603",
+            ErrorKind::Unsupported(..) => "E0604",
+            ErrorKind::Io(..) => "E0605",
+            ErrorKind::Tool(..) => "E0606",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        match self.kind {
+            ErrorKind::ExpectedIntExpr => "Expected integer expression".to_owned(),
+            ErrorKind::ExpectedIdent => "Expected identifier".to_owned(),
+            ErrorKind::InvalidOperator => "Invalid operator".to_owned(),
+            ErrorKind::Unsupported(unsupported) => {
+                format!("{} is not supported yet", unsupported.name())
+            }
+            ErrorKind::Io(kind) => format!("Failed to write output: {}", kind),
+            ErrorKind::Tool(tool) => format!("'{}' failed", tool),
+        }
+    }
+
+    fn note(&self) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::Unsupported(unsupported) => Some(unsupported.instead()),
+            _ => None,
+        }
+    }
+
+    fn failure(&self) -> Failure {
         match self.kind {
-            ErrorKind::ExpectedIntExpr => write!(f.out, "Expected integer expression"),
-            ErrorKind::ExpectedIdent => write!(f.out, "Expected identifier"),
-            ErrorKind::InvalidOperator => write!(f.out, "Invalid operator"),
+            ErrorKind::Io(_) | ErrorKind::Tool(_) => Failure::Environment,
+            _ => Failure::Codegen,
         }
     }
 }
 
 macro_rules! _asm {
     ($self:ident, $($tt:tt)*) => {
-        std::write!($self.out, $($tt)*).expect("failed to write output")
+        std::write!($self.out, $($tt)*).map_err(Error::io)?
     }
 }
 
-pub(self) use _asm as asm;
+use _asm as asm;
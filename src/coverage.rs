@@ -0,0 +1,72 @@
+use crate::Failure;
+
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+
+/// Prints the source files of a program compiled with
+/// `--instrument=coverage`, annotated with the counts it wrote to `path`.
+///
+/// Each line is annotated with the highest count of the statements starting
+/// on it, `#####` if none of them ran, or `-` if no statement starts on it.
+pub fn report(path: &str) -> ! {
+    let data = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("failed to read '{}': {}", path, err);
+        Failure::Environment.exit()
+    });
+
+    // the count of each line of each file, in the order files first appear
+    let mut files: Vec<(&str, BTreeMap<usize, u64>)> = Vec::new();
+
+    for entry in data.lines() {
+        let (count, line, file) = parse(entry).unwrap_or_else(|| {
+            eprintln!("invalid coverage data in '{}': '{}'", path, entry);
+            Failure::Environment.exit()
+        });
+
+        let lines = match files.iter().position(|(name, _)| *name == file) {
+            Some(i) => &mut files[i].1,
+            None => {
+                files.push((file, BTreeMap::new()));
+                &mut files.last_mut().unwrap().1
+            }
+        };
+
+        let hits = lines.entry(line).or_insert(0);
+        *hits = (*hits).max(count);
+    }
+
+    let mut out = BufWriter::new(std::io::stdout().lock());
+
+    for (name, lines) in files {
+        let source = std::fs::read_to_string(name).unwrap_or_else(|err| {
+            eprintln!("failed to read '{}': {}", name, err);
+            Failure::Environment.exit()
+        });
+
+        let source = source.strip_prefix('\u{feff}').unwrap_or(&source);
+
+        let _ = writeln!(out, "{:>9}:{:>5}:Source:{}", "-", 0, name);
+        for (i, text) in source.lines().enumerate() {
+            let count = match lines.get(&(i + 1)) {
+                Some(0) => "#####".to_owned(),
+                Some(count) => count.to_string(),
+                None => "-".to_owned(),
+            };
+
+            let _ = writeln!(out, "{:>9}:{:>5}:{}", count, i + 1, text);
+        }
+    }
+
+    let _ = out.flush();
+    std::process::exit(0)
+}
+
+/// Parses a line of coverage data, `<count> <line> <file>`.
+fn parse(entry: &str) -> Option<(u64, usize, &str)> {
+    let mut parts = entry.splitn(3, ' ');
+    let count = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+
+    Some((count, line, file))
+}
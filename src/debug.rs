@@ -0,0 +1,340 @@
+use crate::codegen::escape;
+use crate::source::SourceMap;
+use crate::ty::Ty;
+
+use std::io::{self, Write};
+
+/// A variable in the frame of a function.
+pub struct Var<'a> {
+    pub name: &'a str,
+    /// The offset below %rbp.
+    pub offset: usize,
+    pub ty: &'a Ty,
+}
+
+/// A function defined in the program, `ripc_main` or one of the program's
+/// own.
+pub struct Function<'a> {
+    pub symbol: &'a str,
+    /// Whether the symbol is visible outside of the object.
+    pub global: bool,
+    /// The variables the arguments are stored in, in order.
+    pub params: Vec<Var<'a>>,
+    /// The other variables in the frame of the function.
+    pub vars: Vec<Var<'a>>,
+}
+
+/// Writes DWARF sections describing `ripc_main`, the functions of the
+/// program, and the parameters and variables in their frames, so that
+/// debuggers can print them by name.
+///
+/// The line table is generated by the assembler from the `.loc` directives
+/// in the code, and the code is expected to be delimited by the `.Ltext0`
+/// and `.Letext0` labels, and each function to end at `.L<symbol>_end`.
+pub fn write<W: Write>(
+    out: &mut W,
+    sources: &SourceMap,
+    functions: &[Function<'_>],
+) -> io::Result<()> {
+    let mut types = Vec::new();
+    for func in functions {
+        for var in func.params.iter().chain(&func.vars) {
+            register(&mut types, var.ty);
+        }
+    }
+
+    abbrevs(out)?;
+
+    writeln!(out, ".section .debug_info,\"\",@progbits")?;
+    writeln!(out, ".Ldebug_info0:")?;
+    writeln!(out, "\t.long .Ldebug_info_end - .Ldebug_info_start")?;
+    writeln!(out, ".Ldebug_info_start:")?;
+    writeln!(out, "\t.value 4")?;
+    writeln!(out, "\t.long .Ldebug_abbrev0")?;
+    writeln!(out, "\t.byte 8")?;
+
+    let dir = std::env::current_dir().unwrap_or_default();
+    writeln!(out, "\t.uleb128 {}", Abbrev::CompileUnit as u8)?;
+    writeln!(out, "\t.string \"ripc\"")?;
+    // C, as debuggers print its types the way ripc writes them
+    writeln!(out, "\t.value 0xc")?;
+    writeln!(out, "\t.string \"{}\"", escape(&sources.main().name))?;
+    writeln!(out, "\t.string \"{}\"", escape(&dir.to_string_lossy()))?;
+    writeln!(out, "\t.quad .Ltext0")?;
+    writeln!(out, "\t.quad .Letext0 - .Ltext0")?;
+    writeln!(out, "\t.long .Ldebug_line0")?;
+
+    for func in functions {
+        let abbrev = match func.global {
+            true => Abbrev::Subprogram,
+            false => Abbrev::StaticSubprogram,
+        };
+
+        writeln!(out, "\t.uleb128 {}", abbrev as u8)?;
+        writeln!(out, "\t.string \"{}\"", func.symbol)?;
+        writeln!(out, "\t.quad {}", func.symbol)?;
+        writeln!(out, "\t.quad .L{}_end - {}", func.symbol, func.symbol)?;
+        // the frame base is %rbp, DW_OP_breg6 0
+        writeln!(out, "\t.uleb128 2")?;
+        writeln!(out, "\t.byte 0x76, 0")?;
+
+        let params = func.params.iter().map(|var| (Abbrev::Param, var));
+        let vars = func.vars.iter().map(|var| (Abbrev::Variable, var));
+
+        for (abbrev, var) in params.chain(vars) {
+            let offset = -(var.offset as i64);
+
+            writeln!(out, "\t.uleb128 {}", abbrev as u8)?;
+            writeln!(out, "\t.string \"{}\"", escape(var.name))?;
+            let ty = index(&types, var.ty);
+            writeln!(out, "\t.long .Ldebug_ty{} - .Ldebug_info0", ty)?;
+            // DW_OP_fbreg offset
+            writeln!(out, "\t.uleb128 {}", 1 + sleb128_len(offset))?;
+            writeln!(out, "\t.byte 0x91")?;
+            writeln!(out, "\t.sleb128 {}", offset)?;
+        }
+
+        writeln!(out, "\t.byte 0")?;
+    }
+
+    writeln!(out, ".Ldebug_char:")?;
+    base_type(out, "char", Encoding::SignedChar, 1)?;
+    writeln!(out, ".Ldebug_long:")?;
+    base_type(out, "long", Encoding::Signed, 8)?;
+
+    // a string points to its length and the address of its bytes
+    writeln!(out, ".Ldebug_bytes:")?;
+    writeln!(out, "\t.uleb128 {}", Abbrev::Pointer as u8)?;
+    writeln!(out, "\t.byte 8")?;
+    writeln!(out, "\t.long .Ldebug_char - .Ldebug_info0")?;
+    writeln!(out, ".Ldebug_str:")?;
+    writeln!(out, "\t.uleb128 {}", Abbrev::Struct as u8)?;
+    writeln!(out, "\t.string \"str\"")?;
+    writeln!(out, "\t.uleb128 16")?;
+    for (name, ty, offset) in [("len", "long", 0), ("bytes", "bytes", 8)] {
+        writeln!(out, "\t.uleb128 {}", Abbrev::Member as u8)?;
+        writeln!(out, "\t.string \"{}\"", name)?;
+        writeln!(out, "\t.long .Ldebug_{} - .Ldebug_info0", ty)?;
+        writeln!(out, "\t.uleb128 {}", offset)?;
+    }
+    writeln!(out, "\t.byte 0")?;
+
+    for (i, ty) in types.iter().enumerate() {
+        writeln!(out, ".Ldebug_ty{}:", i)?;
+
+        match ty {
+            Ty::Int => base_type(out, "int", Encoding::Signed, 4)?,
+            Ty::Float => base_type(out, "float", Encoding::Float, 8)?,
+            Ty::Bool => base_type(out, "bool", Encoding::Boolean, 1)?,
+            Ty::Str => {
+                writeln!(out, "\t.uleb128 {}", Abbrev::Pointer as u8)?;
+                writeln!(out, "\t.byte 8")?;
+                writeln!(out, "\t.long .Ldebug_str - .Ldebug_info0")?;
+            }
+            Ty::Ptr(to) if **to == Ty::Void => {
+                writeln!(out, "\t.uleb128 {}", Abbrev::VoidPointer as u8)?;
+                writeln!(out, "\t.byte 8")?;
+            }
+            Ty::Ptr(to) => {
+                writeln!(out, "\t.uleb128 {}", Abbrev::Pointer as u8)?;
+                writeln!(out, "\t.byte 8")?;
+                let ty = index(&types, to);
+                writeln!(out, "\t.long .Ldebug_ty{} - .Ldebug_info0", ty)?;
+            }
+            Ty::Array(elem, len) => {
+                writeln!(out, "\t.uleb128 {}", Abbrev::Array as u8)?;
+                let ty = index(&types, elem);
+                writeln!(out, "\t.long .Ldebug_ty{} - .Ldebug_info0", ty)?;
+                writeln!(out, "\t.uleb128 {}", Abbrev::Subrange as u8)?;
+                writeln!(out, "\t.uleb128 {}", len)?;
+                writeln!(out, "\t.byte 0")?;
+            }
+            // tuples are described as structs named after their type, with
+            // fields `0`, `1`...
+            Ty::Struct(s) | Ty::Tuple(s) => {
+                writeln!(out, "\t.uleb128 {}", Abbrev::Struct as u8)?;
+                writeln!(out, "\t.string \"{}\"", escape(&s.name))?;
+                writeln!(out, "\t.uleb128 {}", s.size)?;
+
+                for field in &s.fields {
+                    writeln!(out, "\t.uleb128 {}", Abbrev::Member as u8)?;
+                    writeln!(out, "\t.string \"{}\"", escape(&field.name))?;
+                    let ty = index(&types, &field.ty);
+                    writeln!(out, "\t.long .Ldebug_ty{} - .Ldebug_info0", ty)?;
+                    writeln!(out, "\t.uleb128 {}", field.offset)?;
+                }
+
+                writeln!(out, "\t.byte 0")?;
+            }
+            Ty::Void | Ty::Infer => unreachable!("variable of type {}", ty),
+        }
+    }
+
+    writeln!(out, "\t.byte 0")?;
+    writeln!(out, ".Ldebug_info_end:")?;
+
+    // filled in by the assembler
+    writeln!(out, ".section .debug_line,\"\",@progbits")?;
+    writeln!(out, ".Ldebug_line0:")?;
+
+    Ok(())
+}
+
+/// Adds a type to `types`, after any types it refers to, returning its
+/// index.
+fn register(types: &mut Vec<Ty>, ty: &Ty) -> usize {
+    if let Some(i) = types.iter().position(|other| other == ty) {
+        return i;
+    }
+
+    match ty {
+        Ty::Ptr(to) if **to != Ty::Void => {
+            register(types, to);
+        }
+        Ty::Array(elem, _) => {
+            register(types, elem);
+        }
+        Ty::Struct(s) | Ty::Tuple(s) => {
+            for field in &s.fields {
+                register(types, &field.ty);
+            }
+        }
+        _ => {}
+    }
+
+    types.push(ty.clone());
+    types.len() - 1
+}
+
+fn index(types: &[Ty], ty: &Ty) -> usize {
+    types
+        .iter()
+        .position(|other| other == ty)
+        .expect("unregistered type")
+}
+
+fn base_type<W: Write>(out: &mut W, name: &str, encoding: Encoding, size: u8) -> io::Result<()> {
+    writeln!(out, "\t.uleb128 {}", Abbrev::BaseType as u8)?;
+    writeln!(out, "\t.string \"{}\"", name)?;
+    writeln!(out, "\t.byte {}", encoding as u8)?;
+    writeln!(out, "\t.byte {}", size)
+}
+
+/// The abbreviations of the entries in `.debug_info`.
+#[derive(Clone, Copy)]
+enum Abbrev {
+    CompileUnit = 1,
+    Subprogram,
+    StaticSubprogram,
+    Variable,
+    Param,
+    BaseType,
+    Pointer,
+    VoidPointer,
+    Array,
+    Subrange,
+    Struct,
+    Member,
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Boolean = 0x2,
+    Float = 0x4,
+    Signed = 0x5,
+    SignedChar = 0x6,
+}
+
+/// Declares each abbreviation as its tag, whether it has children, and the
+/// attributes and forms of its entries.
+fn abbrevs<W: Write>(out: &mut W) -> io::Result<()> {
+    /// An attribute and its form.
+    type Attr = (u8, u8);
+
+    const NAME: Attr = (0x03, 0x08);
+    const TYPE: Attr = (0x49, 0x13);
+    const BYTE_SIZE: Attr = (0x0b, 0x0b);
+    const LOW_PC: Attr = (0x11, 0x01);
+    const HIGH_PC: Attr = (0x12, 0x07);
+    const FRAME_BASE: Attr = (0x40, 0x18);
+    const LOCATION: Attr = (0x02, 0x18);
+
+    let abbrevs: [(Abbrev, u8, bool, &[Attr]); 12] = [
+        // producer, language, name, comp_dir, low_pc, high_pc, stmt_list
+        (
+            Abbrev::CompileUnit,
+            0x11,
+            true,
+            &[
+                (0x25, 0x08),
+                (0x13, 0x05),
+                NAME,
+                (0x1b, 0x08),
+                LOW_PC,
+                HIGH_PC,
+                (0x10, 0x17),
+            ],
+        ),
+        // name, external, low_pc, high_pc, frame_base
+        (
+            Abbrev::Subprogram,
+            0x2e,
+            true,
+            &[NAME, (0x3f, 0x19), LOW_PC, HIGH_PC, FRAME_BASE],
+        ),
+        // the same, without external
+        (
+            Abbrev::StaticSubprogram,
+            0x2e,
+            true,
+            &[NAME, LOW_PC, HIGH_PC, FRAME_BASE],
+        ),
+        // name, type, location
+        (Abbrev::Variable, 0x34, false, &[NAME, TYPE, LOCATION]),
+        (Abbrev::Param, 0x05, false, &[NAME, TYPE, LOCATION]),
+        // name, encoding, byte_size
+        (
+            Abbrev::BaseType,
+            0x24,
+            false,
+            &[NAME, (0x3e, 0x0b), BYTE_SIZE],
+        ),
+        (Abbrev::Pointer, 0x0f, false, &[BYTE_SIZE, TYPE]),
+        (Abbrev::VoidPointer, 0x0f, false, &[BYTE_SIZE]),
+        (Abbrev::Array, 0x01, true, &[TYPE]),
+        // count
+        (Abbrev::Subrange, 0x21, false, &[(0x37, 0x0f)]),
+        // name, byte_size
+        (Abbrev::Struct, 0x13, true, &[NAME, (0x0b, 0x0f)]),
+        // name, type, data_member_location
+        (Abbrev::Member, 0x0d, false, &[NAME, TYPE, (0x38, 0x0f)]),
+    ];
+
+    writeln!(out, ".section .debug_abbrev,\"\",@progbits")?;
+    writeln!(out, ".Ldebug_abbrev0:")?;
+
+    for (abbrev, tag, children, attrs) in abbrevs {
+        writeln!(out, "\t.uleb128 {}", abbrev as u8)?;
+        writeln!(out, "\t.uleb128 {:#x}", tag)?;
+        writeln!(out, "\t.byte {}", children as u8)?;
+
+        for (attr, form) in attrs {
+            writeln!(out, "\t.uleb128 {:#x}, {:#x}", attr, form)?;
+        }
+
+        writeln!(out, "\t.byte 0, 0")?;
+    }
+
+    writeln!(out, "\t.byte 0")
+}
+
+/// The number of bytes `n` takes as a signed LEB128.
+fn sleb128_len(mut n: i64) -> usize {
+    let mut len = 1;
+    while !(-64..64).contains(&n) {
+        n >>= 7;
+        len += 1;
+    }
+
+    len
+}
@@ -1,53 +1,406 @@
-use crate::codegen::{self, Codegen};
+use crate::build_report::BuildReport;
+use crate::codegen::{self, Codegen, Syntax, Target};
+use crate::header;
 use crate::parse::Ast;
-use crate::rand;
+use crate::rand::{self, Rng};
+use crate::source::SourceMap;
 
 use std::collections::hash_map::DefaultHasher;
-use std::hash::Hasher;
-use std::io::{self, Write};
+use std::ffi::OsString;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
 
-pub fn emit(ast: &Ast) -> Result<(), codegen::Error> {
-    let mut out = Vec::new();
-    Codegen::new(&mut out).write(&ast)?;
+/// The runtime library, assembled and linked into every program.
+const RUNTIME: &str = include_str!("rt.s");
 
-    match std::fs::create_dir("./ripc-target") {
-        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => {
-            panic!("failed to create target directory: {}", err)
+/// The runtime library of freestanding programs, which don't link libc.
+const FREESTANDING_RUNTIME: &str = include_str!("rt_freestanding.s");
+
+/// Options for the output of the compiler.
+#[derive(Default)]
+pub struct Options {
+    /// Whether to link without libc, against a runtime built on raw system
+    /// calls.
+    pub freestanding: bool,
+    /// Whether to stop after assembling, writing a relocatable object that
+    /// exports the program as `ripc_main`, for another program to call.
+    pub obj: bool,
+    /// Whether to leave out `_start`, for startup code linked in from
+    /// elsewhere that calls `ripc_main`.
+    pub no_start: bool,
+    /// The function `_start` calls instead of `ripc_main`.
+    pub entry: Option<String>,
+    /// Where to write the executable or object, `out` or `out.o` by default.
+    pub output: Option<PathBuf>,
+    /// Whether to write a listing of the source, the generated assembly and
+    /// its encoding next to the output, to `out.lst` by default.
+    pub listing: bool,
+    /// Whether to write a C header declaring the exported functions of the
+    /// program next to the output, to `out.h` by default.
+    pub header: bool,
+    /// Whether to write the source files read by the compiler to `out.d`, or
+    /// next to the output, as a Makefile rule.
+    pub dep_info: bool,
+    /// Whether to produce a position independent executable.
+    pub pic: bool,
+    /// Whether to abort when a stack overflow overwrites the return address.
+    pub stack_protector: bool,
+    /// Whether to check array indices at runtime.
+    pub bounds_checks: bool,
+    /// Whether to count how many times the program and its functions run,
+    /// its loops iterate and its labels are reached.
+    pub profile: bool,
+    /// Whether to count how many times each statement runs.
+    pub coverage: bool,
+    /// Whether to describe source lines and variables to debuggers.
+    pub debug: bool,
+    /// How much to optimize the program, from 0 to 2.
+    pub opt_level: u8,
+    /// The syntax of the generated assembly.
+    pub syntax: Syntax,
+    /// The architecture to compile for.
+    pub target: Target,
+    /// Whether to keep intermediate files in a temporary directory that is
+    /// removed afterwards, rather than in `./ripc-target`.
+    pub temp: bool,
+    /// Object files and archives to link into the program, in the order they
+    /// were given.
+    pub objects: Vec<PathBuf>,
+    /// Libraries to link against, with flags found by `pkg-config`.
+    pub packages: Vec<String>,
+}
+
+impl Options {
+    /// The path of the executable or object.
+    pub fn output(&self) -> PathBuf {
+        match self.output {
+            Some(ref output) => output.clone(),
+            None if self.obj => PathBuf::from("out.o"),
+            None => PathBuf::from("out"),
         }
-        _ => {}
+    }
+}
+
+/// Compiles the program to the output, recording the tools run and files
+/// written in `report`.
+pub fn emit(
+    ast: &Ast,
+    sources: &SourceMap,
+    options: &Options,
+    report: &mut BuildReport,
+) -> Result<(), codegen::Error> {
+    let temp_dir;
+    let dir = if options.temp {
+        temp_dir = TempDir::new().map_err(codegen::Error::io)?;
+        temp_dir.path()
+    } else {
+        match std::fs::create_dir("./ripc-target") {
+            Err(err) if err.kind() != io::ErrorKind::AlreadyExists => {
+                return Err(codegen::Error::io(err))
+            }
+            _ => {}
+        };
+
+        Path::new("./ripc-target")
     };
 
+    // intermediates are named after the input rather than anything random,
+    // so that compiling the same input twice produces identical output
     let hash = {
         let mut hasher = DefaultHasher::new();
-        hasher.write_u64(rand::rand());
+        sources.main().name.hash(&mut hasher);
         hasher.finish()
     };
 
-    let asm_file = format!("./ripc-target/{}.s", hash);
-    let out_file = format!("./ripc-target/{}.o", hash);
+    let asm_file = dir.join(format!("{}.s", hash));
+    let out_file = dir.join(format!("{}.o", hash));
 
-    std::fs::File::create(&asm_file)
-        .expect("failed to open output file")
-        .write_all(&out)
-        .expect("failed to write output");
+    let runtime = if options.freestanding {
+        FREESTANDING_RUNTIME
+    } else {
+        RUNTIME
+    };
 
-    std::process::Command::new("as")
-        .arg(&asm_file)
-        .arg("-g")
-        .arg("-o")
-        .arg(&out_file)
-        .status()
-        .expect("failed to assemble output");
-
-    std::process::Command::new("ld")
-        .arg("-o")
-        .arg("out")
-        .arg("--dynamic-linker")
-        .arg("/lib64/ld-linux-x86-64.so.2")
+    // the runtime is named after its contents, so that concurrent builds
+    // linking different runtimes don't overwrite each other's
+    let rt_hash = {
+        let mut hasher = DefaultHasher::new();
+        runtime.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let rt_asm_file = &dir.join(format!("rt-{}.s", rt_hash));
+    let rt_out_file = &dir.join(format!("rt-{}.o", rt_hash));
+
+    let temp = temp_path(&asm_file);
+    if let Err(err) = write_assembly(&temp, ast, sources, options) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err);
+    }
+    rename(&temp, &asm_file)?;
+
+    let temp = temp_path(rt_asm_file);
+    std::fs::write(&temp, runtime).map_err(codegen::Error::io)?;
+    rename(&temp, rt_asm_file)?;
+
+    let temp = temp_path(&out_file);
+    let mut assemble = Command::new("as");
+    assemble.arg(&asm_file).arg("-o").arg(&temp);
+
+    // without debug info for the source, the assembly is described instead
+    if !options.debug {
+        assemble.arg("-g");
+    }
+
+    // the debug info names the assembly as if it were in `./ripc-target`,
+    // so that the temporary directory doesn't make the output differ
+    if options.temp {
+        let mut map = dir.as_os_str().to_owned();
+        map.push("=./ripc-target");
+        assemble.arg("--debug-prefix-map").arg(map);
+    }
+
+    let output = options.output();
+    let listing = output.with_extension("lst");
+    if options.listing {
+        let mut arg = OsString::from("-al=");
+        arg.push(&listing);
+        assemble.arg(arg);
+    }
+
+    run(&mut assemble, "as", &temp, report)?;
+    rename(&temp, &out_file)?;
+
+    if options.listing {
+        report.artifact(&listing);
+    }
+
+    let temp = temp_path(rt_out_file);
+    let mut assemble = Command::new("as");
+    assemble.arg(rt_asm_file).arg("-o").arg(&temp);
+    run(&mut assemble, "as", &temp, report)?;
+    rename(&temp, rt_out_file)?;
+
+    let temp = temp_path(&output);
+    let mut ld = Command::new("ld");
+    ld.arg("-o")
+        .arg(&temp)
         .arg(&out_file)
-        .arg("-lc")
-        .status()
-        .expect("linking failed");
+        .arg(rt_out_file)
+        .args(&options.objects);
+
+    // an object carries the runtime with it, and leaves libraries to
+    // whatever links it
+    if options.obj {
+        ld.arg("-r");
+    } else {
+        link_flags(&mut ld, options, report)?;
+    }
+
+    run(&mut ld, "ld", &temp, report)?;
+    rename(&temp, &output)?;
+    report.artifact(&output);
+
+    if options.header {
+        let path = output.with_extension("h");
+        std::fs::write(&path, header::header(ast, &path)).map_err(codegen::Error::io)?;
+        report.artifact(&path);
+    }
+
+    if options.dep_info {
+        let rule = dep_info(&output, sources, &options.objects);
+        let path = output.with_extension("d");
+        std::fs::write(&path, rule).map_err(codegen::Error::io)?;
+        report.artifact(&path);
+    }
+
+    Ok(())
+}
+
+/// Generates the assembly of the program into `path`.
+fn write_assembly(
+    path: &Path,
+    ast: &Ast,
+    sources: &SourceMap,
+    options: &Options,
+) -> Result<(), codegen::Error> {
+    let file = File::create(path).map_err(codegen::Error::io)?;
+    let mut out = BufWriter::new(file);
+    Codegen::builder(sources)
+        .syntax(options.syntax)
+        .target(options.target)
+        .comments(options.listing)
+        .pic(options.pic)
+        .stack_protector(options.stack_protector)
+        .bounds_checks(options.bounds_checks)
+        .profile(options.profile)
+        .coverage(options.coverage)
+        .debug(options.debug)
+        .opt_level(options.opt_level)
+        .entry(!options.obj && !options.no_start)
+        .entry_fn(options.entry.clone())
+        .build(&mut out)
+        .write(ast)?;
+    out.flush().map_err(codegen::Error::io)
+}
+
+/// The flags for linking an executable, rather than an object.
+fn link_flags(
+    ld: &mut Command,
+    options: &Options,
+    report: &mut BuildReport,
+) -> Result<(), codegen::Error> {
+    for package in &options.packages {
+        ld.args(pkg_config(package, report)?);
+    }
+
+    if options.pic {
+        ld.arg("-pie");
+
+        // the generated code needs no relocations at runtime, so a
+        // freestanding executable can be loaded without an interpreter
+        if options.freestanding {
+            ld.arg("--no-dynamic-linker");
+        }
+    }
+
+    if !options.freestanding {
+        ld.arg("--dynamic-linker")
+            .arg("/lib64/ld-linux-x86-64.so.2")
+            .arg("-lm")
+            .arg("-lc");
+    }
 
     Ok(())
 }
+
+/// Files are written to a temporary path and renamed into place once
+/// complete, so that an interrupted build never leaves a truncated file
+/// behind. The path includes the process id, so concurrent builds don't
+/// write to the same file.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".{}.tmp", std::process::id()));
+    temp.into()
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), codegen::Error> {
+    std::fs::rename(from, to).map_err(codegen::Error::io)
+}
+
+/// Runs a tool writing to `output`, which is removed if the tool fails.
+fn run(
+    command: &mut Command,
+    tool: &'static str,
+    output: &Path,
+    report: &mut BuildReport,
+) -> Result<(), codegen::Error> {
+    let start = Instant::now();
+    let status = command.status();
+    let code = status.as_ref().ok().and_then(|status| status.code());
+    report.command(command, code, start.elapsed());
+
+    let status = status.map_err(codegen::Error::io)?;
+
+    if !status.success() {
+        let _ = std::fs::remove_file(output);
+        return Err(codegen::Error::tool(tool));
+    }
+
+    Ok(())
+}
+
+/// The `-L` and `-l` flags needed to link against a library, as reported by
+/// `pkg-config`. Compiler flags are ignored, as there is no C to compile.
+fn pkg_config(package: &str, report: &mut BuildReport) -> Result<Vec<String>, codegen::Error> {
+    let mut command = Command::new("pkg-config");
+    command
+        .arg("--libs")
+        .arg("--cflags")
+        .arg(package)
+        .stderr(Stdio::inherit());
+
+    let start = Instant::now();
+    let output = command.output();
+    let code = output.as_ref().ok().and_then(|output| output.status.code());
+    report.command(&command, code, start.elapsed());
+
+    let output = output.map_err(codegen::Error::io)?;
+
+    if !output.status.success() {
+        return Err(codegen::Error::tool("pkg-config"));
+    }
+
+    let flags = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .filter(|flag| flag.starts_with("-L") || flag.starts_with("-l"))
+        .map(str::to_owned)
+        .collect();
+
+    Ok(flags)
+}
+
+/// A directory in the system's temporary directory (`$TMPDIR`, or `/tmp`),
+/// removed along with its contents when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates a directory with a random name. A directory that already
+    /// exists, which may belong to someone else, is never reused.
+    pub fn new() -> io::Result<Self> {
+        let mut rng = Rng::new(rand::clock_seed() ^ u64::from(std::process::id()));
+
+        loop {
+            let name = format!("ripc-{:016x}", rng.next_u64());
+            let path = std::env::temp_dir().join(name);
+
+            match std::fs::create_dir(&path) {
+                Ok(()) => return Ok(Self(path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A Makefile rule making the output depend on every file read from disk and
+/// every object linked in, with an empty rule for each file so that deleting
+/// one doesn't break the build.
+fn dep_info(output: &Path, sources: &SourceMap, objects: &[PathBuf]) -> String {
+    let sources = sources
+        .files()
+        .iter()
+        .filter(|file| file.path.is_some())
+        .map(|file| file.name.clone());
+
+    let objects = objects
+        .iter()
+        .map(|object| object.to_string_lossy().into_owned());
+
+    let files = sources
+        .chain(objects)
+        .map(|file| file.replace(' ', "\\ "))
+        .collect::<Vec<_>>();
+
+    let output = output.to_string_lossy().replace(' ', "\\ ");
+    let mut rule = format!("{}: {}\n", output, files.join(" "));
+    for file in files {
+        rule.push_str(&format!("\n{}:\n", file));
+    }
+
+    rule
+}
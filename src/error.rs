@@ -1,62 +1,337 @@
+use crate::source::SourceMap;
 use crate::{Span, Spanned};
 
+use std::any::TypeId;
 use std::fmt;
 use std::io::{self, Write};
+use std::path::Path;
 
-pub trait Report<W>: Spanned {
-    fn report(&self, reporter: &mut Reporter<'_, W>) -> io::Result<()>;
+pub trait Report: Spanned {
+    /// Identifies the kind of error, `E0301`. Codes are stable, so a code
+    /// keeps its meaning when messages are reworded or kinds are added.
+    fn code(&self) -> &'static str;
+
+    /// What went wrong, without the location, `Expected ';', found '}'`.
+    fn message(&self, sources: &SourceMap) -> String;
+
+    fn failure(&self) -> Failure;
+
+    /// An edit that fixes the error, if there is exactly one.
+    fn suggestion(&self) -> Option<Suggestion> {
+        None
+    }
+
+    /// Further explanation of the error, such as what to do instead.
+    fn note(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Other places in the source the error refers to, each with a message.
+    fn labels(&self) -> Vec<(Span, String)> {
+        Vec::new()
+    }
+}
+
+/// The category of an error, which determines the exit status of the
+/// compiler, so that scripts can tell a broken program from a broken
+/// environment.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Failure {
+    /// Invalid command line arguments.
+    Usage,
+    /// A lex or parse error.
+    Syntax,
+    /// An error resolving names or checking types.
+    Semantic,
+    /// An error generating code.
+    Codegen,
+    /// A failure to read or write files or to run the assembler or linker.
+    Environment,
+}
+
+impl Failure {
+    pub fn exit(self) -> ! {
+        let code = match self {
+            Failure::Usage => 2,
+            Failure::Syntax => 3,
+            Failure::Semantic => 4,
+            Failure::Codegen => 5,
+            Failure::Environment => 6,
+        };
+
+        std::process::exit(code)
+    }
+}
+
+/// An edit to the source, replacing the text of `span` with `replacement`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Suggestion {
+    /// A description of the edit, `insert ';'`.
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Suggestion {
+    /// Applies the edit to the file it points into, returning the path of the
+    /// file and its new source, or `None` if the file isn't on disk.
+    pub fn apply<'a>(&self, sources: &'a SourceMap) -> Option<(&'a Path, String)> {
+        let range = self.span.range()?;
+        let file = sources.file(range.start);
+        let path = file.path.as_deref()?;
+
+        let mut source = file.source.to_string();
+        source.replace_range(
+            range.start - file.start..range.end - file.start,
+            &self.replacement,
+        );
+        Some((path, source))
+    }
 }
 
 pub struct Reporter<'a, W> {
     pub out: W,
-    pub source: &'a str,
+    pub sources: &'a SourceMap,
+    format: Format,
+    /// The number of diagnostics rendered so far.
+    rendered: usize,
+}
+
+/// How errors are rendered.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// The message, followed by the location and source line of the error.
+    Human,
+    /// A single line, `file:line:column: error[E0301]: message`, for tools
+    /// that scrape compiler output.
+    Short,
 }
 
 impl<'a, W> Reporter<'a, W>
 where
     W: Write,
 {
-    pub fn new(out: W, source: &'a str) -> Self {
-        Self { out, source }
+    pub fn new(out: W, sources: &'a SourceMap) -> Self {
+        Self {
+            out,
+            sources,
+            format: Format::Human,
+            rendered: 0,
+        }
     }
 
-    fn report(&mut self, err: impl Report<W>) -> Result<(), io::Error> {
-        write!(self.out, "[error]: ")?;
-        err.report(self)?;
-        writeln!(self.out, "\n{}", self.source)?;
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
 
-        let pad = if err.span() == Span::EOF {
-            self.source.len()
-        } else {
-            err.span().start
-        };
+    /// Renders an error or warning, `severity`.
+    fn render(&mut self, err: &dyn Report, severity: &str) -> io::Result<()> {
+        if self.format == Format::Short {
+            let (file, line, column) = self.sources.location(err.span());
+            return writeln!(
+                self.out,
+                "{}:{}:{}: {}[{}]: {}",
+                file.name,
+                line,
+                column,
+                severity,
+                err.code(),
+                err.message(self.sources)
+            );
+        }
+
+        // separate the diagnostics of a compilation by a blank line
+        if self.rendered > 0 {
+            writeln!(self.out)?;
+        }
+        self.rendered += 1;
+
+        write!(self.out, "[{}]: {}", severity, err.message(self.sources))?;
+
+        let (file, line, column) = self.sources.location(err.span());
+        let (end_line, end_column) = self.sources.end_location(err.span());
+
+        writeln!(self.out, "\n --> {}:{}:{}", file.name, line, column)?;
+        writeln!(self.out, "{}", file.line(line))?;
+        writeln!(self.out, "{:1$}^ ", "", column - 1)?;
+
+        // spans over several lines, such as multi-line strings, also show
+        // the line they end on
+        if end_line > line {
+            if end_line > line + 1 {
+                writeln!(self.out, "...")?;
+            }
+
+            writeln!(self.out, "{}", file.line(end_line))?;
+            writeln!(self.out, "{:1$}^ ", "", end_column.saturating_sub(2))?;
+        }
+
+        for (span, message) in err.labels() {
+            let (file, line, column) = self.sources.location(span);
+            writeln!(self.out, " --> {}:{}:{}", file.name, line, column)?;
+            writeln!(self.out, "{}", file.line(line))?;
+            writeln!(self.out, "{:1$}^ {2}", "", column - 1, message)?;
+        }
+
+        if let Some(suggestion) = err.suggestion() {
+            writeln!(self.out, "help: {}", suggestion.message)?;
+        }
+
+        if let Some(note) = err.note() {
+            writeln!(self.out, "note: {}", note)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn exit(&mut self, err: impl Report) -> ! {
+        self.on_error(&err);
+        err.failure().exit()
+    }
+
+    /// Reports every error, in the order of their spans.
+    pub fn exit_all(&mut self, diagnostics: Diagnostics) -> ! {
+        diagnostics.emit(self);
+        diagnostics.first().failure().exit()
+    }
+}
+
+/// Receives the diagnostics of a compilation as they are emitted, as
+/// structured values rather than rendered text. `Reporter` is the handler
+/// that renders them for the terminal.
+pub trait DiagnosticHandler {
+    fn on_error(&mut self, err: &dyn Report);
 
-        write!(self.out, "{:pad$}^ \n", "")
+    fn on_warning(&mut self, warning: &dyn Report);
+}
+
+impl<W: Write> DiagnosticHandler for Reporter<'_, W> {
+    fn on_error(&mut self, err: &dyn Report) {
+        self.render(err, "error")
+            .expect("failed to write to stdout");
     }
 
-    pub fn exit(&mut self, err: impl Report<W>) -> ! {
-        self.report(err).expect("failed to write to stdout");
-        std::process::exit(1)
+    fn on_warning(&mut self, warning: &dyn Report) {
+        self.render(warning, "warning")
+            .expect("failed to write to stdout");
     }
 }
 
-impl<E, W> From<E> for Box<dyn Report<W>>
+impl<E> From<E> for Box<dyn Report>
 where
-    E: Report<W> + fmt::Debug + 'static,
+    E: Report + fmt::Debug + 'static,
 {
     fn from(err: E) -> Self {
         Box::new(err)
     }
 }
 
-impl<W> Spanned for Box<dyn Report<W>> {
+/// The errors of a failed compilation, sorted by span, with duplicates
+/// removed.
+pub struct Diagnostics(Vec<Diagnostic>);
+
+/// An error, along with what tells it apart from other errors: its type and
+/// its debug representation, which covers its kind and span.
+struct Diagnostic {
+    id: (TypeId, String),
+    report: Box<dyn Report>,
+}
+
+impl Diagnostic {
+    fn new<E>(err: E) -> Self
+    where
+        E: Report + fmt::Debug + 'static,
+    {
+        Self {
+            id: (TypeId::of::<E>(), format!("{:?}", err)),
+            report: Box::new(err),
+        }
+    }
+}
+
+impl Diagnostics {
+    pub fn new<E>(errors: Vec<E>) -> Self
+    where
+        E: Report + fmt::Debug + 'static,
+    {
+        Self(Vec::new()).merge(Self(errors.into_iter().map(Diagnostic::new).collect()))
+    }
+
+    /// Combines the errors of two phases, in the order of their spans,
+    /// dropping errors that are already present.
+    pub fn merge(mut self, other: Self) -> Self {
+        for err in other.0 {
+            if !self.0.iter().any(|present| present.id == err.id) {
+                self.0.push(err);
+            }
+        }
+
+        self.0.sort_by_key(|err| order(err.report.span()));
+        self
+    }
+
+    /// Passes every error to a handler, in order.
+    pub fn emit(&self, handler: &mut impl DiagnosticHandler) {
+        for err in &self.0 {
+            handler.on_error(&*err.report);
+        }
+    }
+
+    /// The number of errors.
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The earliest error.
+    pub fn first(&self) -> &dyn Report {
+        &*self.0[0].report
+    }
+}
+
+/// The order errors are reported in: by file and then position within the
+/// file, with EOF last.
+fn order(span: Span) -> (bool, usize, usize) {
+    (span == Span::EOF, span.start, span.end)
+}
+
+impl<E> From<E> for Diagnostics
+where
+    E: Report + fmt::Debug + 'static,
+{
+    fn from(err: E) -> Self {
+        Self(vec![Diagnostic::new(err)])
+    }
+}
+
+impl Spanned for Box<dyn Report> {
     fn span(&self) -> Span {
         (**self).span()
     }
 }
 
-impl<W> Report<W> for Box<dyn Report<W>> {
-    fn report(&self, reporter: &mut Reporter<'_, W>) -> io::Result<()> {
-        (**self).report(reporter)
+impl Report for Box<dyn Report> {
+    fn code(&self) -> &'static str {
+        (**self).code()
+    }
+
+    fn message(&self, sources: &SourceMap) -> String {
+        (**self).message(sources)
+    }
+
+    fn suggestion(&self) -> Option<Suggestion> {
+        (**self).suggestion()
+    }
+
+    fn note(&self) -> Option<&'static str> {
+        (**self).note()
+    }
+
+    fn labels(&self) -> Vec<(Span, String)> {
+        (**self).labels()
+    }
+
+    fn failure(&self) -> Failure {
+        (**self).failure()
     }
 }
@@ -0,0 +1,281 @@
+use crate::parse::{Ast, BinaryOp, Cast, Expr, ExprKind, Lit, UnaryOp};
+use crate::sema;
+use crate::source::SourceMap;
+use crate::ty::Ty;
+use crate::{Failure, Report, Span, Spanned};
+
+use std::fmt;
+use std::rc::Rc;
+
+/// Evaluates a checked program without compiling it, returning the value of
+/// its last statement, if it has one.
+///
+/// Only programs made of literals, arithmetic, comparisons, casts,
+/// conditionals and variables can be evaluated. Values behave as they would at runtime, with
+/// 32-bit integers that wrap on overflow.
+pub fn eval(ast: &Ast) -> Result<Option<Value>, Error> {
+    let mut eval = Eval {
+        vars: vec![None; ast.vars.len()],
+    };
+
+    let mut value = None;
+    for expr in &ast.exprs {
+        value = eval.expr(expr)?;
+    }
+
+    Ok(value)
+}
+
+/// Evaluates an expression made only of constants, or returns `None` if it
+/// isn't one or can't be evaluated, such as when it divides by zero.
+pub fn constant(expr: &Expr) -> Option<Value> {
+    if !sema::is_const(expr) {
+        return None;
+    }
+
+    Eval { vars: Vec::new() }.value(expr).ok()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Int(i32),
+    Float(f64),
+    Bool(bool),
+    Str(Rc<str>),
+}
+
+impl fmt::Display for Value {
+    /// Formats a value the way `println` prints it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(int) => write!(f, "{}", int),
+            Value::Float(float) => write!(f, "{:.6}", float),
+            Value::Bool(bool) => write!(f, "{}", bool),
+            Value::Str(str) => write!(f, "{}", str),
+        }
+    }
+}
+
+struct Eval {
+    /// The value of each variable, once it is assigned.
+    vars: Vec<Option<Value>>,
+}
+
+impl Eval {
+    /// Evaluates an expression, returning `None` for statements without a
+    /// value, such as `let`.
+    fn expr(&mut self, expr: &Expr) -> Result<Option<Value>, Error> {
+        let value = match expr.kind {
+            ExprKind::Lit(ref lit) => match lit.value {
+                Lit::Num(num) => Value::Int(num as i32),
+                Lit::Float(float) => Value::Float(float),
+                Lit::Bool(bool) => Value::Bool(bool),
+                Lit::String(ref str) => Value::Str(str.clone()),
+            },
+            ExprKind::SizeOf(ref of) => Value::Int(of.size() as i32),
+            // reads of unassigned variables are rejected by `init::check`
+            ExprKind::Var(i) => self.vars[i].clone().expect("unassigned variable"),
+            ExprKind::Let(ref expr) => {
+                self.vars[expr.var] = Some(self.value(&expr.init)?);
+                return Ok(None);
+            }
+            // the value of an assignment is the value assigned
+            ExprKind::Binary(ref binary) if binary.op.value == BinaryOp::Assign => {
+                match binary.left.kind {
+                    ExprKind::Var(i) => {
+                        let value = self.value(&binary.right)?;
+                        self.vars[i] = Some(value.clone());
+                        value
+                    }
+                    _ => return Err(Error::new(ErrorKind::NotConst, binary.left.span)),
+                }
+            }
+            ExprKind::Binary(ref binary) if binary.op.value.is_logical() => {
+                // the right operand only decides the result if the left
+                // operand doesn't
+                match self.value(&binary.left)? {
+                    Value::Bool(left) if left == (binary.op.value == BinaryOp::Or) => {
+                        Value::Bool(left)
+                    }
+                    Value::Bool(_) => self.value(&binary.right)?,
+                    value => unreachable!("operand of type {}", value_ty(&value)),
+                }
+            }
+            ExprKind::Binary(ref binary) => {
+                let left = self.value(&binary.left)?;
+                let right = self.value(&binary.right)?;
+                binary_op(binary.op.value, left, right, expr.span)?
+            }
+            ExprKind::Unary(ref unary) if unary.op.value == UnaryOp::Neg => {
+                match self.value(&unary.expr)? {
+                    Value::Int(int) => Value::Int(int.wrapping_neg()),
+                    Value::Float(float) => Value::Float(-float),
+                    value => unreachable!("negating a value of type {}", value_ty(&value)),
+                }
+            }
+            ExprKind::Unary(ref unary) if unary.op.value == UnaryOp::Not => {
+                match self.value(&unary.expr)? {
+                    Value::Bool(bool) => Value::Bool(!bool),
+                    value => unreachable!("operand of type {}", value_ty(&value)),
+                }
+            }
+            ExprKind::Cast(ref cast) => self.cast(cast, expr.span)?,
+            ExprKind::Block(ref block) => {
+                for stmt in &block.stmts {
+                    self.expr(stmt)?;
+                }
+
+                match block.value {
+                    Some(ref value) => return self.expr(value),
+                    None => return Ok(None),
+                }
+            }
+            ExprKind::If(ref expr) => {
+                let branch = match self.value(&expr.cond)? {
+                    Value::Bool(true) => Some(&expr.then),
+                    Value::Bool(false) => expr.otherwise.as_ref(),
+                    value => unreachable!("condition of type {}", value_ty(&value)),
+                };
+
+                match branch {
+                    Some(branch) => return self.expr(branch),
+                    None => return Ok(None),
+                }
+            }
+            _ => return Err(Error::new(ErrorKind::NotConst, expr.span)),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Evaluates an expression that the type checker ensured has a value.
+    fn value(&mut self, expr: &Expr) -> Result<Value, Error> {
+        match self.expr(expr)? {
+            Some(value) => Ok(value),
+            None => Err(Error::new(ErrorKind::NotConst, expr.span)),
+        }
+    }
+
+    fn cast(&mut self, cast: &Cast, span: Span) -> Result<Value, Error> {
+        let value = match (self.value(&cast.expr)?, &cast.ty.value) {
+            (Value::Int(int), Ty::Float) => Value::Float(int as f64),
+            (Value::Float(float), Ty::Int) => Value::Int(truncate(float)),
+            (Value::Bool(bool), Ty::Int) => Value::Int(bool as i32),
+            (Value::Bool(bool), Ty::Float) => Value::Float(bool as i32 as f64),
+            (value, _) if value_ty(&value) == cast.ty.value => value,
+            _ => return Err(Error::new(ErrorKind::NotConst, span)),
+        };
+
+        Ok(value)
+    }
+}
+
+fn binary_op(op: BinaryOp, left: Value, right: Value, span: Span) -> Result<Value, Error> {
+    let value = match (left, right) {
+        (Value::Int(left), Value::Int(right)) => match op {
+            BinaryOp::Add => Value::Int(left.wrapping_add(right)),
+            BinaryOp::Sub => Value::Int(left.wrapping_sub(right)),
+            BinaryOp::Mul => Value::Int(left.wrapping_mul(right)),
+            BinaryOp::Div if right == 0 => return Err(Error::new(ErrorKind::DivByZero, span)),
+            // `idiv` faults rather than wrapping
+            BinaryOp::Div => match left.checked_div(right) {
+                Some(value) => Value::Int(value),
+                None => return Err(Error::new(ErrorKind::DivOverflow, span)),
+            },
+            op => Value::Bool(compare(op, left.cmp(&right))),
+        },
+        (Value::Float(left), Value::Float(right)) => match op {
+            BinaryOp::Add => Value::Float(left + right),
+            BinaryOp::Sub => Value::Float(left - right),
+            BinaryOp::Mul => Value::Float(left * right),
+            BinaryOp::Div => Value::Float(left / right),
+            // comparisons with NaN are false, except `!=`
+            op => match left.partial_cmp(&right) {
+                Some(ordering) => Value::Bool(compare(op, ordering)),
+                None => Value::Bool(op == BinaryOp::Ne),
+            },
+        },
+        (Value::Bool(left), Value::Bool(right)) => Value::Bool(compare(op, left.cmp(&right))),
+        _ => return Err(Error::new(ErrorKind::NotConst, span)),
+    };
+
+    Ok(value)
+}
+
+fn compare(op: BinaryOp, ordering: std::cmp::Ordering) -> bool {
+    match op {
+        BinaryOp::Eq => ordering.is_eq(),
+        BinaryOp::Ne => ordering.is_ne(),
+        BinaryOp::Lt => ordering.is_lt(),
+        BinaryOp::Le => ordering.is_le(),
+        BinaryOp::Gt => ordering.is_gt(),
+        BinaryOp::Ge => ordering.is_ge(),
+        _ => unreachable!("{:?} is not a comparison", op),
+    }
+}
+
+/// Converts a float to an integer as `cvttsd2si` does, rounding toward zero,
+/// with `i32::MIN` for values out of range.
+fn truncate(float: f64) -> i32 {
+    if float.is_nan() || float < i32::MIN as f64 || float >= -(i32::MIN as f64) {
+        return i32::MIN;
+    }
+
+    float as i32
+}
+
+fn value_ty(value: &Value) -> Ty {
+    match value {
+        Value::Int(_) => Ty::Int,
+        Value::Float(_) => Ty::Float,
+        Value::Bool(_) => Ty::Bool,
+        Value::Str(_) => Ty::Str,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Error {
+    fn new(kind: ErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    NotConst,
+    DivByZero,
+    DivOverflow,
+}
+
+impl Spanned for Error {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::NotConst => "E0501",
+            ErrorKind::DivByZero => "E0502",
+            ErrorKind::DivOverflow => "E0503",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        match self.kind {
+            ErrorKind::NotConst => "Expression can't be evaluated without compiling".to_owned(),
+            ErrorKind::DivByZero => "Division by zero".to_owned(),
+            ErrorKind::DivOverflow => "Division overflows".to_owned(),
+        }
+    }
+
+    fn failure(&self) -> Failure {
+        Failure::Semantic
+    }
+}
@@ -0,0 +1,155 @@
+//! Format strings, the first argument of `print("x = {}, y = {}", x, y)`.
+//!
+//! A format string is a string literal that contains a brace. Each `{}` is
+//! replaced by the next argument, and `{{` and `}}` print a brace. A
+//! placeholder can also name how to print its argument:
+//!
+//! - `{:x}`, an `int` in hexadecimal.
+//! - `{:c}`, an `int` as the character with that code.
+//! - `{:.N}`, a `float` with `N` digits after the decimal point.
+
+use crate::ty::Ty;
+
+use std::fmt;
+use std::ops::Range;
+
+/// Whether a string literal passed to `print` is a format string.
+pub fn is_format(literal: &str) -> bool {
+    literal.contains(['{', '}'])
+}
+
+/// A part of a format string.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Piece {
+    /// Text that is printed as written, escapes included.
+    Text(String),
+    /// A placeholder, at a range of bytes in the literal.
+    Arg(Spec, Range<usize>),
+}
+
+/// How a placeholder prints its argument.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Spec {
+    /// `{}`, the way `print` prints a value on its own.
+    Display,
+    /// `{:x}`
+    Hex,
+    /// `{:c}`
+    Char,
+    /// `{:.N}`
+    Precision(usize),
+}
+
+impl Spec {
+    /// Whether the placeholder can print a value of the given type.
+    pub fn accepts(&self, ty: &Ty) -> bool {
+        match self {
+            Spec::Display => ty.is_scalar(),
+            Spec::Hex | Spec::Char => *ty == Ty::Int,
+            Spec::Precision(_) => *ty == Ty::Float,
+        }
+    }
+}
+
+impl fmt::Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Spec::Display => write!(f, "{{}}"),
+            Spec::Hex => write!(f, "{{:x}}"),
+            Spec::Char => write!(f, "{{:c}}"),
+            Spec::Precision(digits) => write!(f, "{{:.{}}}", digits),
+        }
+    }
+}
+
+/// Splits a format string into text and placeholders.
+pub fn parse(format: &str) -> Result<Vec<Piece>, Error> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.char_indices().peekable();
+
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '{' if chars.peek().map(|&(_, ch)| ch) == Some('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek().map(|&(_, ch)| ch) == Some('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let end = match format[i..].find('}') {
+                    Some(end) => i + end + 1,
+                    None => return Err(Error::new(ErrorKind::Unclosed, i..i + 1)),
+                };
+
+                let spec = match &format[i + 1..end - 1] {
+                    "" => Spec::Display,
+                    ":x" => Spec::Hex,
+                    ":c" => Spec::Char,
+                    spec => match spec.strip_prefix(":.").map(str::parse) {
+                        Some(Ok(digits)) => Spec::Precision(digits),
+                        _ => return Err(Error::new(ErrorKind::UnknownSpec, i..end)),
+                    },
+                };
+
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+
+                pieces.push(Piece::Arg(spec, i..end));
+                while chars.peek().is_some_and(|&(j, _)| j < end) {
+                    chars.next();
+                }
+            }
+            '}' => return Err(Error::new(ErrorKind::Unmatched, i..i + 1)),
+            // an escaped character, which can't start a placeholder
+            '\\' => {
+                text.push('\\');
+                if let Some((_, ch)) = chars.next() {
+                    text.push(ch);
+                }
+            }
+            ch => text.push(ch),
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+
+    Ok(pieces)
+}
+
+/// An error in a format string, at a range of bytes in the literal.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub range: Range<usize>,
+}
+
+impl Error {
+    fn new(kind: ErrorKind, range: Range<usize>) -> Self {
+        Self { kind, range }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorKind {
+    /// A `{` without a `}`.
+    Unclosed,
+    /// A `}` without a `{`.
+    Unmatched,
+    UnknownSpec,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Unclosed => write!(f, "Unclosed '{{' in format string"),
+            ErrorKind::Unmatched => write!(f, "Unmatched '}}' in format string"),
+            ErrorKind::UnknownSpec => write!(f, "Unknown placeholder in format string"),
+        }
+    }
+}
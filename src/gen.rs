@@ -0,0 +1,390 @@
+use crate::rand::Rng;
+
+/// Generates a random program, for stress testing the compiler.
+///
+/// The program is well-formed and always terminates: variables are
+/// initialized where they are declared, divisors are nonzero literals, loops
+/// have constant bounds and counters the body never assigns, and functions
+/// only call the functions defined before them. It prints what it computes,
+/// so a miscompile shows up in its output.
+pub fn program(rng: &mut Rng) -> String {
+    let mut gen = Generator {
+        rng,
+        out: String::new(),
+        indent: 0,
+        vars: Vec::new(),
+        functions: Vec::new(),
+        names: 0,
+        loops: 0,
+        ret: None,
+    };
+
+    for _ in 0..gen.rng.below(4) {
+        gen.function();
+    }
+
+    let count = 4 + gen.rng.below(8);
+    gen.stmts(count, 0);
+
+    // everything declared at the top level is still in scope
+    let names = gen
+        .vars
+        .iter()
+        .map(|var| var.name.clone())
+        .collect::<Vec<_>>();
+    for chunk in names.chunks(4) {
+        gen.line(format!("println({});", chunk.join(", ")));
+    }
+
+    gen.out
+}
+
+/// The depth past which expressions are always variables or literals.
+const MAX_DEPTH: usize = 3;
+
+/// The depth past which statements don't nest further.
+const MAX_NESTING: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Int,
+    Float,
+    Bool,
+}
+
+const TYS: [Ty; 3] = [Ty::Int, Ty::Float, Ty::Bool];
+
+impl Ty {
+    fn name(self) -> &'static str {
+        match self {
+            Ty::Int => "int",
+            Ty::Float => "float",
+            Ty::Bool => "bool",
+        }
+    }
+}
+
+struct Var {
+    name: String,
+    ty: Ty,
+    /// Loop counters and parameters are never assigned.
+    assignable: bool,
+}
+
+struct Function {
+    name: String,
+    params: Vec<Ty>,
+    ret: Ty,
+}
+
+struct Generator<'a> {
+    rng: &'a mut Rng,
+    out: String,
+    indent: usize,
+    /// The variables in scope.
+    vars: Vec<Var>,
+    /// The functions defined so far.
+    functions: Vec<Function>,
+    /// The number of names handed out, which keeps them unique.
+    names: usize,
+    /// The enclosing loops.
+    loops: usize,
+    /// The return type of the function being generated.
+    ret: Option<Ty>,
+}
+
+impl Generator<'_> {
+    fn function(&mut self) {
+        let name = self.fresh("f");
+        let ret = *self.rng.choose(&TYS);
+
+        let mut params = Vec::new();
+        for _ in 0..self.rng.below(4) {
+            let ty = *self.rng.choose(&TYS);
+            let name = self.fresh("p");
+            params.push(format!("{}: {}", name, ty.name()));
+            self.vars.push(Var {
+                name,
+                ty,
+                assignable: false,
+            });
+        }
+
+        self.line(format!(
+            "fn {}({}) -> {} {{",
+            name,
+            params.join(", "),
+            ret.name()
+        ));
+        self.ret = Some(ret);
+        self.indent += 1;
+
+        let count = 1 + self.rng.below(4);
+        self.stmts(count, 1);
+
+        let value = self.expr(ret, 0);
+        self.line(value);
+
+        self.indent -= 1;
+        self.line("}".to_owned());
+
+        // the body's variables come after the parameters
+        self.vars.truncate(params.len());
+        let params = self.vars.drain(..).map(|var| var.ty).collect();
+        self.ret = None;
+        self.functions.push(Function { name, params, ret });
+    }
+
+    fn stmts(&mut self, count: u64, nesting: usize) {
+        for _ in 0..count {
+            self.stmt(nesting);
+        }
+    }
+
+    fn stmt(&mut self, nesting: usize) {
+        let nested = nesting < MAX_NESTING;
+        let assignable = self
+            .vars
+            .iter()
+            .filter(|var| var.assignable)
+            .map(|var| (var.name.clone(), var.ty))
+            .collect::<Vec<_>>();
+
+        match self.rng.below(10) {
+            0..=2 if !assignable.is_empty() => {
+                let (name, ty) = self.rng.choose(&assignable).clone();
+                let value = self.expr(ty, 0);
+                self.line(format!("{} = {};", name, value));
+            }
+            3 => {
+                let args = (0..1 + self.rng.below(3))
+                    .map(|_| {
+                        let ty = *self.rng.choose(&TYS);
+                        self.expr(ty, 0)
+                    })
+                    .collect::<Vec<_>>();
+
+                self.line(format!("println({});", args.join(", ")));
+            }
+            4 | 5 if nested => {
+                let cond = self.expr(Ty::Bool, 0);
+                self.line(format!("if {} {{", cond));
+                self.block(nesting);
+
+                if self.rng.chance(50) {
+                    self.line("} else {".to_owned());
+                    self.block(nesting);
+                }
+
+                self.line("}".to_owned());
+            }
+            6 if nested => {
+                let counter = self.fresh("i");
+                let bound = self.rng.below(6);
+                self.line(format!(
+                    "for (let {0} = 0; {0} < {1}; {0} = {0} + 1) {{",
+                    counter, bound
+                ));
+
+                self.vars.push(Var {
+                    name: counter,
+                    ty: Ty::Int,
+                    assignable: false,
+                });
+
+                self.loops += 1;
+                self.block(nesting);
+                self.loops -= 1;
+
+                self.vars.pop();
+                self.line("}".to_owned());
+            }
+            // the counter is incremented first, so `continue` can't skip it
+            7 if nested => {
+                let counter = self.fresh("w");
+                let bound = self.rng.below(6);
+                self.line(format!("let {} = 0;", counter));
+                self.line(format!("while {0} < {1} {{", counter, bound));
+                self.line(format!("    {0} = {0} + 1;", counter));
+
+                self.vars.push(Var {
+                    name: counter,
+                    ty: Ty::Int,
+                    assignable: false,
+                });
+
+                self.loops += 1;
+                self.block(nesting);
+                self.loops -= 1;
+
+                self.line("}".to_owned());
+            }
+            _ => {
+                let ty = *self.rng.choose(&TYS);
+                let value = self.expr(ty, 0);
+                let name = self.fresh("v");
+                self.line(format!("let {} = {};", name, value));
+
+                self.vars.push(Var {
+                    name,
+                    ty,
+                    assignable: true,
+                });
+            }
+        }
+    }
+
+    /// Generates the statements of a block in a new scope, some of the time
+    /// ending it early with `break`, `continue` or `return`.
+    fn block(&mut self, nesting: usize) {
+        let scope = self.vars.len();
+        self.indent += 1;
+
+        let count = 1 + self.rng.below(3);
+        self.stmts(count, nesting + 1);
+
+        let mut exits = Vec::new();
+        if self.loops > 0 {
+            exits.extend(["break", "continue"]);
+        }
+
+        if self.ret.is_some() {
+            exits.push("return");
+        }
+
+        if !exits.is_empty() && self.rng.chance(20) {
+            match *self.rng.choose(&exits) {
+                "return" => {
+                    let value = self.expr(self.ret.unwrap(), 0);
+                    self.line(format!("return {};", value));
+                }
+                exit => self.line(format!("{};", exit)),
+            }
+        }
+
+        self.indent -= 1;
+        self.vars.truncate(scope);
+    }
+
+    fn expr(&mut self, ty: Ty, depth: usize) -> String {
+        if depth >= MAX_DEPTH || self.rng.chance(30) {
+            return self.leaf(ty);
+        }
+
+        let depth = depth + 1;
+
+        if self.rng.chance(15) {
+            if let Some(call) = self.call(ty, depth) {
+                return call;
+            }
+        }
+
+        match (ty, self.rng.below(6)) {
+            (Ty::Int, 0) => format!(
+                "({} / {})",
+                self.expr(Ty::Int, depth),
+                1 + self.rng.below(9)
+            ),
+            (Ty::Float, 0) => {
+                let value = self.expr(Ty::Float, depth);
+                format!("({} / {}.5)", value, self.rng.below(10))
+            }
+            (Ty::Int | Ty::Float, 1) => {
+                let from = if self.rng.chance(50) {
+                    Ty::Bool
+                } else {
+                    Ty::Int
+                };
+                let value = self.expr(from, depth);
+                format!("({} as {})", value, ty.name())
+            }
+            (Ty::Int | Ty::Float, 3) => format!("(-{})", self.expr(ty, depth)),
+            (Ty::Bool, 4) => format!("(!{})", self.expr(ty, depth)),
+            (_, 2) => {
+                let cond = self.expr(Ty::Bool, depth);
+                let then = self.expr(ty, depth);
+                let otherwise = self.expr(ty, depth);
+                format!("(if {} {{ {} }} else {{ {} }})", cond, then, otherwise)
+            }
+            (Ty::Bool, 3) => {
+                let op = *self.rng.choose(&["&&", "||"]);
+                let left = self.expr(Ty::Bool, depth);
+                let right = self.expr(Ty::Bool, depth);
+                format!("({} {} {})", left, op, right)
+            }
+            (Ty::Bool, _) => {
+                let operand = *self.rng.choose(&TYS);
+                let ops: &[&str] = match operand {
+                    Ty::Bool => &["==", "!="],
+                    _ => &["==", "!=", "<", "<=", ">", ">="],
+                };
+
+                let op = *self.rng.choose(ops);
+                let left = self.expr(operand, depth);
+                let right = self.expr(operand, depth);
+                format!("({} {} {})", left, op, right)
+            }
+            (_, _) => {
+                let op = *self.rng.choose(&["+", "-", "*"]);
+                let left = self.expr(ty, depth);
+                let right = self.expr(ty, depth);
+                format!("({} {} {})", left, op, right)
+            }
+        }
+    }
+
+    /// Calls a function returning `ty`, if one has been defined.
+    fn call(&mut self, ty: Ty, depth: usize) -> Option<String> {
+        let candidates = self
+            .functions
+            .iter()
+            .filter(|f| f.ret == ty)
+            .map(|f| (f.name.clone(), f.params.clone()))
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let (name, params) = self.rng.choose(&candidates).clone();
+        let args = params
+            .into_iter()
+            .map(|ty| self.expr(ty, depth))
+            .collect::<Vec<_>>();
+
+        Some(format!("{}({})", name, args.join(", ")))
+    }
+
+    /// Returns a variable or literal of type `ty`.
+    fn leaf(&mut self, ty: Ty) -> String {
+        let vars = self
+            .vars
+            .iter()
+            .filter(|var| var.ty == ty)
+            .collect::<Vec<_>>();
+
+        if !vars.is_empty() && self.rng.chance(60) {
+            return self.rng.choose(&vars).name.clone();
+        }
+
+        match ty {
+            Ty::Int => self.rng.below(100).to_string(),
+            Ty::Float => format!("{}.{}", self.rng.below(100), self.rng.below(10)),
+            Ty::Bool => self.rng.chance(50).to_string(),
+        }
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.names += 1;
+        format!("{}{}", prefix, self.names)
+    }
+
+    fn line(&mut self, line: String) {
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+
+        self.out.push_str(&line);
+        self.out.push('\n');
+    }
+}
@@ -0,0 +1,134 @@
+use crate::parse::Ast;
+use crate::ty::Ty;
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// A C header declaring the exported functions of a program, to be written
+/// to `path`, so that C can call them without writing the declarations by
+/// hand.
+///
+/// Structs are only declared, as C can't see their fields, and tuples are
+/// pointed to by `void *`. Strings are C strings where they are passed or
+/// returned, and pointers to `struct ripc_str` anywhere else.
+pub fn header(ast: &Ast, path: &Path) -> String {
+    let mut structs = BTreeSet::new();
+    let mut prototypes = Vec::new();
+
+    for func in ast.functions.iter().filter(|func| func.export) {
+        let params = func
+            .params
+            .iter()
+            .map(|param| {
+                // an unnamed parameter is still valid C
+                let name = match C_KEYWORDS.contains(&param.name.value.as_str()) {
+                    true => "",
+                    false => &param.name.value,
+                };
+
+                declare(&param.ty.value, name, true, &mut structs)
+            })
+            .collect::<Vec<_>>();
+
+        let params = match params.is_empty() {
+            true => "void".to_owned(),
+            false => params.join(", "),
+        };
+
+        let ret = func.ret.as_ref().map_or(&Ty::Void, |ret| &ret.value);
+        let name = format!("{}({})", func.name.value, params);
+        prototypes.push(format!("{};\n", declare(ret, &name, true, &mut structs)));
+    }
+
+    let guard = guard(path);
+    let mut header = format!(
+        "#ifndef {}\n#define {}\n\n#include <stdbool.h>\n\n",
+        guard, guard
+    );
+
+    if !structs.is_empty() {
+        for name in structs {
+            header.push_str(&format!("struct {};\n", name));
+        }
+
+        header.push('\n');
+    }
+
+    for prototype in prototypes {
+        header.push_str(&prototype);
+    }
+
+    header.push_str(&format!("\n#endif /* {} */\n", guard));
+    header
+}
+
+/// The C declaration of `name` as a value of type `ty`, such as
+/// `int (*name)[4]`, where `name` can also be a function and its
+/// parameters. Structs are added to `structs`, to be declared before they
+/// are used.
+fn declare(ty: &Ty, name: &str, c_string: bool, structs: &mut BTreeSet<String>) -> String {
+    let base = match ty {
+        Ty::Int => "int",
+        Ty::Float => "double",
+        Ty::Bool => "bool",
+        Ty::Void | Ty::Tuple(_) => "void",
+        Ty::Str if c_string => "const char *",
+        Ty::Str => {
+            structs.insert("ripc_str".to_owned());
+            "struct ripc_str *"
+        }
+        Ty::Struct(s) => {
+            structs.insert(s.name.clone());
+            return format!("struct {} {}", s.name, name).trim_end().to_owned();
+        }
+        // the declarator of a pointer to an array is parenthesized, as `[]`
+        // binds more tightly than `*`
+        Ty::Ptr(to) => {
+            let name = match **to {
+                Ty::Array(..) => format!("(*{})", name),
+                _ => format!("*{}", name),
+            };
+
+            return declare(to, &name, false, structs);
+        }
+        Ty::Array(elem, len) => {
+            return declare(elem, &format!("{}[{}]", name, len), false, structs)
+        }
+        Ty::Infer => unreachable!("uninferred type in signature"),
+    };
+
+    match base.ends_with('*') {
+        true => format!("{}{}", base, name),
+        false => format!("{} {}", base, name).trim_end().to_owned(),
+    }
+}
+
+/// The include guard of the header, its file name in upper case with
+/// anything that can't be part of an identifier replaced by `_`.
+fn guard(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let guard = name
+        .chars()
+        .map(|ch| match ch.is_ascii_alphanumeric() {
+            true => ch.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+
+    match guard.starts_with(|ch: char| ch.is_ascii_digit()) {
+        true => format!("_{}", guard),
+        false => guard,
+    }
+}
+
+/// Names that can't be used as parameter names in C.
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while",
+];
@@ -0,0 +1,306 @@
+use crate::parse::{Ast, BinaryOp, Expr, ExprKind, SizeOf, UnaryOp};
+use crate::source::SourceMap;
+use crate::{Failure, Report, Span, Spanned};
+
+use std::collections::{HashMap, HashSet};
+use std::slice;
+
+/// Checks that every variable is assigned on every path leading to a read of
+/// it, as its stack slot holds whatever was there before otherwise.
+///
+/// Each variable is reported once, at its first such read.
+pub fn check(ast: &Ast) -> Result<(), Vec<Error>> {
+    let mut errors = flow(ast, &ast.exprs, &[]);
+
+    // functions have their own variables and labels, and their parameters
+    // are assigned by the caller
+    for def in &ast.defs {
+        errors.extend(flow(ast, slice::from_ref(&def.body), &def.params));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Checks a sequence of statements, with the given variables assigned before
+/// the first.
+fn flow<'a>(ast: &'a Ast, exprs: &'a [Expr], assigned: &[usize]) -> Vec<Error> {
+    let mut flow = Flow {
+        ast,
+        labels: HashMap::new(),
+        errors: None,
+        reported: HashSet::new(),
+    };
+
+    let mut entry = vec![false; ast.vars.len()];
+    for &var in assigned {
+        entry[var] = true;
+    }
+
+    // a label is reached by falling through to it and by every `goto`
+    // targeting it, which may come later in the program, so the state at
+    // each label is refined until it stops changing
+    loop {
+        let labels = flow.labels.clone();
+        flow.run(exprs, entry.clone());
+
+        if flow.labels == labels {
+            break;
+        }
+    }
+
+    // the states at labels are final, report any reads they allow
+    flow.errors = Some(Vec::new());
+    flow.run(exprs, entry);
+    flow.errors.unwrap_or_default()
+}
+
+/// The variables that have been assigned on every path to a point in the
+/// program. Unreachable code is treated as if every variable is assigned.
+type State = Vec<bool>;
+
+struct Flow<'a> {
+    ast: &'a Ast,
+    /// The state at each label, on any `goto` targeting it seen so far.
+    labels: HashMap<&'a str, State>,
+    /// Reads of unassigned variables, collected on the final pass.
+    errors: Option<Vec<Error>>,
+    /// The variables that have been reported.
+    reported: HashSet<usize>,
+}
+
+impl<'a> Flow<'a> {
+    fn run(&mut self, exprs: &'a [Expr], mut state: State) {
+        for expr in exprs {
+            self.expr(expr, &mut state);
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expr, state: &mut State) {
+        match expr.kind {
+            ExprKind::Lit(_) | ExprKind::SizeOf(SizeOf::Ty(_)) => {}
+            // the operand of `sizeof` is never evaluated
+            ExprKind::SizeOf(SizeOf::Expr(_)) => {}
+            ExprKind::Var(i) => {
+                if !state[i] {
+                    self.report(i, expr.span);
+                }
+            }
+            ExprKind::Binary(ref binary) if binary.op.value == BinaryOp::Assign => {
+                self.expr(&binary.right, state);
+
+                // the places of a destructuring assignment are each assigned
+                let places = match binary.left.kind {
+                    ExprKind::Tuple(ref places) => places.as_slice(),
+                    _ => std::slice::from_ref(&*binary.left),
+                };
+
+                for place in places {
+                    match place.kind {
+                        ExprKind::Var(i) => state[i] = true,
+                        _ => self.expr(place, state),
+                    }
+                }
+            }
+            // the right operand of `&&` and `||` may not be evaluated
+            ExprKind::Binary(ref binary) if binary.op.value.is_logical() => {
+                self.expr(&binary.left, state);
+                self.expr(&binary.right, &mut state.clone());
+            }
+            ExprKind::Binary(ref binary) => {
+                self.expr(&binary.left, state);
+                self.expr(&binary.right, state);
+            }
+            // a variable whose address is taken may be assigned through it
+            ExprKind::Unary(ref unary) if unary.op.value == UnaryOp::AddrOf => {
+                match unary.expr.kind {
+                    ExprKind::Var(i) => state[i] = true,
+                    _ => self.expr(&unary.expr, state),
+                }
+            }
+            ExprKind::Unary(ref unary) => self.expr(&unary.expr, state),
+            ExprKind::Call(ref call) => {
+                for arg in &call.args {
+                    self.expr(arg, state);
+                }
+            }
+            ExprKind::Match(ref expr) => {
+                self.expr(&expr.scrutinee, state);
+
+                // without a default arm, a value matching no arm skips
+                // every body
+                let mut end = match expr.default {
+                    Some(ref body) => {
+                        let mut end = state.clone();
+                        self.expr(body, &mut end);
+                        end
+                    }
+                    None => state.clone(),
+                };
+
+                for arm in &expr.arms {
+                    let mut arm_state = state.clone();
+                    self.expr(&arm.body, &mut arm_state);
+                    meet(&mut end, &arm_state);
+                }
+
+                *state = end;
+            }
+            ExprKind::If(ref expr) => {
+                self.expr(&expr.cond, state);
+
+                let mut end = state.clone();
+                if let Some(ref otherwise) = expr.otherwise {
+                    self.expr(otherwise, &mut end);
+                }
+
+                let mut then = state.clone();
+                self.expr(&expr.then, &mut then);
+                meet(&mut end, &then);
+
+                *state = end;
+            }
+            // the body may not run at all, and anything it assigns is
+            // assigned again by the time it reads it on a later iteration
+            ExprKind::While(ref expr) => {
+                self.expr(&expr.cond, state);
+
+                let mut body = state.clone();
+                self.expr(&expr.body, &mut body);
+
+                // reached by `continue`, which may come before any of the
+                // body's assignments
+                if let Some(ref step) = expr.step {
+                    let mut step_state = state.clone();
+                    self.expr(step, &mut step_state);
+                }
+            }
+            ExprKind::Return(ref value) => {
+                if let Some(ref value) = value {
+                    self.expr(value, state);
+                }
+
+                state.fill(true);
+            }
+            // nothing after a `break` or `continue` in a block is reached
+            ExprKind::Break | ExprKind::Continue => state.fill(true),
+            ExprKind::Label(ref label) => {
+                if let Some(from) = self.labels.get(label.as_str()) {
+                    meet(state, from);
+                }
+            }
+            ExprKind::Goto(ref label) => {
+                match self.labels.get_mut(label.as_str()) {
+                    Some(to) => meet(to, state),
+                    None => {
+                        self.labels.insert(label, state.clone());
+                    }
+                }
+
+                state.fill(true);
+            }
+            ExprKind::Let(ref expr) => {
+                self.expr(&expr.init, state);
+                state[expr.var] = true;
+            }
+            ExprKind::Array(ref elems) | ExprKind::Tuple(ref elems) => {
+                for elem in elems {
+                    self.expr(elem, state);
+                }
+            }
+            ExprKind::Repeat(ref repeat) => self.expr(&repeat.value, state),
+            ExprKind::Index(ref index) => {
+                self.expr(&index.base, state);
+                self.expr(&index.index, state);
+            }
+            ExprKind::Slice(ref slice) => {
+                self.expr(&slice.base, state);
+
+                for bound in slice.start.iter().chain(&slice.end) {
+                    self.expr(bound, state);
+                }
+            }
+            ExprKind::Struct(ref lit) => {
+                for (_, value) in &lit.fields {
+                    self.expr(value, state);
+                }
+            }
+            ExprKind::Field(ref field) => self.expr(&field.base, state),
+            ExprKind::Cast(ref cast) => self.expr(&cast.expr, state),
+            ExprKind::Block(ref block) => {
+                for stmt in &block.stmts {
+                    self.expr(stmt, state);
+                }
+
+                if let Some(ref value) = block.value {
+                    self.expr(value, state);
+                }
+            }
+        }
+    }
+
+    fn report(&mut self, var: usize, span: Span) {
+        if let Some(ref mut errors) = self.errors {
+            if !self.reported.insert(var) {
+                return;
+            }
+
+            let name = self.ast.symbols.get(self.ast.vars[var].name);
+            errors.push(Error::new(ErrorKind::Uninitialized(name.to_string()), span));
+        }
+    }
+}
+
+/// Narrows `state` to the variables also assigned in `other`, where two paths
+/// join.
+fn meet(state: &mut State, other: &State) {
+    for (assigned, other) in state.iter_mut().zip(other) {
+        *assigned &= other;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Error {
+    fn new(kind: ErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    Uninitialized(String),
+}
+
+impl Spanned for Error {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::Uninitialized(..) => "E0401",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        match self.kind {
+            ErrorKind::Uninitialized(ref name) => {
+                format!("'{}' is read before it is assigned", name)
+            }
+        }
+    }
+
+    fn failure(&self) -> Failure {
+        Failure::Semantic
+    }
+}
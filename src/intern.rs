@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// An interned string, cheap to copy and compare.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Symbol(u32);
+
+/// Stores every distinct string once, handing out a `Symbol` for each.
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+pub struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    strings: Vec<Rc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, str: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(str) {
+            return symbol;
+        }
+
+        let symbol = Symbol(self.strings.len() as u32);
+        let str: Rc<str> = str.into();
+        self.strings.push(str.clone());
+        self.symbols.insert(str, symbol);
+        symbol
+    }
+
+    /// Returns the string of a symbol, which can be cloned to share its
+    /// storage.
+    pub fn get(&self, symbol: Symbol) -> &Rc<str> {
+        &self.strings[symbol.0 as usize]
+    }
+}
@@ -1,9 +1,8 @@
-use crate::{Report, Reporter, Span, Spanned};
+use crate::source::SourceMap;
+use crate::{Failure, Report, Span, Spanned};
 
 use std::fmt;
-use std::io::Write;
-use std::iter::Peekable;
-use std::str::Chars;
+use std::io::{self, Read};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Token<'a> {
@@ -11,52 +10,132 @@ pub struct Token<'a> {
     pub span: Span,
 }
 
+impl Token<'_> {
+    fn error(err: Error) -> Self {
+        Self {
+            kind: TokenKind::Error(err.kind),
+            span: err.span,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TokenKind<'a> {
     Add,
     Sub,
     Mul,
     Div,
+    Amp,
+    AndAnd,
+    OrOr,
+    Not,
     Semi,
     Assign,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
     Num(usize),
+    /// A floating point literal, `1.5`.
+    Float(&'a str),
     Str(&'a str),
     Ident(&'a str),
     Whitespace,
     OpenParen,
     CloseParen,
     Comma,
+    Colon,
+    /// `::`, between the segments of a path.
+    PathSep,
+    Dot,
+    /// `..`, between the bounds of a slice.
+    DotDot,
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    FatArrow,
+    Arrow,
+    Ellipsis,
+    Underscore,
+    Break,
+    Continue,
+    If,
+    Else,
+    While,
+    For,
+    Return,
+    Match,
+    Goto,
+    Extern,
+    Export,
+    Fn,
+    Let,
+    Struct,
+    Import,
+    Mod,
+    Pub,
+    Use,
+    True,
+    False,
+    SizeOf,
+    As,
+    /// Input that could not be lexed, only produced by a tolerant lexer.
+    Error(ErrorKind),
 }
 
 pub struct Lexer<'a> {
-    chars: Peekable<Chars<'a>>,
     source: &'a str,
+    /// The span of the current token, relative to `source`.
     span: Span,
+    /// The offset of `source` in the source map.
+    offset: usize,
     eof: bool,
+    tolerant: bool,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(source: &'a str) -> Self {
+    pub fn new(source: &'a str, offset: usize) -> Self {
         Self {
-            chars: source.chars().peekable(),
             source,
             span: Span::default(),
+            offset,
             eof: false,
+            tolerant: false,
         }
     }
 
-    fn peek(&mut self) -> Option<char> {
-        self.chars.peek().copied()
+    /// Returns errors as `TokenKind::Error` tokens and keeps lexing after
+    /// them, for tools that work on broken source.
+    pub fn tolerant(mut self) -> Self {
+        self.tolerant = true;
+        self
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.peek_n(0)
+    }
+
+    fn peek_n(&self, n: usize) -> Option<u8> {
+        self.source.as_bytes().get(self.span.end + n).copied()
     }
 
-    fn peek_n(&self, n: usize) -> Option<char> {
-        self.chars.clone().nth(n)
+    /// Returns the (possibly multi-byte) character at the current position.
+    fn peek_char(&self) -> Option<char> {
+        self.source[self.span.end..].chars().next()
     }
 
-    fn chomp(&mut self) -> Option<char> {
-        self.chars.next().map(|x| {
+    fn chomp(&mut self) -> Option<u8> {
+        self.peek().inspect(|_| {
             self.span.end += 1;
-            x
+        })
+    }
+
+    fn chomp_char(&mut self) -> Option<char> {
+        self.peek_char().inspect(|ch| {
+            self.span.end += ch.len_utf8();
         })
     }
 
@@ -68,17 +147,36 @@ impl<'a> Lexer<'a> {
         self.span.start = self.span.end;
     }
 
-    fn chomp_while(&mut self, f: impl Fn(&char) -> bool + Copy) {
-        while self.peek().map(|x| f(&x)).unwrap_or(false) {
+    fn chomp_while(&mut self, f: impl Fn(u8) -> bool) {
+        while self.peek().is_some_and(&f) {
             self.chomp();
         }
     }
 
+    fn chomp_ident(&mut self) {
+        while let Some(ch) = self.peek_char() {
+            if !(ch.is_alphanumeric() || ch == '_') {
+                break;
+            }
+
+            self.chomp_char();
+        }
+    }
+
+    /// The span of the current token in the source map.
+    fn global_span(&self) -> Span {
+        Span::new(self.span.start + self.offset..self.span.end + self.offset)
+    }
+
+    fn error(&self, kind: ErrorKind) -> Error {
+        Error::new(kind, self.global_span())
+    }
+
     pub fn current_span(&self) -> Span {
         if self.eof {
             Span::EOF
         } else {
-            self.span
+            self.global_span()
         }
     }
 }
@@ -87,53 +185,167 @@ impl<'a> Iterator for Lexer<'a> {
     type Item = Result<Token<'a>, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        match self.lex()? {
+            Err(err) if self.tolerant => Some(Ok(Token::error(err))),
+            result => Some(result),
+        }
+    }
+}
+
+impl<'a> Lexer<'a> {
+    fn lex(&mut self) -> Option<Result<Token<'a>, Error>> {
         use ErrorKind::*;
         use TokenKind::*;
 
         self.reset();
-        if let Some(ch) = self.chomp() {
+        if let Some(ch) = self.peek() {
+            // non-ASCII characters can only start identifiers
+            if !ch.is_ascii() {
+                let ch = self.chomp_char().unwrap();
+                if !ch.is_alphabetic() {
+                    return Some(Err(self.error(InvalidCharacter(ch))));
+                }
+            } else {
+                self.chomp();
+            }
+
             let kind = match ch {
-                '+' => Add,
-                '-' => Sub,
-                '/' => Div,
-                '*' => Mul,
-                '0'..='9' => {
-                    self.chomp_while(char::is_ascii_digit);
-                    Num(self.slice().parse().unwrap())
+                b'+' => Add,
+                b'-' if self.peek() == Some(b'>') => {
+                    self.chomp();
+                    Arrow
+                }
+                b'-' => Sub,
+                b'/' => Div,
+                b'*' => Mul,
+                b'&' if self.peek() == Some(b'&') => {
+                    self.chomp();
+                    AndAnd
+                }
+                b'&' => Amp,
+                b'|' if self.peek() == Some(b'|') => {
+                    self.chomp();
+                    OrOr
+                }
+                b'0'..=b'9' => {
+                    self.chomp_while(|b| b.is_ascii_digit());
+
+                    if self.peek() == Some(b'.')
+                        && self.peek_n(1).is_some_and(|b| b.is_ascii_digit())
+                    {
+                        self.chomp();
+                        self.chomp_while(|b| b.is_ascii_digit());
+                        Float(self.slice())
+                    } else {
+                        match self.slice().parse() {
+                            Ok(num) => Num(num),
+                            Err(_) => return Some(Err(self.error(NumberTooLarge))),
+                        }
+                    }
                 }
                 ch if ch.is_ascii_whitespace() => {
-                    self.chomp_while(char::is_ascii_whitespace);
+                    self.chomp_while(|b| b.is_ascii_whitespace());
                     Whitespace
                 }
-                '"' => loop {
+                b'"' => loop {
                     match self.peek() {
-                        Some('"') => {
+                        Some(b'"') => {
                             self.chomp();
                             let str = self.slice();
                             break TokenKind::Str(&str[1..str.len() - 1]);
                         }
-                        Some('\\') if matches!(self.peek_n(1), Some('\\') | Some('"')) => {
+                        Some(b'\\') if matches!(self.peek_n(1), Some(b'\\') | Some(b'"')) => {
                             self.chomp();
                         }
                         Some(_) => {}
                         None => {
                             self.eof = true;
-                            return Some(Err(Error::new(UnexpectedEof, self.span)));
+                            return Some(Err(self.error(UnexpectedEof)));
                         }
                     }
 
                     self.chomp();
                 },
-                ';' => TokenKind::Semi,
-                '=' => TokenKind::Assign,
-                '(' => TokenKind::OpenParen,
-                ')' => TokenKind::CloseParen,
-                ',' => TokenKind::Comma,
-                ch if ch.is_alphabetic() => {
-                    self.chomp_while(|c| c.is_alphanumeric());
-                    TokenKind::Ident(self.slice())
-                }
-                ch => return Some(Err(Error::new(InvalidCharacter(ch), self.span))),
+                b';' => TokenKind::Semi,
+                b'=' if self.peek() == Some(b'>') => {
+                    self.chomp();
+                    TokenKind::FatArrow
+                }
+                b'=' if self.peek() == Some(b'=') => {
+                    self.chomp();
+                    TokenKind::Eq
+                }
+                b'=' => TokenKind::Assign,
+                b'!' if self.peek() == Some(b'=') => {
+                    self.chomp();
+                    TokenKind::Ne
+                }
+                b'!' => TokenKind::Not,
+                b'<' if self.peek() == Some(b'=') => {
+                    self.chomp();
+                    TokenKind::Le
+                }
+                b'<' => TokenKind::Lt,
+                b'>' if self.peek() == Some(b'=') => {
+                    self.chomp();
+                    TokenKind::Ge
+                }
+                b'>' => TokenKind::Gt,
+                b'(' => TokenKind::OpenParen,
+                b')' => TokenKind::CloseParen,
+                b',' => TokenKind::Comma,
+                b':' if self.peek() == Some(b':') => {
+                    self.chomp();
+                    TokenKind::PathSep
+                }
+                b':' => TokenKind::Colon,
+                b'.' if self.peek() == Some(b'.') && self.peek_n(1) == Some(b'.') => {
+                    self.chomp();
+                    self.chomp();
+                    TokenKind::Ellipsis
+                }
+                b'.' if self.peek() == Some(b'.') => {
+                    self.chomp();
+                    TokenKind::DotDot
+                }
+                b'.' => TokenKind::Dot,
+                b'{' => TokenKind::OpenBrace,
+                b'}' => TokenKind::CloseBrace,
+                b'[' => TokenKind::OpenBracket,
+                b']' => TokenKind::CloseBracket,
+                ch if ch.is_ascii_alphabetic() || ch == b'_' || !ch.is_ascii() => {
+                    self.chomp_ident();
+                    match self.slice() {
+                        "_" => TokenKind::Underscore,
+                        "break" => TokenKind::Break,
+                        "continue" => TokenKind::Continue,
+                        "if" => TokenKind::If,
+                        "else" => TokenKind::Else,
+                        "while" => TokenKind::While,
+                        "for" => TokenKind::For,
+                        "return" => TokenKind::Return,
+                        "match" => TokenKind::Match,
+                        "goto" => TokenKind::Goto,
+                        "extern" => TokenKind::Extern,
+                        "export" => TokenKind::Export,
+                        "fn" => TokenKind::Fn,
+                        "let" => TokenKind::Let,
+                        "struct" => TokenKind::Struct,
+                        "import" => TokenKind::Import,
+                        "mod" => TokenKind::Mod,
+                        "pub" => TokenKind::Pub,
+                        "use" => TokenKind::Use,
+                        "true" => TokenKind::True,
+                        "false" => TokenKind::False,
+                        "sizeof" => TokenKind::SizeOf,
+                        "as" => TokenKind::As,
+                        ident => TokenKind::Ident(ident),
+                    }
+                }
+                ch => {
+                    let ch = ch as char;
+                    return Some(Err(self.error(InvalidCharacter(ch))));
+                }
             };
 
             if self.peek().is_none() {
@@ -142,7 +354,7 @@ impl<'a> Iterator for Lexer<'a> {
 
             let token = Token {
                 kind,
-                span: self.span,
+                span: self.global_span(),
             };
 
             return Some(Ok(token));
@@ -153,22 +365,240 @@ impl<'a> Iterator for Lexer<'a> {
     }
 }
 
+/// A lexer that reads its source from an `io::Read` in chunks, buffering
+/// only the bytes of the token being lexed.
+///
+/// Tokens borrow the buffer, so each one has to be dropped before the next
+/// is lexed. Spans are offsets from the start of the input.
+pub struct StreamLexer<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// The number of bytes at the start of `buf` that have been lexed.
+    pos: usize,
+    /// The offset of the start of `buf` in the input.
+    offset: usize,
+    eof: bool,
+    tolerant: bool,
+    /// Whether a byte order mark at the start of the input has been looked
+    /// for. Spans start after it, as in the source map.
+    bom: bool,
+    /// A failure to read, returned once the tokens read before it are.
+    failed: Option<Error>,
+}
+
+impl<R: Read> StreamLexer<R> {
+    const CHUNK: usize = 4096;
+
+    /// The number of bytes past the end of a token that the lexer can look
+    /// at, as in `1.5` or `...`.
+    const LOOKAHEAD: usize = 2;
+
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            offset: 0,
+            eof: false,
+            tolerant: false,
+            bom: false,
+            failed: None,
+        }
+    }
+
+    /// Returns errors other than failures to read as `TokenKind::Error`
+    /// tokens and keeps lexing after them, see `Lexer::tolerant`.
+    pub fn tolerant(mut self) -> Self {
+        self.tolerant = true;
+        self
+    }
+
+    pub fn next_token(&mut self) -> Option<Result<Token<'_>, Error>> {
+        // drop the lexed bytes once they make up most of the buffer
+        if self.pos > self.buf.len() / 2 {
+            self.buf.drain(..self.pos);
+            self.offset += self.pos;
+            self.pos = 0;
+        }
+
+        // read until the buffer holds a token that can't be extended by
+        // more input
+        let end = loop {
+            let (source, complete) = match std::str::from_utf8(&self.buf[self.pos..]) {
+                Ok(source) => (source, self.eof),
+                // a character split across chunks
+                Err(err) if err.error_len().is_none() && !self.eof => {
+                    let valid = &self.buf[self.pos..self.pos + err.valid_up_to()];
+                    (std::str::from_utf8(valid).unwrap(), false)
+                }
+                Err(err) if err.valid_up_to() == 0 => {
+                    let len = err.error_len().unwrap_or(self.buf.len() - self.pos);
+                    let at = self.offset + self.pos;
+                    let err = Error::new(ErrorKind::InvalidUtf8, Span::new(at..at + len));
+
+                    if !self.tolerant {
+                        self.eof = true;
+                        self.pos = self.buf.len();
+                        return Some(Err(err));
+                    }
+
+                    self.pos += len;
+                    return Some(Ok(Token::error(err)));
+                }
+                // lex up to the invalid bytes
+                Err(err) => {
+                    let valid = &self.buf[self.pos..self.pos + err.valid_up_to()];
+                    (std::str::from_utf8(valid).unwrap(), true)
+                }
+            };
+
+            if !self.bom && (source.len() >= 3 || complete) {
+                self.bom = true;
+
+                if source.starts_with('\u{feff}') {
+                    self.buf.drain(..3);
+                    continue;
+                }
+            }
+
+            let start = self.offset + self.pos;
+            let end = match Lexer::new(source, start).next() {
+                Some(Ok(token))
+                    if complete || token.span.end - start + Self::LOOKAHEAD <= source.len() =>
+                {
+                    Some(token.span.end - start)
+                }
+                // an error can also be extended by more input, such as the
+                // digits of a literal that is too large
+                Some(Err(err))
+                    if complete
+                        || (err.kind != ErrorKind::UnexpectedEof
+                            && err.span.end - start + Self::LOOKAHEAD <= source.len()) =>
+                {
+                    if !self.tolerant {
+                        None
+                    } else {
+                        Some(err.span.end - start)
+                    }
+                }
+                None if complete => return self.failed.take().map(Err),
+                _ => {
+                    // the input ends where reading it failed
+                    if let Err(err) = self.fill() {
+                        let at = self.offset + self.buf.len();
+                        let err = Error::new(ErrorKind::Io(err.kind()), Span::new(at..at));
+                        self.failed = Some(err);
+                        self.eof = true;
+                    }
+
+                    continue;
+                }
+            };
+
+            break end;
+        };
+
+        let start = self.pos;
+        let offset = self.offset + start;
+
+        match end {
+            Some(end) => self.pos += end,
+            // errors end the input
+            None => {
+                self.pos = self.buf.len();
+                self.eof = true;
+            }
+        }
+
+        let source = std::str::from_utf8(&self.buf[start..self.pos]).unwrap_or_default();
+        let mut lexer = Lexer::new(source, offset);
+        lexer.tolerant = self.tolerant;
+        lexer.next()
+    }
+
+    /// Reads the next chunk of input into the buffer.
+    fn fill(&mut self) -> io::Result<()> {
+        let len = self.buf.len();
+        self.buf.resize(len + Self::CHUNK, 0);
+
+        let read = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+
+        let read = read.inspect_err(|_| self.buf.truncate(len))?;
+        self.buf.truncate(len + read);
+
+        if read == 0 {
+            self.eof = true;
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> fmt::Display for TokenKind<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let x = match *self {
             TokenKind::Add => "+",
-            TokenKind::Sub => "+",
+            TokenKind::Sub => "-",
             TokenKind::Mul => "*",
             TokenKind::Div => "/",
+            TokenKind::Amp => "&",
             TokenKind::Whitespace => " ",
+            TokenKind::AndAnd => "&&",
+            TokenKind::OrOr => "||",
+            TokenKind::Not => "!",
             TokenKind::Semi => ";",
             TokenKind::Assign => "=",
+            TokenKind::Eq => "==",
+            TokenKind::Ne => "!=",
+            TokenKind::Lt => "<",
+            TokenKind::Le => "<=",
+            TokenKind::Gt => ">",
+            TokenKind::Ge => ">=",
             TokenKind::OpenParen => "(",
             TokenKind::CloseParen => ")",
             TokenKind::Comma => ",",
-            TokenKind::Str(str) => str,
+            TokenKind::Colon => ":",
+            TokenKind::PathSep => "::",
+            TokenKind::Dot => ".",
+            TokenKind::OpenBrace => "{",
+            TokenKind::CloseBrace => "}",
+            TokenKind::OpenBracket => "[",
+            TokenKind::CloseBracket => "]",
+            TokenKind::FatArrow => "=>",
+            TokenKind::Arrow => "->",
+            TokenKind::Ellipsis => "...",
+            TokenKind::DotDot => "..",
+            TokenKind::Underscore => "_",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::While => "while",
+            TokenKind::For => "for",
+            TokenKind::Return => "return",
+            TokenKind::Match => "match",
+            TokenKind::Goto => "goto",
+            TokenKind::Extern => "extern",
+            TokenKind::Export => "export",
+            TokenKind::Fn => "fn",
+            TokenKind::Let => "let",
+            TokenKind::Struct => "struct",
+            TokenKind::Import => "import",
+            TokenKind::Mod => "mod",
+            TokenKind::Pub => "pub",
+            TokenKind::Use => "use",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::SizeOf => "sizeof",
+            TokenKind::As => "as",
+            TokenKind::Str(str) | TokenKind::Float(str) | TokenKind::Ident(str) => str,
             TokenKind::Num(num) => return write!(f, "{}", num),
-            TokenKind::Ident(_) => todo!(),
+            TokenKind::Error(kind) => return write!(f, "{}", kind),
         };
 
         write!(f, "{}", x)
@@ -191,6 +621,10 @@ impl Error {
 pub enum ErrorKind {
     UnexpectedEof,
     InvalidCharacter(char),
+    InvalidUtf8,
+    /// An integer literal that doesn't fit in 64 bits.
+    NumberTooLarge,
+    Io(io::ErrorKind),
 }
 
 impl Spanned for Error {
@@ -199,11 +633,37 @@ impl Spanned for Error {
     }
 }
 
-impl<W: Write> Report<W> for Error {
-    fn report(&self, f: &mut Reporter<'_, W>) -> std::io::Result<()> {
+impl Report for Error {
+    fn code(&self) -> &'static str {
         match self.kind {
-            ErrorKind::InvalidCharacter(ch) => write!(f.out, "Invalid character '{}'", ch),
-            ErrorKind::UnexpectedEof => write!(f.out, "Found unexpected EOF"),
+            ErrorKind::UnexpectedEof => "E0001",
+            ErrorKind::InvalidCharacter(..) => "E0002",
+            ErrorKind::InvalidUtf8 => "E0003",
+            ErrorKind::NumberTooLarge => "E0004",
+            ErrorKind::Io(..) => "E0005",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        self.kind.to_string()
+    }
+
+    fn failure(&self) -> Failure {
+        match self.kind {
+            ErrorKind::Io(_) => Failure::Environment,
+            _ => Failure::Syntax,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ErrorKind::InvalidCharacter(ch) => write!(f, "Invalid character '{}'", ch),
+            ErrorKind::UnexpectedEof => write!(f, "Found unexpected EOF"),
+            ErrorKind::InvalidUtf8 => write!(f, "Invalid UTF-8"),
+            ErrorKind::NumberTooLarge => write!(f, "Integer literal is too large"),
+            ErrorKind::Io(kind) => write!(f, "Failed to read input: {}", kind),
         }
     }
 }
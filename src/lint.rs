@@ -0,0 +1,285 @@
+use crate::eval::{self, Value};
+use crate::intern::Symbol;
+use crate::parse::{Ast, BinaryOp, Expr, ExprKind, Lit, SizeOf};
+use crate::source::SourceMap;
+use crate::{Failure, Report, Span, Spanned};
+
+use std::collections::HashMap;
+
+/// The lints that are off unless asked for, each enabled by `--warn=<name>`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Lints {
+    /// Declarations that shadow a variable of an enclosing block.
+    pub shadow: bool,
+}
+
+/// Looks for code that compiles but is likely a mistake. Unlike errors,
+/// warnings don't stop the program from being compiled.
+///
+/// Conditions that are always true or always false are always reported,
+/// other lints only when enabled.
+pub fn check(ast: &Ast, lints: Lints) -> Vec<Warning> {
+    let mut linter = Linter {
+        ast,
+        lints,
+        scopes: vec![HashMap::new()],
+        warnings: Vec::new(),
+    };
+
+    for expr in &ast.exprs {
+        linter.expr(expr);
+    }
+
+    // functions can't see the variables of the program
+    for def in &ast.defs {
+        let params = def
+            .params
+            .iter()
+            .map(|&var| (ast.vars[var].name, var))
+            .collect();
+
+        linter.scopes = vec![params];
+        linter.expr(&def.body);
+    }
+
+    linter.warnings.sort_by_key(|warning| warning.span.start);
+    linter.warnings
+}
+
+struct Linter<'a> {
+    ast: &'a Ast,
+    lints: Lints,
+    /// The variables declared in each enclosing block, innermost last.
+    scopes: Vec<HashMap<Symbol, usize>>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter<'_> {
+    fn expr(&mut self, expr: &Expr) {
+        match expr.kind {
+            ExprKind::Lit(_)
+            | ExprKind::SizeOf(SizeOf::Ty(_))
+            | ExprKind::Break
+            | ExprKind::Continue
+            | ExprKind::Label(_)
+            | ExprKind::Goto(_) => {}
+            // the operand of `sizeof` is never evaluated
+            ExprKind::SizeOf(SizeOf::Expr(_)) => {}
+            // a variable that is assigned without being declared is declared
+            // in the innermost block
+            ExprKind::Var(i) => {
+                let name = self.ast.vars[i].name;
+                if !self.scopes.iter().any(|scope| scope.get(&name) == Some(&i)) {
+                    self.scopes.last_mut().unwrap().insert(name, i);
+                }
+            }
+            ExprKind::Let(ref expr) => {
+                self.expr(&expr.init);
+                self.declare(expr.var);
+            }
+            ExprKind::Binary(ref binary) => {
+                self.expr(&binary.left);
+                self.expr(&binary.right);
+            }
+            ExprKind::Unary(ref unary) => self.expr(&unary.expr),
+            ExprKind::Call(ref call) => {
+                for arg in &call.args {
+                    self.expr(arg);
+                }
+            }
+            ExprKind::Match(ref expr) => {
+                self.expr(&expr.scrutinee);
+
+                for arm in &expr.arms {
+                    self.expr(&arm.body);
+                }
+
+                if let Some(ref body) = expr.default {
+                    self.expr(body);
+                }
+            }
+            ExprKind::If(ref expr) => {
+                self.condition(&expr.cond);
+                self.expr(&expr.cond);
+                self.expr(&expr.then);
+
+                if let Some(ref otherwise) = expr.otherwise {
+                    self.expr(otherwise);
+                }
+            }
+            ExprKind::While(ref expr) => {
+                // `while true` is how an infinite loop is written
+                if !matches!(expr.cond.kind, ExprKind::Lit(ref lit) if lit.value == Lit::Bool(true))
+                {
+                    self.condition(&expr.cond);
+                }
+
+                self.expr(&expr.cond);
+                self.expr(&expr.body);
+
+                if let Some(ref step) = expr.step {
+                    self.expr(step);
+                }
+            }
+            ExprKind::Return(ref value) => {
+                if let Some(ref value) = value {
+                    self.expr(value);
+                }
+            }
+            ExprKind::Array(ref elems) | ExprKind::Tuple(ref elems) => {
+                for elem in elems {
+                    self.expr(elem);
+                }
+            }
+            ExprKind::Repeat(ref repeat) => self.expr(&repeat.value),
+            ExprKind::Index(ref index) => {
+                self.expr(&index.base);
+                self.expr(&index.index);
+            }
+            ExprKind::Slice(ref slice) => {
+                self.expr(&slice.base);
+
+                for bound in slice.start.iter().chain(&slice.end) {
+                    self.expr(bound);
+                }
+            }
+            ExprKind::Struct(ref lit) => {
+                for (_, value) in &lit.fields {
+                    self.expr(value);
+                }
+            }
+            ExprKind::Field(ref field) => self.expr(&field.base),
+            ExprKind::Cast(ref cast) => self.expr(&cast.expr),
+            ExprKind::Block(ref block) => {
+                self.scopes.push(HashMap::new());
+
+                for stmt in &block.stmts {
+                    self.expr(stmt);
+                }
+
+                if let Some(ref value) = block.value {
+                    self.expr(value);
+                }
+
+                self.scopes.pop();
+            }
+        }
+    }
+
+    /// Warns about the condition of an `if` or `while` if it is always true or
+    /// always false, most often because `=` was written instead of `==`.
+    fn condition(&mut self, cond: &Expr) {
+        let (value, assign) = match cond.kind {
+            ExprKind::Binary(ref binary) if binary.op.value == BinaryOp::Assign => {
+                (eval::constant(&binary.right), true)
+            }
+            _ => (eval::constant(cond), false),
+        };
+
+        if let Some(Value::Bool(value)) = value {
+            let kind = WarningKind::ConstantCondition { value, assign };
+            self.warnings.push(Warning::new(kind, cond.span));
+        }
+    }
+
+    /// Declares a variable in the innermost block. Declaring a name again in
+    /// the same block is deliberate, but hiding a variable of an enclosing
+    /// block is easily mistaken for assigning it.
+    fn declare(&mut self, var: usize) {
+        let name = self.ast.vars[var].name;
+        let (scope, outer) = self.scopes.split_last_mut().unwrap();
+
+        if self.lints.shadow && !scope.contains_key(&name) {
+            if let Some(&shadowed) = outer.iter().rev().find_map(|scope| scope.get(&name)) {
+                let name = self.ast.symbols.get(name).to_string();
+                let kind = WarningKind::Shadowed {
+                    name,
+                    shadowed: self.ast.vars[shadowed].span,
+                };
+
+                self.warnings
+                    .push(Warning::new(kind, self.ast.vars[var].span));
+            }
+        }
+
+        scope.insert(name, var);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub span: Span,
+}
+
+impl Warning {
+    fn new(kind: WarningKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WarningKind {
+    Shadowed {
+        name: String,
+        shadowed: Span,
+    },
+    /// A condition that is always `value`, which may be an assignment of a
+    /// constant.
+    ConstantCondition {
+        value: bool,
+        assign: bool,
+    },
+}
+
+impl Spanned for Warning {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Report for Warning {
+    fn code(&self) -> &'static str {
+        match self.kind {
+            WarningKind::Shadowed { .. } => "W0001",
+            WarningKind::ConstantCondition { .. } => "W0002",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        match self.kind {
+            WarningKind::Shadowed { ref name, .. } => {
+                format!("'{}' shadows a variable of an enclosing block", name)
+            }
+            WarningKind::ConstantCondition { value, .. } => {
+                format!("This condition is always {}", value)
+            }
+        }
+    }
+
+    fn labels(&self) -> Vec<(Span, String)> {
+        match self.kind {
+            WarningKind::Shadowed { shadowed, .. } => {
+                vec![(
+                    shadowed,
+                    "the shadowed variable is declared here".to_owned(),
+                )]
+            }
+            WarningKind::ConstantCondition { .. } => Vec::new(),
+        }
+    }
+
+    fn note(&self) -> Option<&'static str> {
+        match self.kind {
+            WarningKind::ConstantCondition { assign: true, .. } => {
+                Some("'=' assigns, use '==' to compare")
+            }
+            _ => None,
+        }
+    }
+
+    // warnings are never the reason a compilation fails
+    fn failure(&self) -> Failure {
+        Failure::Semantic
+    }
+}
@@ -1,37 +1,589 @@
-#![deny(rust_2018_idioms)]
+#![deny(rust_2018_idioms, clippy::all)]
 
+mod build_report;
 mod codegen;
+mod coverage;
+mod debug;
 mod emit;
 mod error;
+mod eval;
+mod format;
+mod gen;
+mod header;
+mod init;
+mod intern;
 mod lex;
+mod lint;
 mod parse;
 mod rand;
+mod resolve;
+mod sema;
+#[cfg(test)]
+mod snapshot;
+mod source;
 mod span;
+#[cfg(test)]
+mod tests;
+mod ty;
 
 pub use codegen::Codegen;
-pub use error::{Report, Reporter};
+pub use error::{DiagnosticHandler, Diagnostics, Failure, Report, Reporter, Suggestion};
 pub use lex::Lexer;
 pub use parse::Parser;
+pub use source::SourceMap;
 pub use span::{Span, Spanned, WithSpan};
 
+use build_report::BuildReport;
+use codegen::{Syntax, Target};
+use lex::{StreamLexer, TokenKind};
+use parse::Ast;
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 fn main() {
-    let input = std::env::args().nth(1).unwrap_or_else(|| {
+    let mut input = None;
+    let mut options = Options::default();
+
+    let mut args = std::env::args().skip(1).peekable();
+
+    // `ripc cov [out.cov]` shows the coverage written by an instrumented program
+    if args.peek().map(String::as_str) == Some("cov") {
+        args.next();
+        let path = args.next().unwrap_or_else(|| "out.cov".to_owned());
+
+        if args.next().is_some() {
+            eprintln!("invalid arguments");
+            Failure::Usage.exit()
+        }
+
+        coverage::report(&path);
+    }
+
+    // `ripc gen-test [--seed <n>]` prints a random program, for stress testing
+    if args.peek().map(String::as_str) == Some("gen-test") {
+        args.next();
+        options.gen_test = true;
+    }
+
+    // `ripc run <input> [flags] -- [args]` runs the program after compiling it
+    if args.peek().map(String::as_str) == Some("run") {
+        args.next();
+        options.run = Some(Vec::new());
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // the rest of the arguments are passed to the program
+            "--" if options.run.is_some() => options.run = Some(args.by_ref().collect()),
+            "--no-prelude" => options.prelude = false,
+            "--freestanding" => options.emit.freestanding = true,
+            "--emit=header" => options.emit.header = true,
+            "--emit=listing" => options.emit.listing = true,
+            "--emit=obj" => options.emit.obj = true,
+            "-o" => match args.next() {
+                Some(output) => options.emit.output = Some(output.into()),
+                None => {
+                    eprintln!("expected a path after '-o'");
+                    Failure::Usage.exit()
+                }
+            },
+            "--emit-dep-info" => options.emit.dep_info = true,
+            "--temp" => options.emit.temp = true,
+            "--pic" => options.emit.pic = true,
+            "--stack-protector" => options.emit.stack_protector = true,
+            "--bounds-checks" => options.emit.bounds_checks = true,
+            "--instrument=profile" => options.emit.profile = true,
+            "--instrument=coverage" => options.emit.coverage = true,
+            "-g" => options.emit.debug = true,
+            "--pkg" => match args.next() {
+                Some(package) => options.emit.packages.push(package),
+                None => {
+                    eprintln!("expected a package name after '--pkg'");
+                    Failure::Usage.exit()
+                }
+            },
+            "--no-start" => options.emit.no_start = true,
+            "--entry" => match args.next() {
+                Some(entry) => options.emit.entry = Some(entry),
+                None => {
+                    eprintln!("expected a function name after '--entry'");
+                    Failure::Usage.exit()
+                }
+            },
+            "--eval" => match args.next() {
+                Some(source) => options.eval = Some(source),
+                None => {
+                    eprintln!("expected an expression after '--eval'");
+                    Failure::Usage.exit()
+                }
+            },
+            "--seed" => match args.next() {
+                Some(seed) => {
+                    options.seed = Some(seed.parse().unwrap_or_else(|_| {
+                        eprintln!("invalid seed '{}'", seed);
+                        Failure::Usage.exit()
+                    }))
+                }
+                None => {
+                    eprintln!("expected a number after '--seed'");
+                    Failure::Usage.exit()
+                }
+            },
+            "--print-tokens" => options.print_tokens = true,
+            "--fix" => options.fix = true,
+            "--error-format=human" => options.error_format = error::Format::Human,
+            "--error-format=short" => options.error_format = error::Format::Short,
+            "--build-report=json" => options.build_report = true,
+            "--warn=shadow" => options.lints.shadow = true,
+            _ if arg.starts_with("--max-depth=") => {
+                let depth = &arg["--max-depth=".len()..];
+                options.max_depth = depth.parse().unwrap_or_else(|_| {
+                    eprintln!("invalid depth '{}'", depth);
+                    Failure::Usage.exit()
+                });
+            }
+            _ if arg.starts_with("--syntax=") => {
+                let name = &arg["--syntax=".len()..];
+                options.emit.syntax = Syntax::from_name(name).unwrap_or_else(|| {
+                    eprintln!("unsupported syntax '{}', only 'att' is supported", name);
+                    Failure::Usage.exit()
+                });
+            }
+            _ if arg.starts_with("--target=") => {
+                let name = &arg["--target=".len()..];
+                options.emit.target = Target::from_name(name).unwrap_or_else(|| {
+                    eprintln!("unsupported target '{}', only 'x86_64' is supported", name);
+                    Failure::Usage.exit()
+                });
+            }
+            _ if arg.starts_with("-O") => {
+                let level = &arg["-O".len()..];
+                options.emit.opt_level = match level.parse() {
+                    Ok(level) if level <= 2 => level,
+                    _ => {
+                        eprintln!("invalid optimization level '{}'", level);
+                        Failure::Usage.exit()
+                    }
+                };
+            }
+            _ if arg.starts_with("--timeout=") => {
+                let secs = &arg["--timeout=".len()..];
+                let timeout = secs
+                    .parse()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+
+                options.timeout = Some(timeout.unwrap_or_else(|| {
+                    eprintln!("invalid timeout '{}'", secs);
+                    Failure::Usage.exit()
+                }));
+            }
+            // objects and archives are linked into the program, in order
+            _ if arg.ends_with(".o") || arg.ends_with(".a") => {
+                options.emit.objects.push(arg.into())
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => {
+                eprintln!("invalid arguments");
+                Failure::Usage.exit()
+            }
+        }
+    }
+
+    if options.gen_test {
+        if input.is_some() {
+            eprintln!("invalid arguments");
+            Failure::Usage.exit()
+        }
+
+        print!("{}", gen::program(&mut options.rng()));
+        return;
+    }
+
+    if options.seed.is_some() {
+        eprintln!("'--seed' is only used by 'ripc gen-test'");
+        Failure::Usage.exit()
+    }
+
+    if let Some(ref source) = options.eval {
+        if input.is_some() {
+            eprintln!("invalid arguments");
+            Failure::Usage.exit()
+        }
+
+        evaluate(source, &options);
+    }
+
+    // an object has no entry point to run
+    if options.run.is_some() && options.emit.obj {
+        eprintln!("'ripc run' can't be used with '--emit=obj'");
+        Failure::Usage.exit()
+    }
+
+    // `--entry` picks what `_start` calls
+    if options.emit.entry.is_some() && (options.emit.no_start || options.emit.obj) {
+        eprintln!("'--entry' requires '_start', which '--no-start' and '--emit=obj' leave out");
+        Failure::Usage.exit()
+    }
+
+    let input = input.unwrap_or_else(|| {
         eprintln!("invalid arguments");
-        std::process::exit(1)
+        Failure::Usage.exit()
     });
 
-    let mut reporter = Reporter::new(std::io::stderr(), &input);
+    if options.print_tokens {
+        print_tokens(&input);
+    }
+
+    // with `--fix`, suggested edits are applied and the input compiled again,
+    // until it compiles or fails with an error that has no suggestion
+    loop {
+        let start = Instant::now();
+        let mut sources = load(&input);
+        let mut report = BuildReport::default();
+        let result = run(&mut sources, &options, &mut report);
+
+        if options.build_report {
+            let errors = result.as_ref().err().map_or(0, Diagnostics::count);
+            let json = report.json(&sources, errors, start.elapsed());
+            let path = options.emit.output().with_extension("json");
+
+            std::fs::write(&path, json).unwrap_or_else(|err| {
+                eprintln!("failed to write '{}': {}", path.display(), err);
+                Failure::Environment.exit()
+            });
+        }
+
+        let err = match result {
+            Ok(()) => match options.run {
+                Some(ref args) => execute(&options.emit.output(), args, options.timeout),
+                None => return,
+            },
+            Err(err) => err,
+        };
+
+        if options.fix {
+            let fix = err.first().suggestion().and_then(|suggestion| {
+                let (path, source) = suggestion.apply(&sources)?;
+                Some((path.to_owned(), source, suggestion.message))
+            });
+
+            if let Some((path, source, message)) = fix {
+                std::fs::write(&path, source).unwrap_or_else(|err| {
+                    eprintln!("failed to write '{}': {}", path.display(), err);
+                    Failure::Environment.exit()
+                });
+
+                eprintln!("fixed: {} in '{}'", message, path.display());
+                continue;
+            }
+        }
+
+        Reporter::new(std::io::stderr(), &sources)
+            .format(options.error_format)
+            .exit_all(err)
+    }
+}
+
+/// Runs the compiled program at `path`, exiting with its exit status, or with 128 plus
+/// the signal that killed it, as shells do. A program that runs for longer
+/// than the timeout is killed, and the exit status is 124, as with
+/// `timeout(1)`.
+fn execute(path: &Path, args: &[String], timeout: Option<Duration>) -> ! {
+    // a bare file name would be looked up in $PATH
+    let path = Path::new(".").join(path);
+    let mut child = std::process::Command::new(&path)
+        .args(args)
+        .spawn()
+        .unwrap_or_else(|err| {
+            eprintln!("failed to run '{}': {}", path.display(), err);
+            Failure::Environment.exit()
+        });
+
+    let start = Instant::now();
+    let status = loop {
+        let status = match timeout {
+            None => child.wait().map(Some),
+            Some(_) => child.try_wait(),
+        };
+
+        match status {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() >= timeout.unwrap() => {
+                let _ = child.kill();
+                let _ = child.wait();
+                eprintln!("[error]: timed out after {:?}", timeout.unwrap());
+                std::process::exit(124)
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(err) => {
+                eprintln!("failed to wait for '{}': {}", path.display(), err);
+                Failure::Environment.exit()
+            }
+        }
+    };
+
+    if let Some(signal) = status.signal() {
+        eprintln!("[error]: killed by {}", signal_name(signal));
+        std::process::exit(128 + signal)
+    }
+
+    std::process::exit(status.code().unwrap_or(1))
+}
+
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        _ => return format!("signal {}", signal),
+    };
+
+    name.to_owned()
+}
+
+/// Reads the input, which is either the path of a source file, or the source
+/// itself.
+fn load(input: &str) -> SourceMap {
+    let mut sources = SourceMap::new();
+
+    let path = Path::new(input);
+    if path.is_file() {
+        let source = std::fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("failed to read '{}': {}", input, err);
+            Failure::Environment.exit()
+        });
+
+        let canonical = path.canonicalize().ok();
+        sources.add(input.to_owned(), canonical, source);
+    } else {
+        sources.add("<input>".to_owned(), None, input.to_owned());
+    }
+
+    sources
+}
 
-    match run(&input) {
-        Ok(()) => {}
-        Err(e) => reporter.exit(e),
+/// Options passed on the command line.
+struct Options {
+    /// Whether the prelude is available, disabled by `--no-prelude`.
+    prelude: bool,
+    /// How deeply expressions can be nested, set by `--max-depth=<n>`.
+    max_depth: usize,
+    /// Whether to print the tokens of the input rather than compiling it,
+    /// enabled by `--print-tokens`.
+    print_tokens: bool,
+    /// Whether to apply the edits suggested by errors to the source files,
+    /// enabled by `--fix`.
+    fix: bool,
+    /// How errors are rendered, set by `--error-format=human|short`.
+    error_format: error::Format,
+    /// The optional lints that are enabled, by `--warn=shadow`.
+    lints: lint::Lints,
+    /// The source to evaluate and print the value of rather than compiling
+    /// it, set by `--eval <source>`.
+    eval: Option<String>,
+    /// The arguments to run the compiled program with, if it was compiled by
+    /// `ripc run`.
+    run: Option<Vec<String>>,
+    /// How long the program is allowed to run for before it is killed, set
+    /// by `--timeout=<seconds>`.
+    timeout: Option<Duration>,
+    /// Whether to write a JSON report of what the build did next to the
+    /// output, enabled by `--build-report=json`.
+    build_report: bool,
+    /// Whether to print a random program rather than compiling one, enabled
+    /// by `ripc gen-test`.
+    gen_test: bool,
+    /// The seed of everything random, set by `--seed <n>`.
+    seed: Option<u64>,
+    /// Enabled by `--freestanding`, `--emit=header|listing|obj`,
+    /// `--emit-dep-info`, `--pic`, `--stack-protector`, `--bounds-checks`,
+    /// `--instrument=profile|coverage`, `-g`, `--temp`, `--pkg <name>`,
+    /// `--entry <name>`, `--no-start` and `-o <path>`, along with any `.o`
+    /// and `.a` inputs.
+    emit: emit::Options,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            prelude: true,
+            max_depth: parse::DEFAULT_MAX_DEPTH,
+            print_tokens: false,
+            eval: None,
+            fix: false,
+            error_format: error::Format::Human,
+            lints: lint::Lints::default(),
+            run: None,
+            timeout: None,
+            build_report: false,
+            gen_test: false,
+            seed: None,
+            emit: emit::Options::default(),
+        }
     }
 }
 
-fn run(input: &str) -> Result<(), Box<dyn Report<std::io::Stderr>>> {
-    let lexer = Lexer::new(input);
-    let expr = Parser::new(lexer).parse()?;
-    emit::emit(&expr)?;
+impl Options {
+    /// Returns a generator seeded by `--seed`, or by the clock otherwise, in
+    /// which case the seed is reported so the run can be reproduced.
+    fn rng(&self) -> rand::Rng {
+        let seed = self.seed.unwrap_or_else(|| {
+            let seed = rand::clock_seed();
+            eprintln!("seed: {}", seed);
+            seed
+        });
+
+        rand::Rng::new(seed)
+    }
+}
+
+/// Prints the tokens of the input, which is read as it is lexed. The input is
+/// standard input if it is `-`, and otherwise a path or the source itself.
+fn print_tokens(input: &str) -> ! {
+    let reader: Box<dyn Read> = if input == "-" {
+        Box::new(std::io::stdin().lock())
+    } else if Path::new(input).is_file() {
+        let file = File::open(input).unwrap_or_else(|err| {
+            eprintln!("failed to read '{}': {}", input, err);
+            Failure::Environment.exit()
+        });
+
+        Box::new(file)
+    } else {
+        Box::new(input.as_bytes())
+    };
+
+    let mut lexer = StreamLexer::new(reader).tolerant();
+    let mut out = BufWriter::new(std::io::stdout().lock());
+
+    while let Some(token) = lexer.next_token() {
+        match token {
+            Ok(token) if token.kind == TokenKind::Whitespace => {}
+            Ok(token) => {
+                let _ = writeln!(
+                    out,
+                    "{}..{} {:?}",
+                    token.span.start, token.span.end, token.kind
+                );
+            }
+            Err(err) => {
+                let _ = out.flush();
+                eprintln!("[error]: {} at offset {}", err.kind, err.span.start);
+                Failure::Environment.exit()
+            }
+        }
+    }
+
+    let _ = out.flush();
+    std::process::exit(0)
+}
+
+fn run(
+    sources: &mut SourceMap,
+    options: &Options,
+    report: &mut BuildReport,
+) -> Result<(), Diagnostics> {
+    let start = Instant::now();
+    let checked = check(sources, options);
+    report.phase("check", start.elapsed());
+
+    let (ast, warnings) = checked?;
+    warn(sources, options, &warnings);
+
+    let start = Instant::now();
+    let emitted = emit::emit(&ast, sources, &options.emit, report);
+    report.phase("emit", start.elapsed());
+    emitted?;
 
     Ok(())
 }
+
+/// Evaluates the source and prints its value, without assembling or linking
+/// it. The final statement may leave out its `;`, so that an expression can
+/// be evaluated on its own.
+fn evaluate(source: &str, options: &Options) -> ! {
+    let mut source = source.to_owned();
+    if !source.trim_end().ends_with([';', '}']) {
+        source.push(';');
+    }
+
+    let mut sources = SourceMap::new();
+    sources.add("<eval>".to_owned(), None, source);
+
+    let value = check(&mut sources, options).and_then(|(ast, warnings)| {
+        warn(&sources, options, &warnings);
+        eval::eval(&ast).map_err(Diagnostics::from)
+    });
+
+    match value {
+        Ok(value) => {
+            if let Some(value) = value {
+                println!("{}", value);
+            }
+
+            std::process::exit(0)
+        }
+        Err(err) => Reporter::new(std::io::stderr(), &sources)
+            .format(options.error_format)
+            .exit_all(err),
+    }
+}
+
+/// Parses and checks the main source file, returning the AST ready for
+/// codegen along with any warnings.
+fn check(
+    sources: &mut SourceMap,
+    options: &Options,
+) -> Result<(Ast, Vec<lint::Warning>), Diagnostics> {
+    let source = sources.main().source.clone();
+    let lexer = Lexer::new(&source, 0);
+    let mut ast = Parser::new(lexer, sources)
+        .prelude(options.prelude)
+        .max_depth(options.max_depth)
+        .parse()?;
+
+    // sema checks what resolved, ignoring calls that didn't, so that the
+    // earliest error is reported first whichever phase found it
+    let resolved = resolve::check(&mut ast).map_err(Diagnostics::new);
+    let checked = sema::check(&mut ast, options.emit.freestanding).map_err(Diagnostics::new);
+    match (resolved, checked) {
+        (Err(resolve), Err(sema)) => return Err(resolve.merge(sema)),
+        (Err(err), Ok(())) | (Ok(()), Err(err)) => return Err(err),
+        (Ok(()), Ok(())) => {}
+    }
+
+    init::check(&ast).map_err(Diagnostics::new)?;
+
+    let mut warnings = lint::check(&ast, options.lints);
+
+    // evaluating constant conditions is what `--eval` is for
+    if options.eval.is_some() {
+        warnings
+            .retain(|warning| !matches!(warning.kind, lint::WarningKind::ConstantCondition { .. }));
+    }
+
+    Ok((ast, warnings))
+}
+
+/// Prints warnings to standard error.
+fn warn(sources: &SourceMap, options: &Options, warnings: &[lint::Warning]) {
+    let mut reporter = Reporter::new(std::io::stderr(), sources).format(options.error_format);
+    for warning in warnings {
+        reporter.on_warning(warning);
+    }
+}
@@ -1,153 +1,1729 @@
+use crate::intern::{Interner, Symbol};
 use crate::lex::{self, Lexer, Token, TokenKind};
-use crate::{Report, Reporter, Span, Spanned, WithSpan};
+use crate::resolve::{self, Item, Items, Namespace};
+use crate::source::SourceMap;
+use crate::ty::{Struct, Ty};
+use crate::{Failure, Report, Span, Spanned, Suggestion, WithSpan};
 
-use std::io::Write;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
 use std::mem;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Declarations available to every program, unless it is compiled with
+/// `--no-prelude`.
+const PRELUDE: &str = include_str!("prelude.rp");
+
+/// How deeply expressions can be nested by default, see `Parser::max_depth`.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
 
 pub struct Parser<'a> {
     tokens: Tokens<'a>,
+    sources: &'a mut SourceMap,
+    program: Program,
+    loop_depth: usize,
+    /// Whether the body of a function is being parsed, where `return` is
+    /// allowed.
+    in_fn: bool,
+    prelude: bool,
+    /// How deeply the expression being parsed is nested.
+    depth: usize,
+    max_depth: usize,
+}
+
+/// The state shared by every file of a program.
+#[derive(Default)]
+struct Program {
+    symbols: Interner,
     vars: Vec<Var>,
+    /// The variable each name refers to, the latest declared.
+    scope: HashMap<Symbol, usize>,
+    labels: Vec<String>,
+    gotos: Vec<WithSpan<String>>,
+    functions: Vec<Function>,
+    structs: Vec<StructDef>,
+    modules: Vec<Module>,
+    /// The module currently being parsed.
+    module: usize,
+    /// The files currently being parsed, each imported by the one before it.
+    imports: Vec<PathBuf>,
+    /// Every file that has been imported.
+    imported: HashSet<PathBuf>,
+    prelude: Option<usize>,
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(lexer: Lexer<'a>) -> Self {
-        Self {
-            tokens: Tokens {
-                lexer,
-                peeked: None,
-            },
-            vars: Vec::new(),
+impl Program {
+    /// Declares a variable, shadowing any previous one with the same name.
+    fn declare(&mut self, name: Symbol, ty: Ty, span: Span) -> usize {
+        self.vars.push(Var::new(name, ty, span));
+        self.scope.insert(name, self.vars.len() - 1);
+        self.vars.len() - 1
+    }
+
+    fn items(&self) -> Items<'_> {
+        Items {
+            modules: &self.modules,
+            structs: &self.structs,
+            functions: &self.functions,
+            prelude: self.prelude,
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(lexer: Lexer<'a>, sources: &'a mut SourceMap) -> Self {
+        Self {
+            tokens: Tokens::new(lexer),
+            sources,
+            program: Program::default(),
+            loop_depth: 0,
+            in_fn: false,
+            prelude: true,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Whether to make the declarations of the prelude available.
+    pub fn prelude(mut self, prelude: bool) -> Self {
+        self.prelude = prelude;
+        self
+    }
+
+    /// How deeply expressions can be nested, counting parentheses, blocks,
+    /// operands of prefix operators, each binary operator or cast in a chain
+    /// of them, each index, slice or field access, and each level of a type.
+    /// Deeper expressions are rejected rather than overflowing the stack of
+    /// the parser or any later pass, which all recurse over expressions.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    fn peek(&mut self) -> Result<Option<Token<'a>>, lex::Error> {
+        self.peek_n(0)
+    }
+
+    /// Returns the token `n` tokens ahead of the next one.
+    fn peek_n(&mut self, n: usize) -> Result<Option<Token<'a>>, lex::Error> {
+        self.tokens.peek_n(n).copied().transpose()
+    }
+
+    fn next(&mut self) -> Result<Option<Token<'a>>, lex::Error> {
+        self.tokens.next().transpose()
+    }
+
+    /// Enters a level of nesting, at `span`.
+    fn nest(&mut self, span: Span) -> Result<(), Error> {
+        self.depth += 1;
+
+        if self.depth > self.max_depth {
+            return Err(Error::new(ErrorKind::TooDeep(self.max_depth), span));
+        }
+
+        Ok(())
+    }
+
+    /// Parses something nested a level deeper than its surroundings.
+    fn nested<T>(
+        &mut self,
+        span: Span,
+        parse: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.nest(span)?;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn chomp(&mut self) {
+        let _ = self.next().unwrap();
+    }
+
+    fn expect(&mut self, kind: TokenKind<'static>) -> Result<Token<'a>, Error> {
+        let token = self.next()?.ok_or(Error::EOF)?;
+
+        if token.kind != kind {
+            return Err(Error::new(ErrorKind::ExpectedToken(kind), token.span));
+        }
+
+        Ok(token)
+    }
+
+    pub fn parse(&mut self) -> Result<Ast, Error> {
+        let mut ast = Ast {
+            exprs: Vec::new(),
+            functions: Vec::new(),
+            defs: Vec::new(),
+            structs: Vec::new(),
+            modules: Vec::new(),
+            prelude: None,
+            vars: Vec::new(),
+            symbols: Interner::default(),
+        };
+
+        // the root module
+        self.program.modules.push(Module {
+            name: WithSpan::new(String::new(), Span::default()),
+            parent: None,
+            public: true,
+            uses: Vec::new(),
+        });
+
+        if let Some(ref path) = self.sources.main().path {
+            self.program.imports.push(path.clone());
+            self.program.imported.insert(path.clone());
+        }
+
+        if self.prelude {
+            self.parse_prelude(&mut ast)?;
+        }
+
+        self.items(&mut ast, false)?;
+
+        let program = &self.program;
+        if let Some(goto) = program
+            .gotos
+            .iter()
+            .find(|g| !program.labels.contains(&g.value))
+        {
+            return Err(Error::new(ErrorKind::UndefinedLabel, goto.span));
+        }
+
+        ast.vars = mem::take(&mut self.program.vars);
+        ast.symbols = mem::take(&mut self.program.symbols);
+        ast.functions = mem::take(&mut self.program.functions);
+        ast.structs = mem::take(&mut self.program.structs);
+        ast.modules = mem::take(&mut self.program.modules);
+        ast.prelude = self.program.prelude;
+        Ok(ast)
+    }
+
+    /// Parses the prelude into the `prelude` module, whose items are
+    /// in scope everywhere unless shadowed.
+    fn parse_prelude(&mut self, ast: &mut Ast) -> Result<(), Error> {
+        let file = self
+            .sources
+            .add("<prelude>".to_owned(), None, PRELUDE.to_owned());
+        let (source, start) = (file.source.clone(), file.start);
+
+        self.program.modules.push(Module {
+            name: WithSpan::new("prelude".to_owned(), Span::default()),
+            parent: Some(0),
+            public: true,
+            uses: Vec::new(),
+        });
+
+        let prelude = self.program.modules.len() - 1;
+        self.program.prelude = Some(prelude);
+
+        self.program.module = prelude;
+        self.file(ast, &source, start)?;
+        self.program.module = 0;
+
+        Ok(())
+    }
+
+    /// Parses the items of a file, or of a module up to its closing brace,
+    /// into `ast`.
+    ///
+    /// Statements can only appear in the root module.
+    fn items(&mut self, ast: &mut Ast, nested: bool) -> Result<(), Error> {
+        loop {
+            let mut token = match self.peek()? {
+                Some(token) => token,
+                None if nested => return Err(Error::EOF),
+                None => return Ok(()),
+            };
+
+            let public = token.kind == TokenKind::Pub;
+            if public {
+                self.chomp();
+                token = self.peek()?.ok_or(Error::EOF)?;
+            }
+
+            match token.kind {
+                TokenKind::CloseBrace if nested && !public => return Ok(()),
+                TokenKind::Extern => {
+                    let func = self.extern_fn(public)?;
+                    self.program.functions.push(func);
+                }
+                TokenKind::Export => {
+                    self.chomp();
+                    self.fn_def(ast, public, true)?;
+                }
+                TokenKind::Fn => self.fn_def(ast, public, false)?,
+                TokenKind::Struct => self.struct_def(public)?,
+                TokenKind::Mod => self.module(ast, public)?,
+                _ if public => return Err(Error::new(ErrorKind::ExpectedItem, token.span)),
+                TokenKind::Import => self.import(ast)?,
+                TokenKind::Use => self.use_item()?,
+                _ if self.program.module != 0 => {
+                    return Err(Error::new(ErrorKind::StatementInModule, token.span))
+                }
+                _ => self.statement(ast)?,
+            }
+        }
+    }
+
+    fn statement(&mut self, ast: &mut Ast) -> Result<(), Error> {
+        let expr = self.statement_expr()?;
+
+        // labels, blocks, conditionals and loops are not terminated by a
+        // semicolon, though any but a label may be followed by one
+        match expr.kind {
+            ExprKind::Label(_) => {
+                ast.exprs.push(expr);
+                return Ok(());
+            }
+            ExprKind::Block(_) | ExprKind::If(_) | ExprKind::While(_) => {
+                if self.peek()?.map(|t| t.kind) == Some(TokenKind::Semi) {
+                    self.chomp();
+                }
+
+                ast.exprs.push(expr);
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let token = self.next()?;
+
+        if !matches!(
+            token,
+            Some(Token {
+                kind: TokenKind::Semi,
+                ..
+            }),
+        ) {
+            return Err(Error::new(
+                ErrorKind::UnterminatedExpression { end: expr.span },
+                token.map(|t| t.span).unwrap_or(Span::EOF),
+            ));
+        }
+
+        ast.exprs.push(expr);
+        Ok(())
+    }
+
+    /// Parses the expression of a statement. A block, conditional or loop at
+    /// the start of a statement is a statement of its own, so
+    /// `{ .. } *p = 1` isn't a multiplication.
+    fn statement_expr(&mut self) -> Result<Expr, Error> {
+        let start = self.peek()?.map(|token| token.span).unwrap_or(Span::EOF);
+
+        match self.peek()?.map(|t| t.kind) {
+            Some(TokenKind::OpenBrace) => {
+                self.chomp();
+                return self.nested(start, |p| p.block(start))?.ok_or(Error::EOF);
+            }
+            Some(TokenKind::If) => {
+                self.chomp();
+                return self.nested(start, |p| p.if_expr(start))?.ok_or(Error::EOF);
+            }
+            Some(TokenKind::While) => {
+                self.chomp();
+                return self
+                    .nested(start, |p| p.while_expr(start))?
+                    .ok_or(Error::EOF);
+            }
+            Some(TokenKind::For) => {
+                self.chomp();
+                return self.nested(start, |p| p.for_expr(start))?.ok_or(Error::EOF);
+            }
+            _ => {}
+        }
+
+        let expr = match self.expr(0) {
+            Ok(expr) => expr.ok_or(Error::EOF)?,
+            Err(err) => return Err(self.missing_semi(err, start)),
+        };
+
+        if self.peek()?.map(|t| t.kind) == Some(TokenKind::Comma) {
+            return self.destructure(expr);
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses the rest of a destructuring assignment after its first place,
+    /// `x, y = pair`, as an assignment to a tuple of the places.
+    fn destructure(&mut self, first: Expr) -> Result<Expr, Error> {
+        let mut places = vec![first];
+
+        while self.peek()?.map(|t| t.kind) == Some(TokenKind::Comma) {
+            self.chomp();
+
+            // stop before the `=`
+            let place = self.expr(BinaryOp::Assign.precedence() + 1)?;
+            places.push(place.ok_or(Error::EOF)?);
+        }
+
+        let op = self.expect(TokenKind::Assign)?;
+        let value = self.expr(0)?.ok_or(Error::EOF)?;
+
+        let left = places[0].span + places[places.len() - 1].span;
+        let span = left + value.span;
+        Ok(Expr::new(
+            ExprKind::Binary(BinaryExpr {
+                left: Box::new(Expr::new(ExprKind::Tuple(places), left)),
+                op: WithSpan::new(BinaryOp::Assign, op.span),
+                right: Box::new(value),
+            }),
+            span,
+        ))
+    }
+
+    /// Parses the rest of a tuple literal after its first element, allowing
+    /// a trailing comma, which a tuple of one element requires, `(1,)`.
+    fn tuple(&mut self, start: Span, first: Expr) -> Result<Expr, Error> {
+        let mut elems = vec![first];
+
+        let end = loop {
+            let token = self.next()?.ok_or(Error::EOF)?;
+
+            match token.kind {
+                TokenKind::CloseParen => break token,
+                TokenKind::Comma => {
+                    if let Some(end) = self.peek()?.filter(|t| t.kind == TokenKind::CloseParen) {
+                        self.chomp();
+                        break end;
+                    }
+
+                    elems.push(self.expr(0)?.ok_or(Error::EOF)?);
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedToken(TokenKind::CloseParen),
+                        token.span,
+                    ))
+                }
+            }
+        };
+
+        Ok(Expr::new(ExprKind::Tuple(elems), start + end.span))
+    }
+
+    /// Turns an expected operator at the start of a line, following a
+    /// statement that isn't inside any brackets, into a missing semicolon.
+    fn missing_semi(&self, err: Error, start: Span) -> Error {
+        let ErrorKind::ExpectedOperator { after } = err.kind else {
+            return err;
+        };
+
+        let between = self.sources.slice(Span::new(after.end..err.span.start));
+        if !between.is_some_and(|text| text.contains('\n')) {
+            return err;
+        }
+
+        let Some(statement) = self.sources.slice(Span::new(start.start..after.end)) else {
+            return err;
+        };
+
+        let mut depth = 0;
+        for token in Lexer::new(statement, 0).flatten() {
+            match token.kind {
+                TokenKind::OpenParen | TokenKind::OpenBrace | TokenKind::OpenBracket => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBrace | TokenKind::CloseBracket => {
+                    depth -= 1
+                }
+                _ => {}
+            }
+        }
+
+        if depth != 0 {
+            return err;
+        }
+
+        Error::new(ErrorKind::UnterminatedExpression { end: after }, err.span)
+    }
+
+    /// Parses a module, `mod math { ... }`.
+    fn module(&mut self, ast: &mut Ast, public: bool) -> Result<(), Error> {
+        self.expect(TokenKind::Mod)?;
+        let name = self.ident()?;
+
+        let parent = self.program.module;
+        let duplicate = self
+            .program
+            .modules
+            .iter()
+            .any(|m| m.parent == Some(parent) && m.name.value == name.value);
+
+        if duplicate {
+            return Err(Error::new(ErrorKind::DuplicateModule, name.span));
+        }
+
+        self.expect(TokenKind::OpenBrace)?;
+
+        self.program.modules.push(Module {
+            name,
+            parent: Some(parent),
+            public,
+            uses: Vec::new(),
+        });
+
+        self.program.module = self.program.modules.len() - 1;
+        self.items(ast, true)?;
+        self.program.module = parent;
+
+        self.expect(TokenKind::CloseBrace)?;
+        Ok(())
+    }
+
+    /// Parses `use a::b;`, or `use a::*;` to bring every public item of
+    /// `a` into scope.
+    fn use_item(&mut self) -> Result<(), Error> {
+        self.expect(TokenKind::Use)?;
+
+        let mut segments = vec![self.ident()?];
+        let mut glob = false;
+
+        while self.peek()?.map(|t| t.kind) == Some(TokenKind::PathSep) {
+            self.chomp();
+
+            if self.peek()?.map(|t| t.kind) == Some(TokenKind::Mul) {
+                self.chomp();
+                glob = true;
+                break;
+            }
+
+            segments.push(self.ident()?);
+        }
+
+        self.expect(TokenKind::Semi)?;
+
+        let module = self.program.module;
+        self.program.modules[module].uses.push(Use {
+            path: ItemPath { segments },
+            glob,
+        });
+
+        Ok(())
+    }
+
+    /// Parses the rest of a path starting with `first`.
+    fn path(&mut self, first: WithSpan<String>) -> Result<ItemPath, Error> {
+        let mut segments = vec![first];
+
+        while self.peek()?.map(|t| t.kind) == Some(TokenKind::PathSep) {
+            self.chomp();
+            segments.push(self.ident()?);
+        }
+
+        Ok(ItemPath { segments })
+    }
+
+    /// Resolves a path used in the module being parsed.
+    fn resolve(&self, path: &ItemPath, ns: Namespace) -> Result<Item, resolve::Error> {
+        let items = self.program.items();
+
+        items.resolve(self.program.module, path, ns)
+    }
+
+    /// Resolves the struct named by a path.
+    fn find_struct(&self, path: &ItemPath) -> Result<Rc<Struct>, resolve::Error> {
+        match self.resolve(path, Namespace::Type)? {
+            Item::Struct(i) => Ok(self.program.structs[i].ty.clone()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parses `import "path";`, splicing the items of the imported file in
+    /// place. Paths are relative to the importing file, and every file is
+    /// only imported once.
+    fn import(&mut self, ast: &mut Ast) -> Result<(), Error> {
+        self.expect(TokenKind::Import)?;
+
+        let token = self.next()?.ok_or(Error::EOF)?;
+        let name = match token.kind {
+            TokenKind::Str(name) => name,
+            _ => return Err(Error::new(ErrorKind::ExpectedPath, token.span)),
+        };
+
+        self.expect(TokenKind::Semi)?;
+
+        let importer = self.sources.file(token.span.start);
+        let path = match importer.path {
+            Some(_) => Path::new(&importer.name)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(name),
+            None => PathBuf::from(name),
+        };
+
+        let read_error = |err: std::io::Error| {
+            Error::new(
+                ErrorKind::Import {
+                    path: path.display().to_string(),
+                    error: err.to_string(),
+                },
+                token.span,
+            )
+        };
+
+        let canonical = fs::canonicalize(&path).map_err(read_error)?;
+
+        if let Some(i) = self.program.imports.iter().position(|p| *p == canonical) {
+            let mut chain = self.program.imports[i..]
+                .iter()
+                .map(|p| self.sources.find(p).unwrap().name.clone())
+                .collect::<Vec<_>>();
+            chain.push(path.display().to_string());
+
+            return Err(Error::new(ErrorKind::ImportCycle(chain), token.span));
+        }
+
+        if !self.program.imported.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        let source = fs::read_to_string(&path).map_err(read_error)?;
+        let file = self
+            .sources
+            .add(path.display().to_string(), Some(canonical.clone()), source);
+        let (source, start) = (file.source.clone(), file.start);
+
+        self.program.imports.push(canonical);
+        let result = self.file(ast, &source, start);
+        self.program.imports.pop();
+
+        result
+    }
+
+    /// Parses the items of another file into the current module.
+    fn file(&mut self, ast: &mut Ast, source: &str, start: usize) -> Result<(), Error> {
+        // the file shares the variables, items and labels of the program
+        let mut parser = Parser::new(Lexer::new(source, start), self.sources);
+        mem::swap(&mut parser.program, &mut self.program);
+        let result = parser.items(ast, false);
+        mem::swap(&mut parser.program, &mut self.program);
+
+        result.map_err(|mut err| {
+            // point at the end of the file, rather than the main one
+            if err.span == Span::EOF {
+                let end = start + source.trim_end().len();
+                err.span = Span::new(end..end);
+            }
+
+            err
+        })
+    }
+
+    pub fn expr(&mut self, precedence: usize) -> Result<Option<Expr>, Error> {
+        let expr = match self.unary()? {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+
+        if let ExprKind::Label(_) = expr.kind {
+            return Ok(Some(expr));
+        }
+
+        self.binary(expr, precedence)
+    }
+
+    /// Parses any binary operators following `expr`.
+    fn binary(&mut self, expr: Expr, precedence: usize) -> Result<Option<Expr>, Error> {
+        let depth = self.depth;
+        let expr = self.operators(expr, precedence);
+        self.depth = depth;
+        expr
+    }
+
+    /// Parses binary operators and casts, each nesting the expression before
+    /// it a level deeper.
+    fn operators(&mut self, mut expr: Expr, precedence: usize) -> Result<Option<Expr>, Error> {
+        loop {
+            let token = match self.peek()? {
+                Some(t) => t,
+                None => return Ok(Some(expr)),
+            };
+
+            // casts bind tighter than any binary operator
+            if token.kind == TokenKind::As {
+                self.chomp();
+                self.nest(token.span)?;
+                let ty = self.ty()?;
+
+                let span = expr.span + ty.span;
+                expr = Expr::new(
+                    ExprKind::Cast(Cast {
+                        expr: Box::new(expr),
+                        ty,
+                    }),
+                    span,
+                );
+                continue;
+            }
+
+            let op = match token.kind {
+                TokenKind::Add => BinaryOp::Add,
+                TokenKind::Sub => BinaryOp::Sub,
+                TokenKind::Mul => BinaryOp::Mul,
+                TokenKind::Div => BinaryOp::Div,
+                TokenKind::Assign => BinaryOp::Assign,
+                TokenKind::Eq => BinaryOp::Eq,
+                TokenKind::Ne => BinaryOp::Ne,
+                TokenKind::Lt => BinaryOp::Lt,
+                TokenKind::Le => BinaryOp::Le,
+                TokenKind::Gt => BinaryOp::Gt,
+                TokenKind::Ge => BinaryOp::Ge,
+                TokenKind::AndAnd => BinaryOp::And,
+                TokenKind::OrOr => BinaryOp::Or,
+                TokenKind::Semi
+                | TokenKind::CloseParen
+                | TokenKind::Comma
+                | TokenKind::OpenBrace
+                | TokenKind::CloseBrace
+                | TokenKind::CloseBracket
+                | TokenKind::DotDot => return Ok(Some(expr)),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedOperator { after: expr.span },
+                        token.span,
+                    ))
+                }
+            };
+
+            if op.precedence() < precedence {
+                return Ok(Some(expr));
+            }
+
+            self.chomp();
+            self.nest(token.span)?;
+
+            let right = self.expr(op.precedence() + 1)?.ok_or(Error::EOF)?;
+
+            let span = expr.span + right.span;
+            expr = Expr::new(
+                ExprKind::Binary(BinaryExpr {
+                    op: WithSpan::new(op, token.span),
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                }),
+                span,
+            );
+        }
+    }
+
+    fn unary(&mut self) -> Result<Option<Expr>, Error> {
+        match self.peek()? {
+            Some(token) => self.nested(token.span, Self::prefix),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a prefix operator and its operand, or a primary expression
+    /// and any postfix operators following it.
+    fn prefix(&mut self) -> Result<Option<Expr>, Error> {
+        let token = match self.peek()? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let op = match token.kind {
+            TokenKind::Mul => UnaryOp::Deref,
+            TokenKind::Amp => UnaryOp::AddrOf,
+            TokenKind::Sub => UnaryOp::Neg,
+            TokenKind::Not => UnaryOp::Not,
+            _ => {
+                let expr = match self.primary()? {
+                    Some(e) => e,
+                    None => return Ok(None),
+                };
+
+                if let ExprKind::Label(_) = expr.kind {
+                    return Ok(Some(expr));
+                }
+
+                // postfix operators bind tighter than prefix ones
+                return self.postfix(expr).map(Some);
+            }
+        };
+
+        self.chomp();
+        let operand = self.unary()?.ok_or(Error::EOF)?;
+
+        let span = token.span + operand.span;
+        Ok(Some(Expr::new(
+            ExprKind::Unary(UnaryExpr {
+                op: WithSpan::new(op, token.span),
+                expr: Box::new(operand),
+            }),
+            span,
+        )))
+    }
+
+    fn primary(&mut self) -> Result<Option<Expr>, Error> {
+        let token = match self.next()? {
+            Some(t) => t,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let kind = match token.kind {
+            TokenKind::Num(num) => ExprKind::Lit(WithSpan::new(Lit::Num(num), token.span)),
+            TokenKind::Str(lit) => {
+                let symbol = self.program.symbols.intern(lit);
+                let lit = self.program.symbols.get(symbol).clone();
+                ExprKind::Lit(WithSpan::new(Lit::String(lit), token.span))
+            }
+            TokenKind::Float(lit) => {
+                ExprKind::Lit(WithSpan::new(Lit::Float(lit.parse().unwrap()), token.span))
+            }
+            TokenKind::True => ExprKind::Lit(WithSpan::new(Lit::Bool(true), token.span)),
+            TokenKind::False => ExprKind::Lit(WithSpan::new(Lit::Bool(false), token.span)),
+            TokenKind::Ident(var) => {
+                let name = || WithSpan::new(var.to_owned(), token.span);
+
+                if self.peek()?.map(|t| t.kind) == Some(TokenKind::PathSep) {
+                    let path = self.path(name())?;
+                    return self.path_expr(path);
+                }
+
+                if self.peek()?.map(|t| t.kind) == Some(TokenKind::OpenParen) {
+                    self.chomp();
+                    return self.func_call(ItemPath {
+                        segments: vec![name()],
+                    });
+                }
+
+                if self.peek()?.map(|t| t.kind) == Some(TokenKind::Colon) {
+                    let colon = self.next()?.ok_or(Error::EOF)?;
+                    let span = token.span + colon.span;
+
+                    if self.program.labels.iter().any(|l| l == var) {
+                        return Err(Error::new(ErrorKind::DuplicateLabel, span));
+                    }
+
+                    self.program.labels.push(var.to_owned());
+                    return Ok(Some(Expr::new(ExprKind::Label(var.to_owned()), span)));
+                }
+
+                // a variable can also be followed by a brace, `match x { .. }`
+                if self.peek()?.map(|t| t.kind) == Some(TokenKind::OpenBrace) {
+                    let path = ItemPath {
+                        segments: vec![name()],
+                    };
+
+                    match self.find_struct(&path) {
+                        Ok(ty) => {
+                            self.chomp();
+                            return self.struct_lit(ty, token.span);
+                        }
+                        Err(err) if !matches!(err.kind, resolve::ErrorKind::Unresolved(_)) => {
+                            return Err(err.into())
+                        }
+                        Err(_) => {}
+                    }
+                }
+
+                // the latest declaration of a name wins, variables that are
+                // assigned without being declared are integers
+                let name = self.program.symbols.intern(var);
+                let i = match self.program.scope.get(&name) {
+                    Some(&i) => i,
+                    None => self.program.declare(name, Ty::Int, token.span),
+                };
+
+                ExprKind::Var(i)
+            }
+            TokenKind::OpenParen => {
+                let expr = self.expr(0)?.ok_or(Error::EOF)?;
+
+                if self.peek()?.map(|t| t.kind) == Some(TokenKind::Comma) {
+                    return self.tuple(token.span, expr).map(Some);
+                }
+
+                let end = self.expect(TokenKind::CloseParen)?;
+                return Ok(Some(expr.with_span(token.span + end.span)));
+            }
+            TokenKind::Let => return self.let_expr(token.span),
+            TokenKind::OpenBrace => return self.nested(token.span, |p| p.block(token.span)),
+            TokenKind::OpenBracket => return self.array(token.span),
+            TokenKind::Match => return self.match_expr(token.span),
+            TokenKind::If => return self.if_expr(token.span),
+            TokenKind::While => return self.while_expr(token.span),
+            TokenKind::For => return self.for_expr(token.span),
+            TokenKind::SizeOf => return self.size_of(token.span),
+            TokenKind::Goto => {
+                let target = self.next()?.ok_or(Error::EOF)?;
+                let label = match target.kind {
+                    TokenKind::Ident(label) => label.to_owned(),
+                    _ => return Err(Error::new(ErrorKind::ExpectedIdent, target.span)),
+                };
+
+                let span = token.span + target.span;
+                self.program.gotos.push(WithSpan::new(label.clone(), span));
+
+                return Ok(Some(Expr::new(ExprKind::Goto(label), span)));
+            }
+            TokenKind::Return if self.in_fn => {
+                let value = match self.peek()?.map(|t| t.kind) {
+                    Some(TokenKind::Semi | TokenKind::CloseBrace) => None,
+                    _ => Some(self.expr(0)?.ok_or(Error::EOF)?),
+                };
+
+                let span = value.as_ref().map_or(token.span, |v| token.span + v.span);
+                return Ok(Some(Expr::new(ExprKind::Return(value.map(Box::new)), span)));
+            }
+            TokenKind::Return => return Err(Error::new(ErrorKind::ReturnOutsideFn, token.span)),
+            TokenKind::Break if self.loop_depth > 0 => ExprKind::Break,
+            TokenKind::Continue if self.loop_depth > 0 => ExprKind::Continue,
+            TokenKind::Break => return Err(Error::new(ErrorKind::BreakOutsideLoop, token.span)),
+            TokenKind::Continue => {
+                return Err(Error::new(ErrorKind::ContinueOutsideLoop, token.span))
+            }
+            _ => return Err(Error::new(ErrorKind::ExpectedExpression, token.span)),
+        };
+
+        Ok(Some(Expr::new(kind, token.span)))
+    }
+
+    /// Parses the call or struct literal following a path with more than
+    /// one segment, `math::abs(x)` or `math::Point { x: 1, y: 2 }`.
+    fn path_expr(&mut self, path: ItemPath) -> Result<Option<Expr>, Error> {
+        let token = self.next()?.ok_or(Error::EOF)?;
+
+        match token.kind {
+            TokenKind::OpenParen => self.func_call(path),
+            TokenKind::OpenBrace => {
+                let ty = self.find_struct(&path)?;
+                self.struct_lit(ty, path.span())
+            }
+            _ => Err(Error::new(ErrorKind::UnexpectedPath, path.span())),
+        }
+    }
+
+    /// Parses any indices, slices and field accesses following `expr`.
+    fn postfix(&mut self, expr: Expr) -> Result<Expr, Error> {
+        let depth = self.depth;
+        let expr = self.accesses(expr);
+        self.depth = depth;
+        expr
+    }
+
+    /// Parses postfix operators, each nesting the expression before it a
+    /// level deeper.
+    fn accesses(&mut self, mut expr: Expr) -> Result<Expr, Error> {
+        while let Some(token) = self.peek()? {
+            match token.kind {
+                TokenKind::OpenBracket => {
+                    self.chomp();
+                    self.nest(token.span)?;
+                    expr = self.index(expr)?;
+                }
+                TokenKind::Dot => {
+                    self.chomp();
+
+                    for field in self.fields()? {
+                        self.nest(field.span)?;
+                        let span = expr.span + field.span;
+                        expr = Expr::new(
+                            ExprKind::Field(FieldExpr {
+                                base: Box::new(expr),
+                                field,
+                            }),
+                            span,
+                        );
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses an index, `s[i]`, or a slice, `s[a..b]`, after the opening
+    /// bracket. Either bound of a slice can be left out.
+    fn index(&mut self, base: Expr) -> Result<Expr, Error> {
+        let span = base.span;
+        let start = match self.peek()? {
+            Some(token) if token.kind == TokenKind::DotDot => None,
+            _ => Some(self.expr(0)?.ok_or(Error::EOF)?),
+        };
+
+        let kind = match self.peek()? {
+            Some(token) if token.kind == TokenKind::DotDot => {
+                self.chomp();
+
+                let end = match self.peek()? {
+                    Some(token) if token.kind == TokenKind::CloseBracket => None,
+                    _ => Some(self.expr(0)?.ok_or(Error::EOF)?),
+                };
+
+                ExprKind::Slice(Slice {
+                    base: Box::new(base),
+                    start: start.map(Box::new),
+                    end: end.map(Box::new),
+                })
+            }
+            _ => ExprKind::Index(Index {
+                base: Box::new(base),
+                index: Box::new(start.expect("index without a start")),
+            }),
+        };
+
+        let end = self.expect(TokenKind::CloseBracket)?;
+        Ok(Expr::new(kind, span + end.span))
+    }
+
+    /// Parses the name of a field after a `.`, or the index of a tuple
+    /// element. `t.0.1` is lexed as `t`, `.` and the float `0.1`, which is
+    /// two indices.
+    fn fields(&mut self) -> Result<Vec<WithSpan<String>>, Error> {
+        let token = self.peek()?.ok_or(Error::EOF)?;
+
+        match token.kind {
+            TokenKind::Num(index) => {
+                self.chomp();
+                Ok(vec![WithSpan::new(index.to_string(), token.span)])
+            }
+            TokenKind::Float(lit) if lit.bytes().all(|b| b.is_ascii_digit() || b == b'.') => {
+                self.chomp();
+
+                let (first, second) = lit.split_once('.').unwrap();
+                let start = token.span.start;
+                let dot = start + first.len();
+                Ok(vec![
+                    WithSpan::new(first.to_owned(), Span::new(start..dot)),
+                    WithSpan::new(second.to_owned(), Span::new(dot + 1..token.span.end)),
+                ])
+            }
+            _ => Ok(vec![self.ident()?]),
+        }
+    }
+
+    /// Parses `sizeof(type)` or `sizeof(expr)`.
+    fn size_of(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        self.expect(TokenKind::OpenParen)?;
+
+        // a type can only be told apart from an expression by the token
+        // after any leading `*`s, which are collected up front
+        let mut stars = Vec::new();
+        while let Some(token) = self.peek()? {
+            if token.kind != TokenKind::Mul {
+                break;
+            }
+
+            self.chomp();
+            stars.push(token.span);
+        }
+
+        let is_ty = match self.peek()?.map(|t| t.kind) {
+            Some(TokenKind::OpenBracket) => true,
+            Some(TokenKind::Ident(name)) => {
+                let path = ItemPath {
+                    segments: vec![WithSpan::new(name.to_owned(), Span::default())],
+                };
+
+                Ty::from_name(name).is_some()
+                    || self.resolve(&path, Namespace::Type).is_ok()
+                    || self.resolve(&path, Namespace::Module).is_ok()
+            }
+            _ => false,
+        };
+
+        let of = if is_ty {
+            let mut ty = self.ty()?;
+            for star in stars.into_iter().rev() {
+                ty = WithSpan::new(Ty::Ptr(Box::new(ty.value)), star + ty.span);
+            }
+
+            SizeOf::Ty(ty)
+        } else {
+            let mut expr = self.unary()?.ok_or(Error::EOF)?;
+            for star in stars.into_iter().rev() {
+                let span = star + expr.span;
+                expr = Expr::new(
+                    ExprKind::Unary(UnaryExpr {
+                        op: WithSpan::new(UnaryOp::Deref, star),
+                        expr: Box::new(expr),
+                    }),
+                    span,
+                );
+            }
+
+            let expr = self.binary(expr, 0)?.ok_or(Error::EOF)?;
+            SizeOf::Expr(Box::new(expr))
+        };
+
+        let end = self.expect(TokenKind::CloseParen)?;
+        Ok(Some(Expr::new(ExprKind::SizeOf(of), span + end.span)))
+    }
+
+    /// Parses the statements of a block up to its closing brace. The block
+    /// evaluates to its final expression, if it isn't followed by a
+    /// semicolon, `{ a = 1; a + 2 }`.
+    fn block(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        // variables declared in the block go out of scope at its end
+        let scope = self.program.scope.clone();
+        let mut stmts = Vec::new();
+
+        let (end, value) = loop {
+            let token = self.peek()?.ok_or(Error::EOF)?;
+            if token.kind == TokenKind::CloseBrace {
+                self.chomp();
+                break (token, None);
+            }
+
+            let expr = self.statement_expr()?;
+            let token = self.peek()?.ok_or(Error::EOF)?;
+
+            match (&expr.kind, token.kind) {
+                (ExprKind::Label(_), _) => stmts.push(expr),
+                (_, TokenKind::CloseBrace) => {
+                    self.chomp();
+                    break (token, Some(Box::new(expr)));
+                }
+                (_, TokenKind::Semi) => {
+                    self.chomp();
+                    stmts.push(expr);
+                }
+                (ExprKind::Block(_) | ExprKind::If(_) | ExprKind::While(_), _) => stmts.push(expr),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::UnterminatedExpression { end: expr.span },
+                        token.span,
+                    ))
+                }
+            }
+        };
+
+        self.program.scope = scope;
+
+        Ok(Some(Expr::new(
+            ExprKind::Block(Block { stmts, value }),
+            span + end.span,
+        )))
+    }
+
+    fn let_expr(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        let name = self.ident()?;
+        self.expect(TokenKind::Assign)?;
+        let init = self.expr(0)?.ok_or(Error::EOF)?;
+
+        // declared after the initializer, which still sees any previous
+        // variable of the same name
+        let symbol = self.program.symbols.intern(&name.value);
+        let var = self.program.declare(symbol, Ty::Infer, name.span);
+
+        let span = span + init.span;
+        Ok(Some(Expr::new(
+            ExprKind::Let(Let {
+                var,
+                init: Box::new(init),
+            }),
+            span,
+        )))
+    }
+
+    fn array(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        let first = self.expr(0)?.ok_or(Error::EOF)?;
+
+        let token = self.next()?.ok_or(Error::EOF)?;
+        let kind = match token.kind {
+            TokenKind::Semi => {
+                let token = self.next()?.ok_or(Error::EOF)?;
+                let len = match token.kind {
+                    TokenKind::Num(len) => WithSpan::new(len, token.span),
+                    _ => return Err(Error::new(ErrorKind::ExpectedNumber, token.span)),
+                };
+
+                let end = self.expect(TokenKind::CloseBracket)?;
+
+                return Ok(Some(Expr::new(
+                    ExprKind::Repeat(Repeat {
+                        value: Box::new(first),
+                        len,
+                    }),
+                    span + end.span,
+                )));
+            }
+            TokenKind::Comma | TokenKind::CloseBracket => token.kind,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ExpectedToken(TokenKind::CloseBracket),
+                    token.span,
+                ))
+            }
+        };
+
+        let mut elems = vec![first];
+
+        let end = if kind == TokenKind::CloseBracket {
+            token
+        } else {
+            loop {
+                match self.peek()? {
+                    // trailing comma
+                    Some(token) if token.kind == TokenKind::CloseBracket => {
+                        self.chomp();
+                        break token;
+                    }
+                    _ => {}
+                }
+
+                elems.push(self.expr(0)?.ok_or(Error::EOF)?);
+
+                let token = self.next()?.ok_or(Error::EOF)?;
+                match token.kind {
+                    TokenKind::CloseBracket => break token,
+                    TokenKind::Comma => continue,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::ExpectedToken(TokenKind::CloseBracket),
+                            token.span,
+                        ))
+                    }
+                }
+            }
+        };
+
+        Ok(Some(Expr::new(ExprKind::Array(elems), span + end.span)))
+    }
+
+    fn struct_lit(&mut self, ty: Rc<Struct>, span: Span) -> Result<Option<Expr>, Error> {
+        let mut fields = Vec::new();
+
+        let end = loop {
+            let token = self.next()?.ok_or(Error::EOF)?;
+            let name = match token.kind {
+                TokenKind::CloseBrace => break token,
+                TokenKind::Ident(name) => WithSpan::new(name.to_owned(), token.span),
+                _ => return Err(Error::new(ErrorKind::ExpectedIdent, token.span)),
+            };
+
+            self.expect(TokenKind::Colon)?;
+            let value = self.expr(0)?.ok_or(Error::EOF)?;
+            fields.push((name, value));
+
+            let token = self.next()?.ok_or(Error::EOF)?;
+            match token.kind {
+                TokenKind::CloseBrace => break token,
+                TokenKind::Comma => continue,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedToken(TokenKind::CloseBrace),
+                        token.span,
+                    ))
+                }
+            }
+        };
+
+        Ok(Some(Expr::new(
+            ExprKind::Struct(StructLit { ty, fields }),
+            span + end.span,
+        )))
+    }
+
+    /// Parses a struct definition, `struct Point { x, y: int }`.
+    ///
+    /// Fields without a type are integers.
+    fn struct_def(&mut self, public: bool) -> Result<(), Error> {
+        self.expect(TokenKind::Struct)?;
+        let name = self.ident()?;
+
+        let module = self.program.module;
+        let duplicate = self
+            .program
+            .structs
+            .iter()
+            .any(|s| s.module == module && s.name.value == name.value);
+
+        if duplicate {
+            return Err(Error::new(ErrorKind::DuplicateStruct, name.span));
+        }
+
+        self.expect(TokenKind::OpenBrace)?;
+
+        let mut fields: Vec<(String, Ty)> = Vec::new();
+
+        loop {
+            let token = self.next()?.ok_or(Error::EOF)?;
+            let field = match token.kind {
+                TokenKind::CloseBrace => break,
+                TokenKind::Ident(field) => field.to_owned(),
+                _ => return Err(Error::new(ErrorKind::ExpectedIdent, token.span)),
+            };
+
+            if fields.iter().any(|(name, _)| *name == field) {
+                return Err(Error::new(ErrorKind::DuplicateField, token.span));
+            }
+
+            let ty = match self.peek()? {
+                Some(token) if token.kind == TokenKind::Colon => {
+                    self.chomp();
+                    self.ty()?.value
+                }
+                _ => Ty::Int,
+            };
+
+            fields.push((field, ty));
+
+            let token = self.next()?.ok_or(Error::EOF)?;
+            match token.kind {
+                TokenKind::CloseBrace => break,
+                TokenKind::Comma => continue,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedToken(TokenKind::CloseBrace),
+                        token.span,
+                    ))
+                }
+            }
+        }
+
+        let items = self.program.items();
+
+        let ty = Struct::new(items.qualify(module, &name.value), fields);
+        self.program.structs.push(StructDef {
+            ty: Rc::new(ty),
+            name,
+            module,
+            public,
+        });
+
+        Ok(())
+    }
+
+    fn extern_fn(&mut self, public: bool) -> Result<Function, Error> {
+        let start = self.expect(TokenKind::Extern)?;
+        self.expect(TokenKind::Fn)?;
+        let name = self.ident()?;
+        let (params, variadic) = self.params()?;
+        let ret = self.ret()?;
+        let end = self.expect(TokenKind::Semi)?;
+
+        Ok(Function {
+            name,
+            params,
+            variadic,
+            ret,
+            module: self.program.module,
+            public,
+            external: true,
+            export: false,
+            span: start.span + end.span,
+        })
+    }
+
+    /// Parses the definition of a function,
+    /// `fn add(a: int, b: int) -> int { a + b }`, whose body has its own
+    /// variables and labels.
+    fn fn_def(&mut self, ast: &mut Ast, public: bool, export: bool) -> Result<(), Error> {
+        let start = self.expect(TokenKind::Fn)?;
+        let name = self.ident()?;
+        let open = self.peek()?.map(|t| t.span).unwrap_or(Span::EOF);
+        let (params, variadic) = self.params()?;
+        let ret = self.ret()?;
+
+        if variadic {
+            return Err(Error::new(ErrorKind::VariadicFn, open));
+        }
+
+        // the body can't see the variables of the program, or jump to its
+        // labels
+        let scope = mem::take(&mut self.program.scope);
+        let labels = mem::take(&mut self.program.labels);
+        let gotos = mem::take(&mut self.program.gotos);
+
+        let first = self.program.vars.len();
+        let param_vars = params
+            .iter()
+            .map(|param| {
+                let name = self.program.symbols.intern(&param.name.value);
+                self.program
+                    .declare(name, param.ty.value.clone(), param.name.span)
+            })
+            .collect();
+
+        let open = self.expect(TokenKind::OpenBrace)?;
+        self.in_fn = true;
+        let body = self.nested(open.span, |p| p.block(open.span));
+        self.in_fn = false;
+        let body = body?.ok_or(Error::EOF)?;
+
+        let program = &mut self.program;
+        if let Some(goto) = program
+            .gotos
+            .iter()
+            .find(|g| !program.labels.contains(&g.value))
+        {
+            return Err(Error::new(ErrorKind::UndefinedLabel, goto.span));
+        }
+
+        program.scope = scope;
+        program.labels = labels;
+        program.gotos = gotos;
+
+        program.functions.push(Function {
+            name,
+            params,
+            variadic,
+            ret,
+            module: program.module,
+            public,
+            external: false,
+            export,
+            span: start.span + body.span,
+        });
+
+        ast.defs.push(FnDef {
+            func: program.functions.len() - 1,
+            params: param_vars,
+            vars: first..program.vars.len(),
+            body,
+        });
+
+        Ok(())
+    }
+
+    /// Parses the parameters of a function, returning whether they end with
+    /// `...`.
+    fn params(&mut self) -> Result<(Vec<Param>, bool), Error> {
+        self.expect(TokenKind::OpenParen)?;
+
+        let mut params = Vec::new();
+        let mut variadic = false;
+
+        loop {
+            let token = self.next()?.ok_or(Error::EOF)?;
+            let name = match token.kind {
+                TokenKind::CloseParen => break,
+                TokenKind::Ellipsis => {
+                    // must be the last parameter
+                    self.expect(TokenKind::CloseParen)?;
+                    variadic = true;
+                    break;
+                }
+                TokenKind::Ident(name) => WithSpan::new(name.to_owned(), token.span),
+                _ => return Err(Error::new(ErrorKind::ExpectedIdent, token.span)),
+            };
+
+            self.expect(TokenKind::Colon)?;
+            let ty = self.ty()?;
+
+            let default = match self.peek()? {
+                Some(token) if token.kind == TokenKind::Assign => {
+                    self.chomp();
+                    Some(self.expr(0)?.ok_or(Error::EOF)?)
+                }
+                _ => None,
+            };
+
+            params.push(Param { name, ty, default });
+
+            let token = self.next()?.ok_or(Error::EOF)?;
+            match token.kind {
+                TokenKind::CloseParen => break,
+                TokenKind::Comma => continue,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedToken(TokenKind::CloseParen),
+                        token.span,
+                    ))
+                }
+            }
         }
-    }
 
-    fn peek(&mut self) -> Result<Option<Token<'a>>, lex::Error> {
-        self.tokens.peek().copied().transpose()
+        Ok((params, variadic))
     }
 
-    fn next(&mut self) -> Result<Option<Token<'a>>, lex::Error> {
-        self.tokens.next().transpose()
+    /// Parses the return type of a function, if it has one.
+    fn ret(&mut self) -> Result<Option<WithSpan<Ty>>, Error> {
+        match self.peek()? {
+            Some(token) if token.kind == TokenKind::Arrow => {
+                self.chomp();
+                Ok(Some(self.ty()?))
+            }
+            _ => Ok(None),
+        }
     }
 
-    fn chomp(&mut self) {
-        let _ = self.next().unwrap();
+    fn ident(&mut self) -> Result<WithSpan<String>, Error> {
+        let token = self.next()?.ok_or(Error::EOF)?;
+
+        match token.kind {
+            TokenKind::Ident(ident) => Ok(WithSpan::new(ident.to_owned(), token.span)),
+            _ => Err(Error::new(ErrorKind::ExpectedIdent, token.span)),
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Ast, Error> {
-        let mut exprs = Vec::new();
+    fn ty(&mut self) -> Result<WithSpan<Ty>, Error> {
+        if let Some(token) = self.peek()? {
+            if token.kind == TokenKind::Mul {
+                self.chomp();
+                let pointee = self.nested(token.span, Self::ty)?;
+                let span = token.span + pointee.span;
+                return Ok(WithSpan::new(Ty::Ptr(Box::new(pointee.value)), span));
+            }
 
-        while let Some(expr) = self.expr(0)? {
-            let token = self.next()?;
+            if token.kind == TokenKind::OpenParen {
+                self.chomp();
 
-            if !matches!(
-                token,
-                Some(Token {
-                    kind: TokenKind::Semi,
-                    ..
-                }),
-            ) {
-                return Err(Error::new(
-                    ErrorKind::UnterminatedExpression,
-                    token.map(|t| t.span).unwrap_or(Span::EOF),
-                ));
+                let mut elems = vec![self.nested(token.span, Self::ty)?.value];
+                let mut tuple = false;
+                let end = loop {
+                    let next = self.next()?.ok_or(Error::EOF)?;
+
+                    match next.kind {
+                        TokenKind::CloseParen => break next,
+                        TokenKind::Comma => {
+                            tuple = true;
+
+                            if self.peek()?.map(|t| t.kind) != Some(TokenKind::CloseParen) {
+                                elems.push(self.nested(token.span, Self::ty)?.value);
+                            }
+                        }
+                        _ => {
+                            return Err(Error::new(
+                                ErrorKind::ExpectedToken(TokenKind::CloseParen),
+                                next.span,
+                            ))
+                        }
+                    }
+                };
+
+                // `(int)` is just `int`, a tuple of one element is `(int,)`
+                let ty = match tuple {
+                    true => Ty::Tuple(Rc::new(Struct::tuple(elems))),
+                    false => elems.pop().unwrap(),
+                };
+
+                return Ok(WithSpan::new(ty, token.span + end.span));
             }
 
-            exprs.push(expr);
+            if token.kind == TokenKind::OpenBracket {
+                self.chomp();
+                let elem = self.nested(token.span, Self::ty)?;
+                self.expect(TokenKind::Semi)?;
+
+                let num = self.next()?.ok_or(Error::EOF)?;
+                let len = match num.kind {
+                    TokenKind::Num(len) => len,
+                    _ => return Err(Error::new(ErrorKind::ExpectedNumber, num.span)),
+                };
+
+                let end = self.expect(TokenKind::CloseBracket)?;
+                let span = token.span + end.span;
+                return Ok(WithSpan::new(Ty::Array(Box::new(elem.value), len), span));
+            }
         }
 
-        Ok(Ast {
-            exprs,
-            vars: mem::take(&mut self.vars),
-        })
-    }
+        let name = self.ident()?;
 
-    pub fn expr(&mut self, precedence: usize) -> Result<Option<Expr>, Error> {
-        let mut expr = match self.primary()? {
-            Some(e) => e,
-            None => return Ok(None),
-        };
+        if let Some(ty) = Ty::from_name(&name.value) {
+            return Ok(WithSpan::new(ty, name.span));
+        }
 
-        loop {
-            let token = match self.peek()? {
-                Some(t) => t,
-                None => return Ok(Some(expr)),
-            };
+        let path = self.path(name)?;
+        match self.find_struct(&path) {
+            Ok(ty) => Ok(WithSpan::new(Ty::Struct(ty), path.span())),
+            Err(err) if path.segments.len() == 1 => match err.kind {
+                resolve::ErrorKind::Unresolved(_) => {
+                    Err(Error::new(ErrorKind::UnknownType, path.span()))
+                }
+                _ => Err(err.into()),
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
 
-            let op = match token.kind {
-                TokenKind::Add => BinaryOp::Add,
-                TokenKind::Sub => BinaryOp::Sub,
-                TokenKind::Mul => BinaryOp::Mul,
-                TokenKind::Div => BinaryOp::Div,
-                TokenKind::Assign => BinaryOp::Assign,
-                TokenKind::Semi | TokenKind::CloseParen => return Ok(Some(expr)),
-                _ => return Err(Error::new(ErrorKind::ExpectedOperator, token.span)),
-            };
+    /// Parses a conditional after the `if`, `if a < b { a } else { b }`.
+    /// Each branch is a block, though the `else` branch may also be another
+    /// conditional.
+    fn if_expr(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        let cond = self.expr(0)?.ok_or(Error::EOF)?;
 
-            if op.precedence() < precedence {
-                return Ok(Some(expr));
-            }
+        let open = self.expect(TokenKind::OpenBrace)?;
+        let then = self
+            .nested(open.span, |p| p.block(open.span))?
+            .ok_or(Error::EOF)?;
 
-            self.chomp();
+        let mut end = then.span;
+        let mut otherwise = None;
 
-            let right = self.expr(op.precedence() + 1)?.ok_or(Error::EOF)?;
+        if self.peek()?.map(|t| t.kind) == Some(TokenKind::Else) {
+            self.chomp();
 
-            expr = Expr {
-                span: expr.span + right.span,
-                kind: ExprKind::Binary(BinaryExpr {
-                    op: WithSpan::new(op, token.span),
-                    left: Box::new(expr),
-                    right: Box::new(right),
-                }),
+            let token = self.next()?.ok_or(Error::EOF)?;
+            let branch = match token.kind {
+                TokenKind::OpenBrace => self.nested(token.span, |p| p.block(token.span))?,
+                TokenKind::If => self.nested(token.span, |p| p.if_expr(token.span))?,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedToken(TokenKind::OpenBrace),
+                        token.span,
+                    ))
+                }
             };
+
+            let branch = branch.ok_or(Error::EOF)?;
+            end = branch.span;
+            otherwise = Some(Box::new(branch));
         }
+
+        Ok(Some(Expr::new(
+            ExprKind::If(If {
+                cond: Box::new(cond),
+                then: Box::new(then),
+                otherwise,
+            }),
+            span + end,
+        )))
     }
 
-    fn primary(&mut self) -> Result<Option<Expr>, Error> {
-        let token = match self.next()? {
-            Some(t) => t,
-            None => {
-                return Ok(None);
+    /// Parses a loop after the `while`, `while i < 10 { i = i + 1; }`.
+    fn while_expr(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        let cond = self.expr(0)?.ok_or(Error::EOF)?;
+        let body = self.loop_body()?;
+        let span = span + body.span;
+
+        Ok(Some(Expr::new(
+            ExprKind::While(While {
+                cond: Box::new(cond),
+                body: Box::new(body),
+                step: None,
+            }),
+            span,
+        )))
+    }
+
+    /// Parses a C-style loop after the `for`,
+    /// `for (let i = 0; i < 10; i = i + 1) { .. }`, as a block holding the
+    /// initializer and a `while` loop. Any of the three parts can be left
+    /// out, a missing condition always holding.
+    fn for_expr(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        // variables declared by the initializer go out of scope after the
+        // loop
+        let scope = self.program.scope.clone();
+        self.expect(TokenKind::OpenParen)?;
+
+        let init = match self.peek()? {
+            Some(token) if token.kind == TokenKind::Semi => None,
+            _ => Some(self.expr(0)?.ok_or(Error::EOF)?),
+        };
+
+        let semi = self.expect(TokenKind::Semi)?;
+        let cond = match self.peek()? {
+            Some(token) if token.kind == TokenKind::Semi => {
+                let always = WithSpan::new(Lit::Bool(true), semi.span);
+                Expr::new(ExprKind::Lit(always), semi.span)
             }
+            _ => self.expr(0)?.ok_or(Error::EOF)?,
         };
 
-        let kind = match token.kind {
-            TokenKind::Num(num) => ExprKind::Lit(WithSpan::new(Lit::Num(num), token.span)),
-            TokenKind::Str(lit) => {
-                ExprKind::Lit(WithSpan::new(Lit::String(lit.to_owned()), token.span))
+        self.expect(TokenKind::Semi)?;
+        let step = match self.peek()? {
+            Some(token) if token.kind == TokenKind::CloseParen => None,
+            _ => Some(Box::new(self.expr(0)?.ok_or(Error::EOF)?)),
+        };
+
+        self.expect(TokenKind::CloseParen)?;
+        let body = self.loop_body()?;
+        self.program.scope = scope;
+
+        let span = span + body.span;
+        let looped = Expr::new(
+            ExprKind::While(While {
+                cond: Box::new(cond),
+                body: Box::new(body),
+                step,
+            }),
+            span,
+        );
+
+        Ok(Some(Expr::new(
+            ExprKind::Block(Block {
+                stmts: init.into_iter().chain(Some(looped)).collect(),
+                value: None,
+            }),
+            span,
+        )))
+    }
+
+    /// Parses the block of a loop, in which `break` and `continue` are
+    /// allowed.
+    fn loop_body(&mut self) -> Result<Expr, Error> {
+        let open = self.expect(TokenKind::OpenBrace)?;
+
+        self.loop_depth += 1;
+        let body = self.nested(open.span, |p| p.block(open.span));
+        self.loop_depth -= 1;
+
+        body?.ok_or(Error::EOF)
+    }
+
+    fn match_expr(&mut self, span: Span) -> Result<Option<Expr>, Error> {
+        let scrutinee = self.expr(0)?.ok_or(Error::EOF)?;
+        self.expect(TokenKind::OpenBrace)?;
+
+        let mut arms: Vec<Arm> = Vec::new();
+        let mut default = None;
+
+        let end = loop {
+            let token = self.next()?.ok_or(Error::EOF)?;
+            let pattern = match token.kind {
+                TokenKind::CloseBrace => break token,
+                TokenKind::Num(num) => Some(num),
+                TokenKind::Underscore => None,
+                _ => return Err(Error::new(ErrorKind::ExpectedPattern, token.span)),
+            };
+
+            self.expect(TokenKind::FatArrow)?;
+            let body = self.expr(0)?.ok_or(Error::EOF)?;
+
+            match pattern {
+                Some(num) if arms.iter().any(|arm| arm.value.value == num) => {
+                    return Err(Error::new(ErrorKind::DuplicateArm, token.span));
+                }
+                Some(num) => arms.push(Arm {
+                    value: WithSpan::new(num, token.span),
+                    body,
+                }),
+                None if default.is_some() => {
+                    return Err(Error::new(ErrorKind::DuplicateArm, token.span));
+                }
+                None => default = Some(Box::new(body)),
             }
-            TokenKind::Ident(var) => {
-                if self.peek()?.map(|t| t.kind) == Some(TokenKind::OpenParen) {
-                    self.chomp();
-                    return self.func_call(var, token.span);
-                }
-
-                let i = self
-                    .vars
-                    .iter()
-                    .position(|v| v.name == var)
-                    .unwrap_or_else(|| {
-                        self.vars.push(Var {
-                            name: var.to_owned(),
-                        });
-                        self.vars.len() - 1
-                    });
 
-                ExprKind::Var(i)
+            let token = self.next()?.ok_or(Error::EOF)?;
+            match token.kind {
+                TokenKind::Comma => continue,
+                TokenKind::CloseBrace => break token,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::ExpectedToken(TokenKind::CloseBrace),
+                        token.span,
+                    ))
+                }
             }
-            _ => return Err(Error::new(ErrorKind::ExpectedExpression, token.span)),
         };
 
-        Ok(Some(Expr {
-            kind,
-            span: token.span,
-        }))
+        Ok(Some(Expr::new(
+            ExprKind::Match(Match {
+                scrutinee: Box::new(scrutinee),
+                arms,
+                default,
+            }),
+            span + end.span,
+        )))
     }
 
-    fn func_call(&mut self, ident: &str, span: Span) -> Result<Option<Expr>, Error> {
+    fn func_call(&mut self, path: ItemPath) -> Result<Option<Expr>, Error> {
         let mut args = Vec::new();
 
         let end = loop {
             match self.peek() {
                 Ok(Some(token)) if token.kind == TokenKind::CloseParen => {
+                    self.chomp();
                     break token;
                 }
                 _ => {}
@@ -164,65 +1740,460 @@ impl<'a> Parser<'a> {
             }
         };
 
-        Ok(Some(Expr {
-            kind: ExprKind::Call(Call {
-                name: ident.to_owned(),
+        let span = path.span() + end.span;
+        Ok(Some(Expr::new(
+            ExprKind::Call(Call {
+                path,
                 args,
+                func: None,
+                builtin: None,
             }),
-            span: span + end.span,
-        }))
+            span,
+        )))
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
 pub struct Ast {
     pub exprs: Vec<Expr>,
+    pub functions: Vec<Function>,
+    pub defs: Vec<FnDef>,
+    pub structs: Vec<StructDef>,
+    /// The modules of the program, starting with the root module.
+    pub modules: Vec<Module>,
+    /// The module holding the prelude, if it is enabled.
+    pub prelude: Option<usize>,
     pub vars: Vec<Var>,
+    /// The names of variables.
+    pub symbols: Interner,
+}
+
+/// A module, `mod math { ... }`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Module {
+    pub name: WithSpan<String>,
+    pub parent: Option<usize>,
+    pub public: bool,
+    pub uses: Vec<Use>,
+}
+
+/// `use a::b;` or `use a::*;`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Use {
+    pub path: ItemPath,
+    pub glob: bool,
+}
+
+/// A path to an item, `math::abs`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ItemPath {
+    pub segments: Vec<WithSpan<String>>,
+}
+
+impl ItemPath {
+    pub fn span(&self) -> Span {
+        self.segments[0].span + self.segments[self.segments.len() - 1].span
+    }
+}
+
+impl fmt::Display for ItemPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i != 0 {
+                write!(f, "::")?;
+            }
+
+            write!(f, "{}", segment.value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StructDef {
+    pub ty: Rc<Struct>,
+    pub name: WithSpan<String>,
+    pub module: usize,
+    pub public: bool,
+}
+
+/// The signature of a function, either a declaration of an external (C)
+/// function, `extern fn puts(s: str) -> int;`, or a function defined in the
+/// program, whose body is one of `Ast::defs`.
+///
+/// Variadic functions end their parameter list with `...`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Function {
+    pub name: WithSpan<String>,
+    pub params: Vec<Param>,
+    pub variadic: bool,
+    pub ret: Option<WithSpan<Ty>>,
+    pub module: usize,
+    pub public: bool,
+    /// Whether the function is defined in C, rather than in the program.
+    pub external: bool,
+    /// Whether the function is defined in the program for C to call,
+    /// `export fn add(a: int, b: int) -> int { a + b }`, under its own name
+    /// rather than a `ripc.` symbol.
+    pub export: bool,
+    pub span: Span,
+}
+
+impl Function {
+    /// Whether the function is called the way C calls it, with strings
+    /// passed and returned as NUL-terminated pointers.
+    pub fn is_c(&self) -> bool {
+        self.external || self.export
+    }
 }
 
+/// The body of a function defined in the program,
+/// `fn add(a: int, b: int) -> int { a + b }`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FnDef {
+    /// The index of the function's signature.
+    pub func: usize,
+    /// The variable each parameter is bound to.
+    pub params: Vec<usize>,
+    /// The variables of the function, starting with its parameters, which
+    /// live in its own frame.
+    pub vars: Range<usize>,
+    pub body: Expr,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Param {
+    pub name: WithSpan<String>,
+    pub ty: WithSpan<Ty>,
+    /// A constant that is passed when the argument is omitted, `b: int = 1`.
+    pub default: Option<Expr>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Var {
-    name: String,
+    pub name: Symbol,
+    pub ty: Ty,
+    /// The name where the variable is declared, or first assigned if it
+    /// isn't declared.
+    pub span: Span,
+}
+
+impl Var {
+    fn new(name: Symbol, ty: Ty, span: Span) -> Self {
+        Self { name, ty, span }
+    }
 }
 
+#[derive(Debug, PartialEq, Clone)]
 pub struct Expr {
     pub kind: ExprKind,
     pub span: Span,
+    /// The type of the expression, filled in by sema.
+    pub ty: Ty,
+}
+
+impl Expr {
+    fn new(kind: ExprKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            ty: Ty::Infer,
+        }
+    }
+
+    /// Returns the expression with a different span.
+    pub fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
 }
 
+/// The operands of an expression are evaluated left to right, along with
+/// their side effects, so `x + (x = 10)` adds the old value of `x` to 10.
+/// Assignment is the exception, its value is evaluated before the place it
+/// is assigned to.
+#[derive(Debug, PartialEq, Clone)]
 pub enum ExprKind {
     Lit(WithSpan<Lit>),
     Binary(BinaryExpr),
+    Unary(UnaryExpr),
     Call(Call),
     Var(usize),
+    Match(Match),
+    If(If),
+    While(While),
+    /// `return` or `return value`, only in the body of a function.
+    Return(Option<Box<Expr>>),
+    Break,
+    Continue,
+    Label(String),
+    Goto(String),
+    Let(Let),
+    /// An array literal, `[1, 2, 3]`.
+    Array(Vec<Expr>),
+    /// A tuple literal, `(1, 2.5)`, or the places assigned to by a
+    /// destructuring assignment, `x, y = pair`.
+    Tuple(Vec<Expr>),
+    Repeat(Repeat),
+    Index(Index),
+    /// A slice of a string, `s[a..b]`, `s[a..]` or `s[..b]`.
+    Slice(Slice),
+    /// A struct literal, `Point { x: 1, y: 2 }`.
+    Struct(StructLit),
+    Field(FieldExpr),
+    SizeOf(SizeOf),
+    Cast(Cast),
+    Block(Block),
+}
+
+/// A sequence of statements, `{ a = 1; a + 2 }`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Block {
+    pub stmts: Vec<Expr>,
+    /// The final expression, if it isn't followed by a semicolon, which the
+    /// block evaluates to.
+    pub value: Option<Box<Expr>>,
+}
+
+/// A variable declaration, `let x = 1;`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Let {
+    pub var: usize,
+    pub init: Box<Expr>,
+}
+
+/// An array of `len` copies of a value, `[0; 16]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Repeat {
+    pub value: Box<Expr>,
+    pub len: WithSpan<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Index {
+    pub base: Box<Expr>,
+    pub index: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Slice {
+    pub base: Box<Expr>,
+    /// The index of the first byte, which defaults to the start.
+    pub start: Option<Box<Expr>>,
+    /// The index after the last byte, which defaults to the end.
+    pub end: Option<Box<Expr>>,
+}
+
+/// A struct literal, whose fields are evaluated in the order they are
+/// written rather than the order they are declared in.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructLit {
+    pub ty: Rc<Struct>,
+    pub fields: Vec<(WithSpan<String>, Expr)>,
+}
+
+/// A field access, `p.x`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldExpr {
+    pub base: Box<Expr>,
+    pub field: WithSpan<String>,
+}
+
+/// A conversion between numeric types, `x as float`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cast {
+    pub expr: Box<Expr>,
+    pub ty: WithSpan<Ty>,
+}
+
+/// The size of a type, or of the type of an expression, in bytes.
+///
+/// The expression is only type checked, never evaluated.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SizeOf {
+    Ty(WithSpan<Ty>),
+    Expr(Box<Expr>),
+}
+
+impl SizeOf {
+    pub fn size(&self) -> usize {
+        match self {
+            SizeOf::Ty(ty) => ty.value.size(),
+            SizeOf::Expr(expr) => expr.ty.size(),
+        }
+    }
 }
 
+/// A call, whose arguments are evaluated left to right, followed by the
+/// defaults of any that are left out, before the function is called.
+#[derive(Debug, PartialEq, Clone)]
 pub struct Call {
-    pub name: String,
+    pub path: ItemPath,
     pub args: Vec<Expr>,
+    /// The index of the extern function being called, filled in by name
+    /// resolution.
+    pub func: Option<usize>,
+    /// The builtin being called, filled in by name resolution.
+    pub builtin: Option<Builtin>,
 }
 
+/// A function provided by the compiler. Functions in scope take precedence
+/// over builtins of the same name.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Builtin {
+    /// `len(x)`, the length of an array or string.
+    Len,
+    /// `print(a, b, ...)`, prints its arguments separated by spaces, or
+    /// `print("a = {}", a)`, prints them in the placeholders of a format
+    /// string (see [`crate::format`]).
+    Print,
+    /// `println(a, b, ...)`, like `print` followed by a newline.
+    Println,
+    /// `read_int()`, reads an integer from standard input, or 0 if there
+    /// isn't one.
+    ReadInt,
+    /// `argc()`, the number of command-line arguments.
+    Argc,
+    /// `argv(i)`, the command-line argument at index `i`.
+    Argv,
+    /// `assert(cond)`, aborts with the location and text of the assertion
+    /// if `cond` is false.
+    Assert,
+    /// `panic(msg)`, exits with status 101 after printing `msg` and the
+    /// location of the call.
+    Panic,
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        match name {
+            "len" => Some(Builtin::Len),
+            "print" => Some(Builtin::Print),
+            "println" => Some(Builtin::Println),
+            "read_int" => Some(Builtin::ReadInt),
+            "argc" => Some(Builtin::Argc),
+            "argv" => Some(Builtin::Argv),
+            "assert" => Some(Builtin::Assert),
+            "panic" => Some(Builtin::Panic),
+            _ => None,
+        }
+    }
+}
+
+/// A multi-way branch over integer values, `match x { 1 => a, 2 => b, _ => c }`.
+///
+/// Evaluates to the body of the arm matching the scrutinee, the default
+/// arm if none do, or `0` if there is no default arm.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Match {
+    pub scrutinee: Box<Expr>,
+    pub arms: Vec<Arm>,
+    pub default: Option<Box<Expr>>,
+}
+
+/// A conditional, `if a < b { a } else { b }`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct If {
+    pub cond: Box<Expr>,
+    /// The block evaluated when the condition is true.
+    pub then: Box<Expr>,
+    /// The block or conditional evaluated when the condition is false.
+    pub otherwise: Option<Box<Expr>>,
+}
+
+/// A loop, `while i < 10 { i = i + 1; }`, which runs its body for as long
+/// as the condition holds.
+#[derive(Debug, PartialEq, Clone)]
+pub struct While {
+    pub cond: Box<Expr>,
+    pub body: Box<Expr>,
+    /// The step of a `for` loop, evaluated after the body and on
+    /// `continue`.
+    pub step: Option<Box<Expr>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Arm {
+    pub value: WithSpan<usize>,
+    pub body: Expr,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Lit {
     Num(usize),
-    String(String),
+    /// Shares storage with every other occurrence of the same string.
+    String(Rc<str>),
+    Float(f64),
+    Bool(bool),
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BinaryOp {
     Sub,
     Add,
     Mul,
     Div,
     Assign,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `&&`, which only evaluates its right operand if the left is true.
+    And,
+    /// `||`, which only evaluates its right operand if the left is false.
+    Or,
 }
 
 impl BinaryOp {
     fn precedence(&self) -> usize {
         match self {
             BinaryOp::Assign => 1,
-            BinaryOp::Sub | BinaryOp::Add => 2,
-            BinaryOp::Mul | BinaryOp::Div => 3,
+            BinaryOp::Or => 2,
+            BinaryOp::And => 3,
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => 4,
+            BinaryOp::Sub | BinaryOp::Add => 5,
+            BinaryOp::Mul | BinaryOp::Div => 6,
         }
     }
+
+    pub fn is_logical(&self) -> bool {
+        matches!(self, BinaryOp::And | BinaryOp::Or)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnaryOp {
+    /// `&x`
+    AddrOf,
+    /// `*p`
+    Deref,
+    /// `-x`
+    Neg,
+    /// `!b`
+    Not,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnaryExpr {
+    pub op: WithSpan<UnaryOp>,
+    pub expr: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct BinaryExpr {
     pub left: Box<Expr>,
     pub op: WithSpan<BinaryOp>,
@@ -238,11 +2209,43 @@ pub struct Error {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ErrorKind {
     ExpectedNumber,
-    ExpectedOperator,
+    /// A token that can't follow the expression spanning `after`.
+    ExpectedOperator {
+        after: Span,
+    },
     ExpectedExpression,
+    ExpectedToken(TokenKind<'static>),
+    ExpectedPattern,
+    ExpectedIdent,
+    DuplicateArm,
+    DuplicateLabel,
+    DuplicateStruct,
+    DuplicateField,
+    DuplicateModule,
+    ExpectedItem,
+    StatementInModule,
+    UnexpectedPath,
+    UndefinedLabel,
+    UnknownType,
+    ExpectedPath,
+    Import {
+        path: String,
+        error: String,
+    },
+    ImportCycle(Vec<String>),
     UnexpectedEof,
-    UnterminatedExpression,
+    /// An expression not followed by a semicolon, spanning `end`.
+    UnterminatedExpression {
+        end: Span,
+    },
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    ReturnOutsideFn,
+    VariadicFn,
+    /// An expression nested deeper than the limit.
+    TooDeep(usize),
     Lex(lex::Error),
+    Resolve(resolve::Error),
 }
 
 impl Error {
@@ -256,25 +2259,120 @@ impl Error {
     }
 }
 
-impl<W: Write> Report<W> for Error {
-    fn report(&self, f: &mut Reporter<'_, W>) -> std::io::Result<()> {
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        use ErrorKind::*;
+
+        match self.kind {
+            ExpectedNumber => "E0101",
+            ExpectedOperator { .. } => "E0102",
+            ExpectedExpression => "E0103",
+            ExpectedToken(..) => "E0104",
+            ExpectedPattern => "E0105",
+            ExpectedIdent => "E0106",
+            DuplicateArm => "E0107",
+            DuplicateLabel => "E0108",
+            DuplicateStruct => "E0109",
+            DuplicateField => "E0110",
+            DuplicateModule => "E0111",
+            ExpectedItem => "E0112",
+            StatementInModule => "E0113",
+            UnexpectedPath => "E0114",
+            UndefinedLabel => "E0115",
+            UnknownType => "E0116",
+            ExpectedPath => "E0117",
+            Import { .. } => "E0118",
+            ImportCycle(..) => "E0119",
+            UnexpectedEof => "E0120",
+            UnterminatedExpression { .. } => "E0121",
+            BreakOutsideLoop => "E0122",
+            ContinueOutsideLoop => "E0123",
+            ReturnOutsideFn => "E0124",
+            VariadicFn => "E0125",
+            TooDeep(..) => "E0126",
+            Lex(ref err) => err.code(),
+            Resolve(ref err) => err.code(),
+        }
+    }
+
+    fn message(&self, sources: &SourceMap) -> String {
         use ErrorKind::*;
 
         match self.kind {
-            ExpectedExpression => write!(
-                f.out,
+            ExpectedExpression => format!(
                 "Expected expression, found '{}'",
-                self.span.range().map(|x| &f.source[x]).unwrap_or("EOF")
+                sources.slice(self.span).unwrap_or("EOF")
             ),
-            ExpectedOperator => write!(
-                f.out,
+            ExpectedOperator { .. } => format!(
                 "Expected binary operator, found '{}'",
-                self.span.range().map(|x| &f.source[x]).unwrap_or("EOF")
+                sources.slice(self.span).unwrap_or("EOF")
             ),
-            ExpectedNumber => write!(f.out, "Expected number"),
-            UnexpectedEof => write!(f.out, "Unexpected EOF"),
-            UnterminatedExpression => write!(f.out, "Unterminated expression"),
-            Lex(ref err) => err.report(f),
+            ExpectedToken(kind) => format!(
+                "Expected '{}', found '{}'",
+                kind,
+                sources.slice(self.span).unwrap_or("EOF")
+            ),
+            ExpectedPattern => "Expected integer literal or '_'".to_owned(),
+            ExpectedIdent => "Expected identifier".to_owned(),
+            DuplicateArm => "Duplicate match arm".to_owned(),
+            DuplicateLabel => "Label is defined more than once".to_owned(),
+            DuplicateStruct => "Struct is defined more than once".to_owned(),
+            DuplicateField => "Field is declared more than once".to_owned(),
+            DuplicateModule => "Module is defined more than once".to_owned(),
+            ExpectedItem => "Expected 'extern', 'struct' or 'mod' after 'pub'".to_owned(),
+            StatementInModule => "Statements can only appear outside of modules".to_owned(),
+            UnexpectedPath => "Expected a call or struct literal after path".to_owned(),
+            UndefinedLabel => "Use of undefined label".to_owned(),
+            UnknownType => format!(
+                "Unknown type '{}'",
+                sources.slice(self.span).unwrap_or("EOF")
+            ),
+            ExpectedNumber => "Expected number".to_owned(),
+            ExpectedPath => "Expected a path string".to_owned(),
+            Import {
+                ref path,
+                ref error,
+            } => format!("Failed to import '{}': {}", path, error),
+            ImportCycle(ref chain) => format!("Import cycle: {}", chain.join(" -> ")),
+            UnexpectedEof => "Unexpected EOF".to_owned(),
+            UnterminatedExpression { .. } => "Unterminated expression".to_owned(),
+            BreakOutsideLoop => "`break` outside of a loop".to_owned(),
+            ContinueOutsideLoop => "`continue` outside of a loop".to_owned(),
+            ReturnOutsideFn => "`return` outside of a function".to_owned(),
+            VariadicFn => "Only extern functions can be variadic".to_owned(),
+            TooDeep(limit) => format!("Expression is nested more than {} levels deep", limit),
+            Lex(ref err) => err.message(sources),
+            Resolve(ref err) => err.message(sources),
+        }
+    }
+
+    fn failure(&self) -> Failure {
+        match self.kind {
+            ErrorKind::Lex(ref err) => err.failure(),
+            ErrorKind::Resolve(_) => Failure::Semantic,
+            ErrorKind::Import { .. } => Failure::Environment,
+            _ => Failure::Syntax,
+        }
+    }
+
+    fn suggestion(&self) -> Option<Suggestion> {
+        match self.kind {
+            ErrorKind::UnterminatedExpression { end } => {
+                let end = end.range()?.end;
+                Some(Suggestion {
+                    message: "insert ';'".to_owned(),
+                    span: Span::new(end..end),
+                    replacement: ";".to_owned(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn note(&self) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::TooDeep(_) => Some("the limit can be raised with '--max-depth=<n>'"),
+            _ => None,
         }
     }
 }
@@ -288,33 +2386,57 @@ impl From<lex::Error> for Error {
     }
 }
 
+impl From<resolve::Error> for Error {
+    fn from(err: resolve::Error) -> Self {
+        Self {
+            span: err.span,
+            kind: ErrorKind::Resolve(err),
+        }
+    }
+}
+
 impl Spanned for Error {
     fn span(&self) -> Span {
         self.span
     }
 }
 
+/// The tokens of a source, without whitespace, with any number of tokens of
+/// lookahead.
 pub struct Tokens<'a> {
     lexer: Lexer<'a>,
-    peeked: Option<Option<Result<Token<'a>, lex::Error>>>,
+    /// Tokens that have been peeked at but not consumed, in order. `None` is
+    /// the end of the source.
+    pending: VecDeque<Option<Result<Token<'a>, lex::Error>>>,
 }
 
 impl<'a> Tokens<'a> {
-    pub fn peek(&mut self) -> Option<&Result<Token<'a>, lex::Error>> {
-        if let Some(ref token) = self.peeked {
-            return token.as_ref();
+    fn new(lexer: Lexer<'a>) -> Self {
+        Self {
+            lexer,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the token `n` tokens ahead of the next one, without consuming
+    /// any tokens.
+    pub fn peek_n(&mut self, n: usize) -> Option<&Result<Token<'a>, lex::Error>> {
+        while self.pending.len() <= n {
+            let token = self.lex();
+            self.pending.push_back(token);
         }
 
+        self.pending[n].as_ref()
+    }
+
+    fn lex(&mut self) -> Option<Result<Token<'a>, lex::Error>> {
         loop {
             match self.lexer.next() {
                 Some(Ok(Token {
                     kind: TokenKind::Whitespace,
                     ..
                 })) => continue,
-                t => {
-                    self.peeked.replace(t);
-                    break self.peeked.as_ref().unwrap().as_ref();
-                }
+                token => break token,
             }
         }
     }
@@ -324,19 +2446,9 @@ impl<'a> Iterator for Tokens<'a> {
     type Item = Result<Token<'a>, lex::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            let token = match self.peeked.take() {
-                Some(v) => v,
-                None => self.lexer.next(),
-            };
-
-            match token {
-                Some(Ok(Token {
-                    kind: TokenKind::Whitespace,
-                    ..
-                })) => continue,
-                t => break t,
-            }
+        match self.pending.pop_front() {
+            Some(token) => token,
+            None => self.lex(),
         }
     }
 }
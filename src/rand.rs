@@ -1,27 +1,49 @@
-use std::cell::Cell;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-use std::thread;
-use std::time::Instant;
-
-pub fn rand() -> u64 {
-    pub struct FastRng(Cell<u64>);
-
-    thread_local! {
-        static RNG: FastRng = FastRng(Cell::new({
-            let mut hasher = DefaultHasher::new();
-            Instant::now().hash(&mut hasher);
-            thread::current().id().hash(&mut hasher);
-            (hasher.finish() << 1) | 1
-        }));
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small pseudo-random number generator (xorshift64*).
+///
+/// It is always seeded explicitly, so anything built on it can be reproduced
+/// from the seed alone.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 spreads nearby seeds apart, xorshift can't leave zero
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+
+        Self { state: z.max(1) }
     }
 
-    RNG.with(|rng| {
-        let mut x = rng.0.get();
-        x ^= x >> 12;
-        x ^= x << 25;
-        x ^= x >> 27;
-        rng.0.set(x);
-        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
-    })
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Returns true `percent` times out of a hundred.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// Returns a seed taken from the clock, for when none is given.
+pub fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
 }
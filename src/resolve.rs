@@ -0,0 +1,538 @@
+use crate::parse::{
+    Ast, Builtin, Call, Expr, ExprKind, Function, ItemPath, Module, SizeOf, StructDef,
+};
+use crate::source::SourceMap;
+use crate::{Failure, Report, Span, Spanned, WithSpan};
+
+/// Resolves the path of every call to the function it refers to, after
+/// checking that the items of every module are unique and that every `use`
+/// refers to an item.
+///
+/// Resolution continues after an error in a `use`, declaration or statement,
+/// so every error is returned. Calls in a statement that failed to resolve
+/// may be left unresolved.
+pub fn check(ast: &mut Ast) -> Result<(), Vec<Error>> {
+    let items = Items {
+        modules: &ast.modules,
+        structs: &ast.structs,
+        functions: &ast.functions,
+        prelude: ast.prelude,
+    };
+
+    let mut errors = Vec::new();
+
+    for (i, func) in ast.functions.iter().enumerate() {
+        let duplicate = ast.functions[..i]
+            .iter()
+            .any(|other| other.module == func.module && other.name.value == func.name.value);
+
+        if duplicate {
+            errors.push(Error::new(
+                ErrorKind::DuplicateFunction(func.name.value.clone()),
+                func.name.span,
+            ));
+        }
+
+        // exported functions share the namespace of C symbols, whatever
+        // module they are in
+        let duplicate = ast.functions[..i]
+            .iter()
+            .any(|other| func.export && other.export && other.name.value == func.name.value);
+
+        if duplicate {
+            errors.push(Error::new(
+                ErrorKind::DuplicateExport(func.name.value.clone()),
+                func.name.span,
+            ));
+        }
+    }
+
+    for (i, module) in ast.modules.iter().enumerate() {
+        for import in &module.uses {
+            if import.glob {
+                errors.extend(items.resolve_use(i, &import.path, Namespace::Module).err());
+                continue;
+            }
+
+            let namespaces = [Namespace::Value, Namespace::Type, Namespace::Module];
+            let found = namespaces
+                .iter()
+                .any(|&ns| items.resolve_use(i, &import.path, ns).is_ok());
+
+            if !found {
+                errors.extend(items.resolve_use(i, &import.path, Namespace::Value).err());
+            }
+        }
+    }
+
+    // statements only appear in the root module
+    let mut resolver = Resolver { items, module: 0 };
+    for expr in &mut ast.exprs {
+        errors.extend(resolver.expr(expr).err());
+    }
+
+    for def in &mut ast.defs {
+        resolver.module = resolver.items.functions[def.func].module;
+        errors.extend(resolver.expr(&mut def.body).err());
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The namespaces that items are looked up in. Items in different namespaces
+/// can share a name.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Namespace {
+    Value,
+    Type,
+    Module,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Item {
+    Function(usize),
+    Struct(usize),
+    Module(usize),
+}
+
+/// The items of a program, indexed by module.
+///
+/// A name used in a module refers to, in order:
+///
+/// - an item defined in the module
+/// - an item named by a `use` in the module
+/// - an item in a module imported with `use path::*`, which is ambiguous if
+///   more than one such module has an item with the name
+/// - the same name in the parent module
+/// - an item in the prelude
+///
+/// Items are private to the module that defines them (and its descendants)
+/// unless marked `pub`.
+pub struct Items<'a> {
+    pub modules: &'a [Module],
+    pub structs: &'a [StructDef],
+    pub functions: &'a [Function],
+    /// The module of declarations available everywhere, see `prelude.rp`.
+    pub prelude: Option<usize>,
+}
+
+impl<'a> Items<'a> {
+    /// Resolves a path used in a module.
+    pub fn resolve(&self, from: usize, path: &ItemPath, ns: Namespace) -> Result<Item, Error> {
+        self.resolve_path(from, path, ns, true)
+    }
+
+    /// Resolves the path of a `use`, which can only start with an item that
+    /// is defined in the module or one of its ancestors.
+    fn resolve_use(&self, from: usize, path: &ItemPath, ns: Namespace) -> Result<Item, Error> {
+        self.resolve_path(from, path, ns, false)
+    }
+
+    fn resolve_path(
+        &self,
+        from: usize,
+        path: &ItemPath,
+        ns: Namespace,
+        uses: bool,
+    ) -> Result<Item, Error> {
+        let (last, modules) = path.segments.split_last().expect("empty path");
+
+        let Some((first, rest)) = modules.split_first() else {
+            return self
+                .lookup(from, last, ns, uses)?
+                .ok_or_else(|| Error::new(ErrorKind::Unresolved(last.value.clone()), last.span));
+        };
+
+        let mut module = match self.lookup(from, first, Namespace::Module, uses)? {
+            Some(Item::Module(module)) => module,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::UnresolvedModule(first.value.clone()),
+                    first.span,
+                ))
+            }
+        };
+
+        for segment in rest {
+            module = match self.member(from, module, segment, Namespace::Module)? {
+                Item::Module(module) => module,
+                _ => unreachable!(),
+            };
+        }
+
+        self.member(from, module, last, ns)
+    }
+
+    /// Looks up a name defined in a module, from outside of it.
+    fn member(
+        &self,
+        from: usize,
+        module: usize,
+        name: &WithSpan<String>,
+        ns: Namespace,
+    ) -> Result<Item, Error> {
+        match self.defined(module, &name.value, ns) {
+            Some((item, public)) if public || self.is_within(from, module) => Ok(item),
+            Some(_) => Err(Error::new(
+                ErrorKind::Private(name.value.clone()),
+                name.span,
+            )),
+            None => Err(Error::new(
+                ErrorKind::UnresolvedIn {
+                    name: name.value.clone(),
+                    module: self.path(module),
+                },
+                name.span,
+            )),
+        }
+    }
+
+    fn lookup(
+        &self,
+        from: usize,
+        name: &WithSpan<String>,
+        ns: Namespace,
+        uses: bool,
+    ) -> Result<Option<Item>, Error> {
+        let mut scope = Some(from);
+
+        while let Some(module) = scope {
+            if let Some((item, _)) = self.defined(module, &name.value, ns) {
+                return Ok(Some(item));
+            }
+
+            if !uses {
+                scope = self.modules[module].parent;
+                continue;
+            }
+
+            let imports = &self.modules[module].uses;
+
+            for import in imports.iter().filter(|import| !import.glob) {
+                let last = import.path.segments.last().unwrap();
+                if last.value != name.value {
+                    continue;
+                }
+
+                if let Ok(item) = self.resolve_use(module, &import.path, ns) {
+                    return Ok(Some(item));
+                }
+            }
+
+            let mut found: Option<(Item, usize)> = None;
+
+            for import in imports.iter().filter(|import| import.glob) {
+                let Ok(Item::Module(target)) =
+                    self.resolve_use(module, &import.path, Namespace::Module)
+                else {
+                    continue;
+                };
+
+                let Some((item, public)) = self.defined(target, &name.value, ns) else {
+                    continue;
+                };
+
+                if !public && !self.is_within(from, target) {
+                    continue;
+                }
+
+                match found {
+                    Some((other, _)) if other == item => {}
+                    Some((_, other)) => {
+                        return Err(Error::new(
+                            ErrorKind::Ambiguous {
+                                name: name.value.clone(),
+                                first: self.qualify(other, &name.value),
+                                second: self.qualify(target, &name.value),
+                            },
+                            name.span,
+                        ))
+                    }
+                    None => found = Some((item, target)),
+                }
+            }
+
+            if let Some((item, _)) = found {
+                return Ok(Some(item));
+            }
+
+            scope = self.modules[module].parent;
+        }
+
+        match self.prelude {
+            Some(prelude) if uses => {
+                Ok(self.defined(prelude, &name.value, ns).map(|(item, _)| item))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the item with the given name defined directly in a module,
+    /// and whether it is public.
+    fn defined(&self, module: usize, name: &str, ns: Namespace) -> Option<(Item, bool)> {
+        match ns {
+            Namespace::Value => self
+                .functions
+                .iter()
+                .position(|f| f.module == module && f.name.value == name)
+                .map(|i| (Item::Function(i), self.functions[i].public)),
+            Namespace::Type => self
+                .structs
+                .iter()
+                .position(|s| s.module == module && s.name.value == name)
+                .map(|i| (Item::Struct(i), self.structs[i].public)),
+            Namespace::Module => self
+                .modules
+                .iter()
+                .position(|m| m.parent == Some(module) && m.name.value == name)
+                .map(|i| (Item::Module(i), self.modules[i].public)),
+        }
+    }
+
+    /// Whether `module` is `ancestor` or one of its descendants.
+    fn is_within(&self, module: usize, ancestor: usize) -> bool {
+        let mut scope = Some(module);
+
+        while let Some(module) = scope {
+            if module == ancestor {
+                return true;
+            }
+
+            scope = self.modules[module].parent;
+        }
+
+        false
+    }
+
+    /// The path of a module from the root, `a::b`, which is empty for the
+    /// root module.
+    pub fn path(&self, module: usize) -> String {
+        let mut names = Vec::new();
+        let mut scope = Some(module);
+
+        while let Some(module) = scope {
+            let module = &self.modules[module];
+            if module.parent.is_some() {
+                names.push(module.name.value.as_str());
+            }
+
+            scope = module.parent;
+        }
+
+        names.reverse();
+        names.join("::")
+    }
+
+    /// The full path of an item named `name` in a module.
+    pub fn qualify(&self, module: usize, name: &str) -> String {
+        match self.path(module) {
+            path if path.is_empty() => name.to_owned(),
+            path => format!("{}::{}", path, name),
+        }
+    }
+}
+
+struct Resolver<'a> {
+    items: Items<'a>,
+    /// The module of the expressions being resolved.
+    module: usize,
+}
+
+impl<'a> Resolver<'a> {
+    fn expr(&mut self, expr: &mut Expr) -> Result<(), Error> {
+        match expr.kind {
+            ExprKind::Lit(_)
+            | ExprKind::Var(_)
+            | ExprKind::Break
+            | ExprKind::Continue
+            | ExprKind::Label(_)
+            | ExprKind::Goto(_)
+            | ExprKind::SizeOf(SizeOf::Ty(_)) => {}
+            ExprKind::Binary(ref mut expr) => {
+                self.expr(&mut expr.left)?;
+                self.expr(&mut expr.right)?;
+            }
+            ExprKind::Unary(ref mut expr) => self.expr(&mut expr.expr)?,
+            ExprKind::Call(ref mut call) => self.call(call)?,
+            ExprKind::Match(ref mut expr) => {
+                self.expr(&mut expr.scrutinee)?;
+
+                for arm in &mut expr.arms {
+                    self.expr(&mut arm.body)?;
+                }
+
+                if let Some(ref mut default) = expr.default {
+                    self.expr(default)?;
+                }
+            }
+            ExprKind::If(ref mut expr) => {
+                self.expr(&mut expr.cond)?;
+                self.expr(&mut expr.then)?;
+
+                if let Some(ref mut otherwise) = expr.otherwise {
+                    self.expr(otherwise)?;
+                }
+            }
+            ExprKind::While(ref mut expr) => {
+                self.expr(&mut expr.cond)?;
+                self.expr(&mut expr.body)?;
+
+                if let Some(ref mut step) = expr.step {
+                    self.expr(step)?;
+                }
+            }
+            ExprKind::Return(ref mut value) => {
+                if let Some(ref mut value) = value {
+                    self.expr(value)?;
+                }
+            }
+            ExprKind::Let(ref mut expr) => self.expr(&mut expr.init)?,
+            ExprKind::Array(ref mut elems) | ExprKind::Tuple(ref mut elems) => {
+                for elem in elems {
+                    self.expr(elem)?;
+                }
+            }
+            ExprKind::Repeat(ref mut repeat) => self.expr(&mut repeat.value)?,
+            ExprKind::Index(ref mut index) => {
+                self.expr(&mut index.base)?;
+                self.expr(&mut index.index)?;
+            }
+            ExprKind::Slice(ref mut slice) => {
+                self.expr(&mut slice.base)?;
+
+                for bound in slice.start.iter_mut().chain(&mut slice.end) {
+                    self.expr(bound)?;
+                }
+            }
+            ExprKind::Struct(ref mut lit) => {
+                for (_, value) in &mut lit.fields {
+                    self.expr(value)?;
+                }
+            }
+            ExprKind::Field(ref mut field) => self.expr(&mut field.base)?,
+            ExprKind::SizeOf(SizeOf::Expr(ref mut expr)) => self.expr(expr)?,
+            ExprKind::Cast(ref mut cast) => self.expr(&mut cast.expr)?,
+            ExprKind::Block(ref mut block) => {
+                for stmt in &mut block.stmts {
+                    self.expr(stmt)?;
+                }
+
+                if let Some(ref mut value) = block.value {
+                    self.expr(value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn call(&mut self, call: &mut Call) -> Result<(), Error> {
+        for arg in &mut call.args {
+            self.expr(arg)?;
+        }
+
+        match self
+            .items
+            .resolve(self.module, &call.path, Namespace::Value)
+        {
+            Ok(Item::Function(func)) => call.func = Some(func),
+            Ok(_) => unreachable!(),
+            Err(err) => match call.path.segments[..] {
+                [ref name] => {
+                    call.builtin = Some(Builtin::from_name(&name.value).ok_or(err)?);
+                }
+                _ => return Err(err),
+            },
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Error {
+    fn new(kind: ErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    Unresolved(String),
+    UnresolvedModule(String),
+    UnresolvedIn {
+        name: String,
+        module: String,
+    },
+    Private(String),
+    Ambiguous {
+        name: String,
+        first: String,
+        second: String,
+    },
+    DuplicateFunction(String),
+    DuplicateExport(String),
+}
+
+impl Spanned for Error {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        use ErrorKind::*;
+
+        match self.kind {
+            Unresolved(..) => "E0201",
+            UnresolvedModule(..) => "E0202",
+            UnresolvedIn { .. } => "E0203",
+            Private(..) => "E0204",
+            Ambiguous { .. } => "E0205",
+            DuplicateFunction(..) => "E0206",
+            DuplicateExport(..) => "E0207",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        use ErrorKind::*;
+
+        match self.kind {
+            Unresolved(ref name) => format!("Cannot find '{}' in this scope", name),
+            UnresolvedModule(ref name) => format!("Cannot find module '{}'", name),
+            UnresolvedIn {
+                ref name,
+                ref module,
+            } => format!("Cannot find '{}' in module '{}'", name, module),
+            Private(ref name) => format!("'{}' is private", name),
+            Ambiguous {
+                ref name,
+                ref first,
+                ref second,
+            } => format!(
+                "'{}' is ambiguous, it could refer to '{}' or '{}'",
+                name, first, second
+            ),
+            DuplicateFunction(ref name) => {
+                format!("Function '{}' is declared more than once", name)
+            }
+            DuplicateExport(ref name) => {
+                format!("Function '{}' is exported more than once", name)
+            }
+        }
+    }
+
+    fn failure(&self) -> Failure {
+        Failure::Semantic
+    }
+}
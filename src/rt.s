@@ -0,0 +1,478 @@
+# The ripc runtime, linked into every program.
+#
+# Builtins call these helpers rather than libc directly, so that generated
+# code only depends on the signatures below.
+#
+# A `str` is a pointer to the length of the string, followed by the address
+# of its bytes. The bytes of a string that isn't a slice are followed by a
+# NUL, so the byte after the end of any string can be read.
+
+	.text
+
+# ripc_print_int(n: int)
+	.global ripc_print_int
+ripc_print_int:
+	push %rbp
+	mov %rsp, %rbp
+	mov %edi, %esi
+	lea .Lint(%rip), %rdi
+	mov $0, %eax
+	call printf
+	pop %rbp
+	ret
+
+# ripc_print_float(x: float)
+	.global ripc_print_float
+ripc_print_float:
+	push %rbp
+	mov %rsp, %rbp
+	lea .Lfloat(%rip), %rdi
+	mov $1, %eax
+	call printf
+	pop %rbp
+	ret
+
+# ripc_print_str(s: str)
+	.global ripc_print_str
+ripc_print_str:
+	push %rbp
+	mov %rsp, %rbp
+	# fwrite(s.bytes, 1, s.len, stdout), which doesn't stop at a NUL
+	mov (%rdi), %rdx
+	mov 8(%rdi), %rdi
+	mov $1, %esi
+	mov stdout@GOTPCREL(%rip), %rcx
+	mov (%rcx), %rcx
+	call fwrite
+	pop %rbp
+	ret
+
+# ripc_print_bool(b: bool)
+	.global ripc_print_bool
+ripc_print_bool:
+	push %rbp
+	mov %rsp, %rbp
+	test %dil, %dil
+	lea .Ltrue(%rip), %rsi
+	lea .Lfalse(%rip), %rax
+	cmovz %rax, %rsi
+	lea .Lstr(%rip), %rdi
+	mov $0, %eax
+	call printf
+	pop %rbp
+	ret
+
+# ripc_print_ptr(p: *T)
+	.global ripc_print_ptr
+ripc_print_ptr:
+	push %rbp
+	mov %rsp, %rbp
+	mov %rdi, %rsi
+	lea .Lptr(%rip), %rdi
+	mov $0, %eax
+	call printf
+	pop %rbp
+	ret
+
+# ripc_print_char(c: int)
+	.global ripc_print_char
+ripc_print_char:
+	push %rbp
+	mov %rsp, %rbp
+	call putchar
+	pop %rbp
+	ret
+
+# min(a: int, b: int) -> int
+	.global min
+min:
+	mov %edi, %eax
+	cmp %esi, %edi
+	cmovg %esi, %eax
+	ret
+
+# max(a: int, b: int) -> int
+	.global max
+max:
+	mov %edi, %eax
+	cmp %esi, %edi
+	cmovl %esi, %eax
+	ret
+
+# ripc_read_int() -> int: an integer read from standard input, or 0 if
+# there isn't one
+	.global ripc_read_int
+ripc_read_int:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	movl $0, -4(%rbp)
+	lea -4(%rbp), %rsi
+	lea .Lint(%rip), %rdi
+	mov $0, %eax
+	call scanf
+	mov -4(%rbp), %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+
+# ripc_concat(a: str, b: str) -> str: a newly allocated copy of `a`
+# followed by `b`, with its bytes right after its length and their address
+	.global ripc_concat
+ripc_concat:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rdi, %rbx
+	mov %rsi, %r12
+	mov (%rbx), %rdi
+	add (%r12), %rdi
+	add $17, %rdi
+	call malloc
+	mov %rax, %r13
+	mov (%rbx), %rcx
+	add (%r12), %rcx
+	mov %rcx, (%r13)
+	lea 16(%r13), %rdi
+	mov %rdi, 8(%r13)
+	mov 8(%rbx), %rsi
+	mov (%rbx), %rdx
+	call memcpy
+	mov (%rbx), %rdi
+	add %rax, %rdi
+	mov 8(%r12), %rsi
+	mov (%r12), %rdx
+	call memcpy
+	mov (%r13), %rax
+	movb $0, 16(%r13, %rax)
+	mov %r13, %rax
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_cmp(a: str, b: str) -> int: less than, equal to or greater than
+# zero as `a` sorts before, the same as or after `b`, byte by byte
+	.global ripc_str_cmp
+ripc_str_cmp:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rdi, %rbx
+	mov %rsi, %r12
+	mov (%rbx), %rdx
+	cmp (%r12), %rdx
+	cmova (%r12), %rdx
+	mov 8(%rbx), %rdi
+	mov 8(%r12), %rsi
+	call memcmp
+	test %eax, %eax
+	jnz 1f
+	# one is a prefix of the other, which sorts first
+	mov (%rbx), %rcx
+	cmp (%r12), %rcx
+	seta %al
+	setb %cl
+	movzbl %al, %eax
+	movzbl %cl, %ecx
+	sub %ecx, %eax
+1:
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_slice(s: str, start: int, end: int) -> str: the bytes of `s`
+# from `start` up to `end`, which aren't copied
+	.global ripc_str_slice
+ripc_str_slice:
+	push %rbx
+	push %r12
+	push %r13
+	mov 8(%rdi), %rbx
+	add %rsi, %rbx
+	mov %rdx, %r12
+	sub %rsi, %r12
+	mov $16, %edi
+	call malloc
+	mov %r12, (%rax)
+	mov %rbx, 8(%rax)
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_from_c(s: *u8) -> str: the bytes of a NUL-terminated string up to
+# the NUL, which aren't copied, or a null `str` for a null pointer
+	.global ripc_str_from_c
+ripc_str_from_c:
+	test %rdi, %rdi
+	jnz 1f
+	xor %eax, %eax
+	ret
+1:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rdi, %rbx
+	call strlen
+	mov %rax, %r12
+	mov $16, %edi
+	call malloc
+	mov %r12, (%rax)
+	mov %rbx, 8(%rax)
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_to_c(s: str) -> *u8: the bytes of `s` followed by a NUL, copied
+# only if the byte after them isn't one
+	.global ripc_str_to_c
+ripc_str_to_c:
+	mov 8(%rdi), %rax
+	mov (%rdi), %rcx
+	cmpb $0, (%rax, %rcx)
+	jne 1f
+	ret
+1:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rax, %rbx
+	mov %rcx, %r12
+	lea 1(%r12), %rdi
+	call malloc
+	mov %rax, %r13
+	mov %rax, %rdi
+	mov %rbx, %rsi
+	mov %r12, %rdx
+	call memcpy
+	movb $0, (%r13, %r12)
+	mov %r13, %rax
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_abort(msg: *u8): flushes standard output, then prints `msg` to
+# standard error and aborts
+	.global ripc_abort
+ripc_abort:
+	push %rbp
+	mov %rsp, %rbp
+	push %rdi
+	sub $8, %rsp
+	mov $0, %edi
+	call fflush
+	mov -8(%rbp), %rdx
+	lea .Labort(%rip), %rsi
+	mov $2, %edi
+	mov $0, %eax
+	call dprintf
+	call abort
+
+# ripc_panic(msg: str, location: *u8): flushes standard output, then prints
+# `msg` and the location of the panic to standard error and exits with
+# status 101
+	.global ripc_panic
+ripc_panic:
+	push %rbp
+	mov %rsp, %rbp
+	push %rdi
+	push %rsi
+	mov $0, %edi
+	call fflush
+	mov -8(%rbp), %rax
+	mov (%rax), %rdx
+	mov 8(%rax), %rcx
+	mov -16(%rbp), %r8
+	lea .Lpanic(%rip), %rsi
+	mov $2, %edi
+	mov $0, %eax
+	call dprintf
+	mov $101, %edi
+	call exit
+
+# ripc_bounds_fail(index: int, len: int, location: *u8): flushes standard
+# output, then prints the index that was out of bounds and its location to
+# standard error and exits with status 101
+	.global ripc_bounds_fail
+ripc_bounds_fail:
+	lea .Lbounds(%rip), %rcx
+	jmp ripc_index_fail
+
+# ripc_str_bounds_fail(index: int, len: int, location: *u8): the same, for an
+# index into a string
+	.global ripc_str_bounds_fail
+ripc_str_bounds_fail:
+	lea .Lstr_bounds(%rip), %rcx
+	jmp ripc_index_fail
+
+# ripc_slice_fail(start: int, end: int, location: *u8): the same, for a slice
+# that ends before it starts
+	.global ripc_slice_fail
+ripc_slice_fail:
+	lea .Lslice(%rip), %rcx
+
+# ripc_index_fail(a: int, b: int, location: *u8, format: *u8): flushes
+# standard output, then prints `format` with `a`, `b` and `location` to
+# standard error and exits with status 101
+ripc_index_fail:
+	push %rbp
+	mov %rsp, %rbp
+	push %rdi
+	push %rsi
+	push %rdx
+	push %rcx
+	mov $0, %edi
+	call fflush
+	mov -8(%rbp), %rdx
+	mov -16(%rbp), %rcx
+	mov -24(%rbp), %r8
+	mov -32(%rbp), %rsi
+	mov $2, %edi
+	mov $0, %eax
+	call dprintf
+	mov $101, %edi
+	call exit
+
+# ripc_write_counters(counters: *Counter, fd: int): writes the count and name
+# of every counter to `fd`, a line each, where a counter is an 8-byte count
+# followed by the offset of its name from the offset itself, and the table
+# ends with a zero offset
+ripc_write_counters:
+	push %rbp
+	mov %rsp, %rbp
+	push %rbx
+	push %r12
+	mov %rdi, %rbx
+	mov %esi, %r12d
+1:
+	mov 8(%rbx), %rcx
+	test %rcx, %rcx
+	je 2f
+	lea 8(%rbx,%rcx), %rcx
+	mov (%rbx), %rdx
+	lea .Lcounter(%rip), %rsi
+	mov %r12d, %edi
+	mov $0, %eax
+	call dprintf
+	add $16, %rbx
+	jmp 1b
+2:
+	pop %r12
+	pop %rbx
+	pop %rbp
+	ret
+
+# ripc_profile_report(counters: *Counter): flushes standard output, then
+# writes the counters to standard error
+	.global ripc_profile_report
+ripc_profile_report:
+	push %rbp
+	mov %rsp, %rbp
+	push %rbx
+	sub $8, %rsp
+	mov %rdi, %rbx
+	mov $0, %edi
+	call fflush
+	lea .Lprofile(%rip), %rsi
+	mov $2, %edi
+	mov $0, %eax
+	call dprintf
+	mov %rbx, %rdi
+	mov $2, %esi
+	call ripc_write_counters
+	mov -8(%rbp), %rbx
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+
+# ripc_coverage_dump(counters: *Counter): writes the counters to `out.cov`,
+# if it can be created
+	.global ripc_coverage_dump
+ripc_coverage_dump:
+	push %rbp
+	mov %rsp, %rbp
+	push %rbx
+	push %r12
+	mov %rdi, %rbx
+	# open("out.cov", O_WRONLY | O_CREAT | O_TRUNC, 0644)
+	lea .Lcoverage(%rip), %rdi
+	mov $0x241, %esi
+	mov $0x1a4, %edx
+	mov $0, %eax
+	call open
+	test %eax, %eax
+	js 1f
+	mov %eax, %r12d
+	mov %rbx, %rdi
+	mov %r12d, %esi
+	call ripc_write_counters
+	mov %r12d, %edi
+	call close
+1:
+	pop %r12
+	pop %rbx
+	pop %rbp
+	ret
+
+# ripc_stack_guard_init(): fills ripc_stack_guard with random bytes
+	.global ripc_stack_guard_init
+ripc_stack_guard_init:
+	# getrandom(&ripc_stack_guard, 8, 0)
+	lea ripc_stack_guard(%rip), %rdi
+	mov $8, %esi
+	mov $0, %edx
+	mov $318, %eax
+	syscall
+	ret
+
+# ripc_stack_chk_fail(): aborts after the canary of a frame was overwritten
+	.global ripc_stack_chk_fail
+ripc_stack_chk_fail:
+	lea .Lsmashed(%rip), %rdi
+	jmp ripc_abort
+
+# ripc_stack_guard: the canary stored in frames by `--stack-protector`
+	.bss
+	.p2align 3
+	.global ripc_stack_guard
+ripc_stack_guard:
+	.zero 8
+
+	.section .rodata
+.Lint:
+	.string "%d"
+.Lfloat:
+	.string "%f"
+.Lstr:
+	.string "%s"
+.Lptr:
+	.string "%p"
+.Ltrue:
+	.string "true"
+.Lfalse:
+	.string "false"
+.Labort:
+	.string "%s\n"
+.Lpanic:
+	.string "[panic]: %.*s\n --> %s\n"
+.Lbounds:
+	.string "[panic]: index %d is out of bounds for an array of length %d\n --> %s\n"
+.Lstr_bounds:
+	.string "[panic]: index %d is out of bounds for a string of length %d\n --> %s\n"
+.Lslice:
+	.string "[panic]: slice starts at %d but ends at %d\n --> %s\n"
+.Lprofile:
+	.string "[profile]\n"
+.Lcounter:
+	.string "%lu %s\n"
+.Lcoverage:
+	.string "out.cov"
+.Lsmashed:
+	.string "[panic]: stack smashing detected"
+
+# the stack is not executable
+	.section .note.GNU-stack,"",@progbits
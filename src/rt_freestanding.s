@@ -0,0 +1,643 @@
+# The ripc runtime for programs built with `--freestanding`, which are not
+# linked against libc.
+#
+# Provides the same helpers as rt.s using raw system calls, along with
+# `exit` for `_start`. Helpers that need libc, such as float formatting, are
+# not available, and sema rejects programs that would call them. Strings are
+# allocated from the end of the data segment and never freed.
+
+	.text
+
+# exit(status: int)
+	.global exit
+exit:
+	mov $231, %eax
+	syscall
+
+# ripc_write(fd: int, buf: *u8, len: int)
+ripc_write:
+	mov $1, %eax
+	syscall
+	ret
+
+# ripc_print_int(n: int)
+	.global ripc_print_int
+ripc_print_int:
+	movslq %edi, %rdi
+	mov $1, %esi
+	jmp ripc_write_int
+
+# ripc_write_int(n, fd: int): writes the 64-bit integer `n` in decimal to `fd`
+ripc_write_int:
+	push %rbp
+	mov %rsp, %rbp
+	mov %esi, %r9d
+	sub $32, %rsp
+	# digits are written backwards, ending at %rbp
+	mov %rdi, %rax
+	mov %rax, %r8
+	lea -1(%rbp), %rsi
+	test %rax, %rax
+	jns 1f
+	neg %rax
+1:
+	mov $10, %rcx
+2:
+	xor %edx, %edx
+	div %rcx
+	add $'0', %dl
+	mov %dl, (%rsi)
+	dec %rsi
+	test %rax, %rax
+	jnz 2b
+	test %r8, %r8
+	jns 3f
+	movb $'-', (%rsi)
+	dec %rsi
+3:
+	inc %rsi
+	mov %rbp, %rdx
+	sub %rsi, %rdx
+	mov %r9d, %edi
+	call ripc_write
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+
+# ripc_print_str(s: str)
+	.global ripc_print_str
+ripc_print_str:
+	mov (%rdi), %rdx
+	mov 8(%rdi), %rsi
+	mov $1, %edi
+	jmp ripc_write
+
+# ripc_print_bool(b: bool)
+	.global ripc_print_bool
+ripc_print_bool:
+	test %dil, %dil
+	lea .Ltrue(%rip), %rdi
+	lea .Lfalse(%rip), %rax
+	cmovz %rax, %rdi
+	mov $1, %esi
+	jmp ripc_write_str
+
+# ripc_print_char(c: int)
+	.global ripc_print_char
+ripc_print_char:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %dil, -1(%rbp)
+	lea -1(%rbp), %rsi
+	mov $1, %edx
+	mov $1, %edi
+	call ripc_write
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+
+# ripc_read_byte() -> int: the next byte of standard input, or -1 at its end
+ripc_read_byte:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# read(0, buf, 1)
+	mov $0, %edi
+	lea -1(%rbp), %rsi
+	mov $1, %edx
+	mov $0, %eax
+	syscall
+	cmp $1, %rax
+	jne 1f
+	movzbl -1(%rbp), %eax
+	jmp 2f
+1:
+	mov $-1, %eax
+2:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+
+# ripc_read_int() -> int: an integer read from standard input, or 0 if
+# there isn't one. Like scanf("%d"), leading whitespace is skipped, but the
+# byte following the integer is read as well, as nothing is buffered.
+	.global ripc_read_int
+ripc_read_int:
+	push %rbx
+	push %r12
+	sub $8, %rsp
+	# the value so far and whether it is negative
+	mov $0, %ebx
+	mov $0, %r12d
+1:
+	call ripc_read_byte
+	cmp $' ', %eax
+	je 1b
+	# '\t' through '\r'
+	lea -9(%rax), %ecx
+	cmp $4, %ecx
+	jbe 1b
+	cmp $'-', %eax
+	jne 2f
+	mov $1, %r12d
+	call ripc_read_byte
+	jmp 3f
+2:
+	cmp $'+', %eax
+	jne 3f
+	call ripc_read_byte
+3:
+	lea -'0'(%rax), %ecx
+	cmp $9, %ecx
+	ja 4f
+	imul $10, %ebx
+	add %ecx, %ebx
+	call ripc_read_byte
+	jmp 3b
+4:
+	mov %ebx, %eax
+	test %r12d, %r12d
+	jz 5f
+	neg %eax
+5:
+	add $8, %rsp
+	pop %r12
+	pop %rbx
+	ret
+
+# min(a: int, b: int) -> int
+	.global min
+min:
+	mov %edi, %eax
+	cmp %esi, %edi
+	cmovg %esi, %eax
+	ret
+
+# max(a: int, b: int) -> int
+	.global max
+max:
+	mov %edi, %eax
+	cmp %esi, %edi
+	cmovl %esi, %eax
+	ret
+
+# ripc_strlen(s: *u8) -> int
+ripc_strlen:
+	mov %rdi, %rax
+1:
+	cmpb $0, (%rax)
+	je 2f
+	inc %rax
+	jmp 1b
+2:
+	sub %rdi, %rax
+	ret
+
+# ripc_alloc(size: int) -> *u8: `size` bytes from the end of the data
+# segment, which are never freed
+ripc_alloc:
+	mov .Lheap(%rip), %rax
+	test %rax, %rax
+	jnz 1f
+	# brk(0), the current end of the data segment
+	push %rdi
+	mov $0, %edi
+	mov $12, %eax
+	syscall
+	pop %rdi
+1:
+	# keep allocations 16-byte aligned
+	add $15, %rdi
+	and $-16, %rdi
+	add %rax, %rdi
+	push %rax
+	mov $12, %eax
+	syscall
+	cmp %rdi, %rax
+	jb 2f
+	mov %rdi, .Lheap(%rip)
+	pop %rax
+	ret
+2:
+	lea .Loom(%rip), %rdi
+	jmp ripc_abort
+
+# ripc_copy(dst: *u8, src: *u8, len: int) -> *u8: copies `len` bytes from
+# `src` to `dst`, returning `dst`
+ripc_copy:
+	mov %rdi, %rax
+	mov %rdx, %rcx
+	rep movsb
+	ret
+
+# ripc_concat(a: str, b: str) -> str: a newly allocated copy of `a`
+# followed by `b`, with its bytes right after its length and their address
+	.global ripc_concat
+ripc_concat:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rdi, %rbx
+	mov %rsi, %r12
+	mov (%rbx), %rdi
+	add (%r12), %rdi
+	add $17, %rdi
+	call ripc_alloc
+	mov %rax, %r13
+	mov (%rbx), %rcx
+	add (%r12), %rcx
+	mov %rcx, (%r13)
+	lea 16(%r13), %rdi
+	mov %rdi, 8(%r13)
+	mov 8(%rbx), %rsi
+	mov (%rbx), %rdx
+	call ripc_copy
+	mov (%rbx), %rdi
+	add %rax, %rdi
+	mov 8(%r12), %rsi
+	mov (%r12), %rdx
+	call ripc_copy
+	mov (%r13), %rax
+	movb $0, 16(%r13, %rax)
+	mov %r13, %rax
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_cmp(a: str, b: str) -> int: less than, equal to or greater than
+# zero as `a` sorts before, the same as or after `b`, byte by byte
+	.global ripc_str_cmp
+ripc_str_cmp:
+	mov (%rdi), %rcx
+	cmp (%rsi), %rcx
+	cmova (%rsi), %rcx
+	mov 8(%rdi), %r8
+	mov 8(%rsi), %r9
+1:
+	test %rcx, %rcx
+	jz 2f
+	movzbl (%r8), %eax
+	movzbl (%r9), %edx
+	sub %edx, %eax
+	jnz 3f
+	inc %r8
+	inc %r9
+	dec %rcx
+	jmp 1b
+2:
+	# one is a prefix of the other, which sorts first
+	mov (%rdi), %rcx
+	cmp (%rsi), %rcx
+	seta %al
+	setb %cl
+	movzbl %al, %eax
+	movzbl %cl, %ecx
+	sub %ecx, %eax
+3:
+	ret
+
+# ripc_str_slice(s: str, start: int, end: int) -> str: the bytes of `s`
+# from `start` up to `end`, which aren't copied
+	.global ripc_str_slice
+ripc_str_slice:
+	push %rbx
+	push %r12
+	push %r13
+	mov 8(%rdi), %rbx
+	add %rsi, %rbx
+	mov %rdx, %r12
+	sub %rsi, %r12
+	mov $16, %edi
+	call ripc_alloc
+	mov %r12, (%rax)
+	mov %rbx, 8(%rax)
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_from_c(s: *u8) -> str: the bytes of a NUL-terminated string up to
+# the NUL, which aren't copied, or a null `str` for a null pointer
+	.global ripc_str_from_c
+ripc_str_from_c:
+	test %rdi, %rdi
+	jnz 1f
+	xor %eax, %eax
+	ret
+1:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rdi, %rbx
+	call ripc_strlen
+	mov %rax, %r12
+	mov $16, %edi
+	call ripc_alloc
+	mov %r12, (%rax)
+	mov %rbx, 8(%rax)
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_str_to_c(s: str) -> *u8: the bytes of `s` followed by a NUL, copied
+# only if the byte after them isn't one
+	.global ripc_str_to_c
+ripc_str_to_c:
+	mov 8(%rdi), %rax
+	mov (%rdi), %rcx
+	cmpb $0, (%rax, %rcx)
+	jne 1f
+	ret
+1:
+	push %rbx
+	push %r12
+	push %r13
+	mov %rax, %rbx
+	mov %rcx, %r12
+	lea 1(%r12), %rdi
+	call ripc_alloc
+	mov %rax, %r13
+	mov %rax, %rdi
+	mov %rbx, %rsi
+	mov %r12, %rdx
+	call ripc_copy
+	movb $0, (%r13, %r12)
+	mov %r13, %rax
+	pop %r13
+	pop %r12
+	pop %rbx
+	ret
+
+# ripc_write_str(s: *u8, fd: int): writes `s` to `fd`
+ripc_write_str:
+	push %rbp
+	mov %rsp, %rbp
+	push %rsi
+	push %rdi
+	call ripc_strlen
+	movslq %eax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_write
+	pop %rbp
+	ret
+
+# ripc_eprint(s: *u8): writes `s` to standard error
+ripc_eprint:
+	push %rbp
+	mov %rsp, %rbp
+	mov %rdi, %rsi
+	call ripc_strlen
+	movslq %eax, %rdx
+	mov $2, %edi
+	call ripc_write
+	pop %rbp
+	ret
+
+# ripc_abort(msg: *u8): prints `msg` to standard error and aborts
+	.global ripc_abort
+ripc_abort:
+	push %rbp
+	mov %rsp, %rbp
+	call ripc_eprint
+	lea .Lnewline(%rip), %rdi
+	call ripc_eprint
+	# kill(getpid(), SIGABRT)
+	mov $39, %eax
+	syscall
+	mov %eax, %edi
+	mov $6, %esi
+	mov $62, %eax
+	syscall
+	mov $134, %edi
+	call exit
+
+# ripc_panic(msg: str, location: *u8): prints `msg` and the location of the
+# panic to standard error and exits with status 101
+	.global ripc_panic
+ripc_panic:
+	push %rbp
+	mov %rsp, %rbp
+	push %rdi
+	push %rsi
+	lea .Lpanic(%rip), %rdi
+	call ripc_eprint
+	mov -8(%rbp), %rax
+	mov (%rax), %rdx
+	mov 8(%rax), %rsi
+	mov $2, %edi
+	call ripc_write
+	lea .Larrow(%rip), %rdi
+	call ripc_eprint
+	mov -16(%rbp), %rdi
+	call ripc_eprint
+	lea .Lnewline(%rip), %rdi
+	call ripc_eprint
+	mov $101, %edi
+	call exit
+
+# ripc_bounds_fail(index: int, len: int, location: *u8): prints the index
+# that was out of bounds and its location to standard error and exits with
+# status 101
+	.global ripc_bounds_fail
+ripc_bounds_fail:
+	lea .Lbounds_len(%rip), %rcx
+	lea .Lbounds(%rip), %r8
+	jmp ripc_index_fail
+
+# ripc_str_bounds_fail(index: int, len: int, location: *u8): the same, for an
+# index into a string
+	.global ripc_str_bounds_fail
+ripc_str_bounds_fail:
+	lea .Lstr_bounds_len(%rip), %rcx
+	lea .Lbounds(%rip), %r8
+	jmp ripc_index_fail
+
+# ripc_slice_fail(start: int, end: int, location: *u8): the same, for a slice
+# that ends before it starts
+	.global ripc_slice_fail
+ripc_slice_fail:
+	lea .Lslice_end(%rip), %rcx
+	lea .Lslice(%rip), %r8
+
+# ripc_index_fail(a: int, b: int, location: *u8, middle: *u8, prefix: *u8):
+# prints `prefix`, `a`, `middle`, `b` and `location` to standard error and
+# exits with status 101
+ripc_index_fail:
+	push %rbp
+	mov %rsp, %rbp
+	push %rdi
+	push %rsi
+	push %rdx
+	push %rcx
+	push %r8
+	sub $8, %rsp
+	mov -40(%rbp), %rdi
+	call ripc_eprint
+	movslq -8(%rbp), %rdi
+	mov $2, %esi
+	call ripc_write_int
+	mov -32(%rbp), %rdi
+	call ripc_eprint
+	movslq -16(%rbp), %rdi
+	mov $2, %esi
+	call ripc_write_int
+	lea .Larrow(%rip), %rdi
+	call ripc_eprint
+	mov -24(%rbp), %rdi
+	call ripc_eprint
+	lea .Lnewline(%rip), %rdi
+	call ripc_eprint
+	mov $101, %edi
+	call exit
+
+# ripc_write_counters(counters: *Counter, fd: int): writes the count and name
+# of every counter to `fd`, a line each, where a counter is an 8-byte count
+# followed by the offset of its name from the offset itself, and the table
+# ends with a zero offset
+ripc_write_counters:
+	push %rbp
+	mov %rsp, %rbp
+	push %rbx
+	push %r12
+	mov %rdi, %rbx
+	mov %esi, %r12d
+1:
+	cmpq $0, 8(%rbx)
+	je 2f
+	mov (%rbx), %rdi
+	mov %r12d, %esi
+	call ripc_write_int
+	lea .Lspace(%rip), %rdi
+	mov %r12d, %esi
+	call ripc_write_str
+	mov 8(%rbx), %rdi
+	lea 8(%rbx,%rdi), %rdi
+	mov %r12d, %esi
+	call ripc_write_str
+	lea .Lnewline(%rip), %rdi
+	mov %r12d, %esi
+	call ripc_write_str
+	add $16, %rbx
+	jmp 1b
+2:
+	pop %r12
+	pop %rbx
+	pop %rbp
+	ret
+
+# ripc_profile_report(counters: *Counter): writes the counters to standard
+# error
+	.global ripc_profile_report
+ripc_profile_report:
+	push %rbp
+	mov %rsp, %rbp
+	push %rbx
+	sub $8, %rsp
+	mov %rdi, %rbx
+	lea .Lprofile(%rip), %rdi
+	call ripc_eprint
+	mov %rbx, %rdi
+	mov $2, %esi
+	call ripc_write_counters
+	mov -8(%rbp), %rbx
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+
+# ripc_coverage_dump(counters: *Counter): writes the counters to `out.cov`,
+# if it can be created
+	.global ripc_coverage_dump
+ripc_coverage_dump:
+	push %rbp
+	mov %rsp, %rbp
+	push %rbx
+	push %r12
+	mov %rdi, %rbx
+	# open("out.cov", O_WRONLY | O_CREAT | O_TRUNC, 0644)
+	lea .Lcoverage(%rip), %rdi
+	mov $0x241, %esi
+	mov $0x1a4, %edx
+	mov $2, %eax
+	syscall
+	test %eax, %eax
+	js 1f
+	mov %eax, %r12d
+	mov %rbx, %rdi
+	mov %r12d, %esi
+	call ripc_write_counters
+	# close(fd)
+	mov %r12d, %edi
+	mov $3, %eax
+	syscall
+1:
+	pop %r12
+	pop %rbx
+	pop %rbp
+	ret
+
+# ripc_stack_guard_init(): fills ripc_stack_guard with random bytes
+	.global ripc_stack_guard_init
+ripc_stack_guard_init:
+	# getrandom(&ripc_stack_guard, 8, 0)
+	lea ripc_stack_guard(%rip), %rdi
+	mov $8, %esi
+	mov $0, %edx
+	mov $318, %eax
+	syscall
+	ret
+
+# ripc_stack_chk_fail(): aborts after the canary of a frame was overwritten
+	.global ripc_stack_chk_fail
+ripc_stack_chk_fail:
+	lea .Lsmashed(%rip), %rdi
+	jmp ripc_abort
+
+# ripc_stack_guard: the canary stored in frames by `--stack-protector`
+	.bss
+	.p2align 3
+	.global ripc_stack_guard
+ripc_stack_guard:
+	.zero 8
+
+# the end of the memory handed out by ripc_alloc, once it has been called
+.Lheap:
+	.zero 8
+
+	.section .rodata
+.Ltrue:
+	.string "true"
+.Lfalse:
+	.string "false"
+.Lnewline:
+	.string "\n"
+.Lpanic:
+	.string "[panic]: "
+.Larrow:
+	.string "\n --> "
+.Lbounds:
+	.string "[panic]: index "
+.Lbounds_len:
+	.string " is out of bounds for an array of length "
+.Lstr_bounds_len:
+	.string " is out of bounds for a string of length "
+.Lslice:
+	.string "[panic]: slice starts at "
+.Lslice_end:
+	.string " but ends at "
+.Lprofile:
+	.string "[profile]\n"
+.Lspace:
+	.string " "
+.Lcoverage:
+	.string "out.cov"
+.Loom:
+	.string "[panic]: out of memory"
+.Lsmashed:
+	.string "[panic]: stack smashing detected"
+
+# the stack is not executable
+	.section .note.GNU-stack,"",@progbits
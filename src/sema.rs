@@ -0,0 +1,1274 @@
+use crate::format::{self, Piece, Spec};
+use crate::parse::{
+    Ast, BinaryExpr, BinaryOp, Block, Builtin, Call, Cast, Expr, ExprKind, FieldExpr, FnDef,
+    Function, If, Index, Let, Lit, Match, SizeOf, Slice, StructLit, UnaryExpr, UnaryOp, Var,
+};
+use crate::source::SourceMap;
+use crate::ty::{align_to, Struct, Ty};
+use crate::{Failure, Report, Span, Spanned, WithSpan};
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// The largest stack frame in bytes, far enough below `i32::MAX` that every
+/// variable, and the values pushed while evaluating an expression, can be
+/// addressed by a 32-bit displacement from %rbp.
+const MAX_FRAME: usize = 1 << 30;
+
+/// Checks that every call has arguments matching the function it was
+/// resolved to, and that operands have the types their operators expect,
+/// filling in the type of every expression and variable.
+///
+/// Checking continues after an error in a declaration or statement, so every
+/// error is returned, except those caused by an earlier one.
+///
+/// A `freestanding` program is linked against the runtime without libc,
+/// which can't print floats or pointers or format strings.
+pub fn check(ast: &mut Ast, freestanding: bool) -> Result<(), Vec<Error>> {
+    let mut checker = Checker {
+        functions: &[],
+        vars: &mut ast.vars,
+        ret: Ty::Void,
+        freestanding,
+    };
+
+    let mut errors = Vec::new();
+
+    for func in &mut ast.functions {
+        let result = checker
+            .signature(func)
+            .and_then(|()| checker.defaults(func));
+
+        errors.extend(result.err());
+    }
+
+    checker.functions = &ast.functions;
+
+    for expr in &mut ast.exprs {
+        // a variable whose initializer failed to check is never inferred,
+        // and errors involving its type would only repeat that failure
+        match checker.expr(expr) {
+            Err(err) if !err.kind.is_cascade() => errors.push(err),
+            _ => {}
+        }
+    }
+
+    for def in &mut ast.defs {
+        match checker.def(def) {
+            Err(err) if !err.kind.is_cascade() => errors.push(err),
+            _ => {}
+        }
+    }
+
+    // the type of every variable is known once the program checks
+    if errors.is_empty() {
+        errors.extend(frames(ast));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Lays out the frame of the program and of each function the way codegen
+/// does, returning an error for each that is too large.
+fn frames(ast: &Ast) -> Vec<Error> {
+    let locals = ast
+        .defs
+        .iter()
+        .flat_map(|def| def.vars.clone())
+        .collect::<HashSet<_>>();
+    let main = (0..ast.vars.len())
+        .filter(|i| !locals.contains(i))
+        .collect::<Vec<_>>();
+
+    std::iter::once(main)
+        .chain(ast.defs.iter().map(|def| def.vars.clone().collect()))
+        .filter_map(|frame| {
+            // leaving room for the canary of `--stack-protector`
+            let mut size = 8_usize;
+
+            frame.into_iter().find_map(|i| {
+                let var = &ast.vars[i];
+                size = align_to(size.saturating_add(var.ty.size()), var.ty.align());
+                (size > MAX_FRAME).then(|| Error::new(ErrorKind::FrameTooLarge, var.span))
+            })
+        })
+        .collect()
+}
+
+struct Checker<'a> {
+    functions: &'a [Function],
+    vars: &'a mut [Var],
+    /// The return type of the function being checked.
+    ret: Ty,
+    /// Whether the program is built with `--freestanding`.
+    freestanding: bool,
+}
+
+impl<'a> Checker<'a> {
+    fn expr(&mut self, expr: &mut Expr) -> Result<Ty, Error> {
+        let ty = match expr.kind {
+            ExprKind::Lit(ref lit) => match lit.value {
+                Lit::Num(_) => Ty::Int,
+                Lit::String(_) => Ty::Str,
+                Lit::Float(_) => Ty::Float,
+                Lit::Bool(_) => Ty::Bool,
+            },
+            ExprKind::Var(i) => self.vars[i].ty.clone(),
+            ExprKind::Binary(ref mut expr) => self.binary_op(expr)?,
+            ExprKind::Unary(ref mut expr) => self.unary_op(expr)?,
+            ExprKind::Call(ref mut call) => self.call(call, expr.span)?,
+            ExprKind::Match(ref mut expr) => self.match_expr(expr)?,
+            ExprKind::If(ref mut expr) => self.if_expr(expr)?,
+            ExprKind::While(ref mut expr) => {
+                self.expect(&mut expr.cond, &Ty::Bool)?;
+                self.expr(&mut expr.body)?;
+
+                if let Some(ref mut step) = expr.step {
+                    self.expr(step)?;
+                }
+
+                Ty::Void
+            }
+            ExprKind::Break | ExprKind::Continue | ExprKind::Label(_) | ExprKind::Goto(_) => {
+                Ty::Void
+            }
+            ExprKind::Return(ref mut value) => {
+                let ret = self.ret.clone();
+
+                match value {
+                    Some(value) => {
+                        let found = self.value(value)?;
+
+                        if found != ret {
+                            return Err(Error::new(
+                                ErrorKind::MismatchedTypes {
+                                    expected: ret,
+                                    found,
+                                },
+                                value.span,
+                            ));
+                        }
+                    }
+                    None if ret != Ty::Void => {
+                        return Err(Error::new(
+                            ErrorKind::MismatchedTypes {
+                                expected: ret,
+                                found: Ty::Void,
+                            },
+                            expr.span,
+                        ))
+                    }
+                    None => {}
+                }
+
+                Ty::Void
+            }
+            ExprKind::Let(ref mut expr) => self.let_expr(expr)?,
+            ExprKind::Array(_) | ExprKind::Repeat(_) | ExprKind::Struct(_) | ExprKind::Tuple(_) => {
+                return Err(Error::new(ErrorKind::AggregateOutsideLet, expr.span))
+            }
+            ExprKind::Index(ref mut expr) => self.index(expr)?,
+            ExprKind::Slice(ref mut slice) => self.slice(slice)?,
+            ExprKind::Field(ref mut expr) => self.field(expr)?,
+            ExprKind::Cast(ref mut cast) => self.cast(cast, expr.span)?,
+            ExprKind::Block(ref mut block) => self.block(block, Self::expr)?,
+            ExprKind::SizeOf(SizeOf::Ty(_)) => Ty::Int,
+            ExprKind::SizeOf(SizeOf::Expr(ref mut expr)) => {
+                if self.expr(expr)? == Ty::Void {
+                    return Err(Error::new(ErrorKind::NoValue, expr.span));
+                }
+
+                Ty::Int
+            }
+        };
+
+        expr.ty = ty.clone();
+        Ok(ty)
+    }
+
+    /// Checks the body of a function, whose value is returned unless the
+    /// function doesn't return one.
+    fn def(&mut self, def: &mut FnDef) -> Result<(), Error> {
+        let func = &self.functions[def.func];
+        let ret = func.ret.as_ref().map_or(Ty::Void, |ret| ret.value.clone());
+
+        self.ret = ret.clone();
+        let found = self.value(&mut def.body)?;
+
+        if ret == Ty::Void || found == ret || diverges(&def.body) {
+            return Ok(());
+        }
+
+        match def.body.kind {
+            ExprKind::Block(Block {
+                value: Some(ref value),
+                ..
+            }) if found != Ty::Void => Err(Error::new(
+                ErrorKind::MismatchedTypes {
+                    expected: ret,
+                    found,
+                },
+                value.span,
+            )),
+            _ => Err(Error::new(ErrorKind::MissingReturn(ret), func.name.span)),
+        }
+    }
+
+    /// Arrays and structs can't be passed to or returned from functions by
+    /// value. Functions of the program can return tuples of up to two
+    /// scalars, in %rax and %rdx, but C functions can't.
+    fn signature(&mut self, func: &Function) -> Result<(), Error> {
+        let by_value = |ty: &WithSpan<Ty>| match ty.value {
+            Ty::Array(..) | Ty::Struct(_) | Ty::Tuple(_) => Err(Error::new(
+                ErrorKind::AggregateByValue(ty.value.clone()),
+                ty.span,
+            )),
+            _ => Ok(()),
+        };
+
+        for param in &func.params {
+            by_value(&param.ty)?;
+        }
+
+        match func.ret {
+            Some(ref ret) => match ret.value {
+                Ty::Tuple(ref tuple) if !func.is_c() => {
+                    let scalars = tuple.fields.iter().all(|field| field.ty.is_scalar());
+
+                    if tuple.fields.len() > 2 || !scalars {
+                        return Err(Error::new(
+                            ErrorKind::TupleTooLarge(ret.value.clone()),
+                            ret.span,
+                        ));
+                    }
+
+                    Ok(())
+                }
+                _ => by_value(ret),
+            },
+            None => Ok(()),
+        }
+    }
+
+    fn defaults(&mut self, func: &mut Function) -> Result<(), Error> {
+        let mut defaults = false;
+
+        for param in &mut func.params {
+            match param.default {
+                Some(ref mut default) => {
+                    if !is_const(default) {
+                        return Err(Error::new(ErrorKind::NonConstDefault, default.span));
+                    }
+
+                    self.expect(default, &param.ty.value)?;
+                    defaults = true;
+                }
+                // omitted arguments are filled in from the back
+                None if defaults => {
+                    return Err(Error::new(ErrorKind::MissingDefault, param.ty.span));
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expect(&mut self, expr: &mut Expr, expected: &Ty) -> Result<(), Error> {
+        let found = self.expr(expr)?;
+
+        if found != *expected {
+            return Err(Error::new(
+                ErrorKind::MismatchedTypes {
+                    expected: expected.clone(),
+                    found,
+                },
+                expr.span,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn binary_op(&mut self, expr: &mut BinaryExpr) -> Result<Ty, Error> {
+        match expr.op.value {
+            BinaryOp::Assign if matches!(expr.left.kind, ExprKind::Tuple(_)) => {
+                self.destructure(expr)
+            }
+            BinaryOp::Assign => {
+                let ty = self.expr(&mut expr.left)?;
+
+                assignable(&expr.left)?;
+
+                // arrays and structs can't be copied
+                if !ty.is_scalar() {
+                    return Err(Error::new(ErrorKind::InvalidElement(ty), expr.left.span));
+                }
+
+                self.expect(&mut expr.right, &ty)?;
+                Ok(ty)
+            }
+            BinaryOp::Add | BinaryOp::Sub => {
+                let left = self.expr(&mut expr.left)?;
+                let right = self.expr(&mut expr.right)?;
+
+                match (&expr.op.value, left, right) {
+                    (_, Ty::Int, Ty::Int) => Ok(Ty::Int),
+                    (_, Ty::Float, Ty::Float) => Ok(Ty::Float),
+                    // concatenation, into a newly allocated string
+                    (BinaryOp::Add, Ty::Str, Ty::Str) => Ok(Ty::Str),
+                    // pointer offsets
+                    (_, ptr @ Ty::Ptr(_), Ty::Int) | (BinaryOp::Add, Ty::Int, ptr @ Ty::Ptr(_)) => {
+                        Ok(ptr)
+                    }
+                    // the distance between two pointers, in elements
+                    (BinaryOp::Sub, Ty::Ptr(left), Ty::Ptr(right)) if left == right => Ok(Ty::Int),
+                    (_, left, right) => Err(Error::new(
+                        ErrorKind::InvalidOperands { left, right },
+                        expr.op.span,
+                    )),
+                }
+            }
+            BinaryOp::And | BinaryOp::Or => {
+                self.expect(&mut expr.left, &Ty::Bool)?;
+                self.expect(&mut expr.right, &Ty::Bool)?;
+                Ok(Ty::Bool)
+            }
+            BinaryOp::Mul | BinaryOp::Div => {
+                let left = self.expr(&mut expr.left)?;
+                let right = self.expr(&mut expr.right)?;
+
+                match (left, right) {
+                    (Ty::Int, Ty::Int) => Ok(Ty::Int),
+                    (Ty::Float, Ty::Float) => Ok(Ty::Float),
+                    (left, right) => Err(Error::new(
+                        ErrorKind::InvalidOperands { left, right },
+                        expr.op.span,
+                    )),
+                }
+            }
+            _ => {
+                let left = self.expr(&mut expr.left)?;
+                let right = self.expr(&mut expr.right)?;
+                let equality = matches!(expr.op.value, BinaryOp::Eq | BinaryOp::Ne);
+
+                match (left, right) {
+                    (Ty::Int, Ty::Int) | (Ty::Float, Ty::Float) | (Ty::Str, Ty::Str) => {}
+                    (Ty::Ptr(left), Ty::Ptr(right)) if left == right => {}
+                    (Ty::Bool, Ty::Bool) if equality => {}
+                    (left, right) => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidOperands { left, right },
+                            expr.op.span,
+                        ))
+                    }
+                }
+
+                Ok(Ty::Bool)
+            }
+        }
+    }
+
+    fn cast(&mut self, cast: &mut Cast, span: Span) -> Result<Ty, Error> {
+        let from = self.expr(&mut cast.expr)?;
+        let to = cast.ty.value.clone();
+
+        match (&from, &to) {
+            (from, to) if from == to => {}
+            (Ty::Int, Ty::Float) | (Ty::Float, Ty::Int) => {}
+            (Ty::Bool, Ty::Int | Ty::Float) => {}
+            _ => return Err(Error::new(ErrorKind::InvalidCast { from, to }, span)),
+        }
+
+        Ok(to)
+    }
+
+    /// Checks a destructuring assignment, `x, y = pair`, which assigns each
+    /// element of a tuple to a place.
+    fn destructure(&mut self, expr: &mut BinaryExpr) -> Result<Ty, Error> {
+        let ExprKind::Tuple(ref mut places) = expr.left.kind else {
+            unreachable!("destructuring assignment to {:?}", expr.left.kind)
+        };
+
+        let mut elems = Vec::new();
+        for place in places {
+            let ty = self.expr(place)?;
+
+            assignable(place)?;
+
+            if !ty.is_scalar() {
+                return Err(Error::new(ErrorKind::InvalidElement(ty), place.span));
+            }
+
+            elems.push(ty);
+        }
+
+        let expected = Ty::Tuple(Rc::new(Struct::tuple(elems)));
+        expr.left.ty = expected.clone();
+
+        let found = self.value(&mut expr.right)?;
+
+        if found != expected {
+            return Err(Error::new(
+                ErrorKind::MismatchedTypes { expected, found },
+                expr.right.span,
+            ));
+        }
+
+        Ok(Ty::Void)
+    }
+
+    fn unary_op(&mut self, expr: &mut UnaryExpr) -> Result<Ty, Error> {
+        let ty = self.expr(&mut expr.expr)?;
+
+        match expr.op.value {
+            UnaryOp::AddrOf if is_place(&expr.expr) => Ok(Ty::Ptr(Box::new(ty))),
+            UnaryOp::AddrOf => Err(Error::new(ErrorKind::NotAddressable, expr.expr.span)),
+            UnaryOp::Deref => match ty {
+                Ty::Ptr(ty) => Ok(*ty),
+                ty => Err(Error::new(ErrorKind::InvalidDeref(ty), expr.expr.span)),
+            },
+            UnaryOp::Neg => match ty {
+                Ty::Int | Ty::Float => Ok(ty),
+                ty => Err(Error::new(ErrorKind::InvalidNeg(ty), expr.expr.span)),
+            },
+            UnaryOp::Not => match ty {
+                Ty::Bool => Ok(ty),
+                found => Err(Error::new(
+                    ErrorKind::MismatchedTypes {
+                        expected: Ty::Bool,
+                        found,
+                    },
+                    expr.expr.span,
+                )),
+            },
+        }
+    }
+
+    fn call(&mut self, call: &mut Call, span: Span) -> Result<Ty, Error> {
+        let func = match (call.func, call.builtin) {
+            (Some(func), _) => &self.functions[func],
+            (None, Some(builtin)) => return self.builtin(builtin, &mut call.args, span),
+            // resolution failed, and reported why
+            (None, None) => return Err(Error::new(ErrorKind::Unresolved, span)),
+        };
+
+        let required = func.params.iter().filter(|p| p.default.is_none()).count();
+
+        if call.args.len() < required || (call.args.len() > func.params.len() && !func.variadic) {
+            return Err(Error::new(
+                ErrorKind::ArgCount {
+                    expected: func.params.len(),
+                    found: call.args.len(),
+                    variadic: func.variadic,
+                },
+                span,
+            ));
+        }
+
+        for (arg, param) in call.args.iter_mut().zip(&func.params) {
+            self.expect(arg, &param.ty.value)?;
+        }
+
+        // variadic arguments can be of any type, as long as they have a value
+        for arg in call.args.iter_mut().skip(func.params.len()) {
+            if self.expr(arg)? == Ty::Void {
+                return Err(Error::new(ErrorKind::NoValue, arg.span));
+            }
+        }
+
+        Ok(func
+            .ret
+            .as_ref()
+            .map(|ty| ty.value.clone())
+            .unwrap_or(Ty::Void))
+    }
+
+    fn builtin(&mut self, builtin: Builtin, args: &mut [Expr], span: Span) -> Result<Ty, Error> {
+        let arity = |expected: usize| {
+            if args.len() != expected {
+                return Err(Error::new(
+                    ErrorKind::ArgCount {
+                        expected,
+                        found: args.len(),
+                        variadic: false,
+                    },
+                    span,
+                ));
+            }
+
+            Ok(())
+        };
+
+        match builtin {
+            Builtin::Len => {
+                arity(1)?;
+                let arg = &mut args[0];
+
+                match self.expr(arg)? {
+                    Ty::Array(..) | Ty::Str => Ok(Ty::Int),
+                    ty => Err(Error::new(ErrorKind::NoLength(ty), arg.span)),
+                }
+            }
+            Builtin::Print | Builtin::Println => {
+                if let Some((format, args)) = args.split_first_mut() {
+                    if let Some(pieces) = format_string(format)? {
+                        if self.freestanding {
+                            return Err(Error::new(
+                                ErrorKind::Freestanding("use a format string"),
+                                format.span,
+                            ));
+                        }
+
+                        self.expect(format, &Ty::Str)?;
+                        self.format(&pieces, format.span, args)?;
+                        return Ok(Ty::Void);
+                    }
+                }
+
+                for arg in args {
+                    match self.expr(arg)? {
+                        Ty::Void => return Err(Error::new(ErrorKind::NoValue, arg.span)),
+                        ty if !ty.is_scalar() => {
+                            return Err(Error::new(ErrorKind::NotPrintable(ty), arg.span))
+                        }
+                        Ty::Float if self.freestanding => {
+                            return Err(Error::new(
+                                ErrorKind::Freestanding("print a float"),
+                                arg.span,
+                            ))
+                        }
+                        Ty::Ptr(_) if self.freestanding => {
+                            return Err(Error::new(
+                                ErrorKind::Freestanding("print a pointer"),
+                                arg.span,
+                            ))
+                        }
+                        _ => {}
+                    }
+                }
+
+                Ok(Ty::Void)
+            }
+            Builtin::ReadInt | Builtin::Argc => {
+                arity(0)?;
+                Ok(Ty::Int)
+            }
+            Builtin::Argv => {
+                arity(1)?;
+                self.expect(&mut args[0], &Ty::Int)?;
+                Ok(Ty::Str)
+            }
+            Builtin::Assert => {
+                arity(1)?;
+                self.expect(&mut args[0], &Ty::Bool)?;
+                Ok(Ty::Void)
+            }
+            Builtin::Panic => {
+                arity(1)?;
+                self.expect(&mut args[0], &Ty::Str)?;
+                Ok(Ty::Void)
+            }
+        }
+    }
+
+    /// Checks the arguments of a format string against its placeholders.
+    fn format(&mut self, pieces: &[Piece], span: Span, args: &mut [Expr]) -> Result<(), Error> {
+        let mut args = args.iter_mut();
+
+        for piece in pieces {
+            let Piece::Arg(spec, ref range) = *piece else {
+                continue;
+            };
+
+            let Some(arg) = args.next() else {
+                return Err(Error::new(
+                    ErrorKind::MissingFormatArg,
+                    literal_span(span, range),
+                ));
+            };
+
+            let ty = self.expr(arg)?;
+            if ty == Ty::Void {
+                return Err(Error::new(ErrorKind::NoValue, arg.span));
+            }
+
+            match spec {
+                _ if spec.accepts(&ty) => {}
+                Spec::Display => return Err(Error::new(ErrorKind::NotPrintable(ty), arg.span)),
+                _ => return Err(Error::new(ErrorKind::FormatSpec { spec, ty }, arg.span)),
+            }
+        }
+
+        match args.next() {
+            Some(arg) => Err(Error::new(ErrorKind::UnusedFormatArg, arg.span)),
+            None => Ok(()),
+        }
+    }
+
+    fn match_expr(&mut self, expr: &mut Match) -> Result<Ty, Error> {
+        self.expect(&mut expr.scrutinee, &Ty::Int)?;
+
+        // arms are compared against the 32-bit scrutinee
+        for arm in &expr.arms {
+            if arm.value.value > i32::MAX as usize {
+                return Err(Error::new(
+                    ErrorKind::ArmOutOfRange(arm.value.value),
+                    arm.value.span,
+                ));
+            }
+        }
+
+        let mut ty = Ty::Int;
+        let bodies = expr.arms.iter_mut().map(|arm| &mut arm.body);
+
+        for body in bodies.chain(expr.default.as_deref_mut()) {
+            if self.expr(body)? != Ty::Int {
+                ty = Ty::Void;
+            }
+        }
+
+        Ok(ty)
+    }
+
+    /// A conditional has a value if both of its branches have a scalar
+    /// value of the same type.
+    fn if_expr(&mut self, expr: &mut If) -> Result<Ty, Error> {
+        self.expect(&mut expr.cond, &Ty::Bool)?;
+
+        let then = self.expr(&mut expr.then)?;
+        let otherwise = match expr.otherwise {
+            Some(ref mut otherwise) => otherwise,
+            None => return Ok(Ty::Void),
+        };
+
+        let found = self.expr(otherwise)?;
+        if then == Ty::Void || found == Ty::Void || !then.is_scalar() {
+            return Ok(Ty::Void);
+        }
+
+        if found != then {
+            // point at the value of the branch, rather than all of it
+            let span = match otherwise.kind {
+                ExprKind::Block(Block {
+                    value: Some(ref value),
+                    ..
+                }) => value.span,
+                _ => otherwise.span,
+            };
+
+            return Err(Error::new(
+                ErrorKind::MismatchedTypes {
+                    expected: then,
+                    found,
+                },
+                span,
+            ));
+        }
+
+        Ok(then)
+    }
+
+    /// Checks a block, whose value is checked by `value`.
+    fn block(
+        &mut self,
+        block: &mut Block,
+        value: fn(&mut Self, &mut Expr) -> Result<Ty, Error>,
+    ) -> Result<Ty, Error> {
+        for stmt in &mut block.stmts {
+            self.expr(stmt)?;
+        }
+
+        match block.value {
+            Some(ref mut expr) => value(self, expr),
+            None => Ok(Ty::Void),
+        }
+    }
+
+    /// Checks a value that is returned or taken apart rather than stored,
+    /// which can also be a tuple literal, or a block ending in one.
+    fn value(&mut self, expr: &mut Expr) -> Result<Ty, Error> {
+        let ty = match expr.kind {
+            ExprKind::Tuple(_) => return self.init(expr),
+            ExprKind::Block(ref mut block) => self.block(block, Self::value)?,
+            _ => return self.expr(expr),
+        };
+
+        expr.ty = ty.clone();
+        Ok(ty)
+    }
+
+    fn let_expr(&mut self, expr: &mut Let) -> Result<Ty, Error> {
+        let ty = self.init(&mut expr.init)?;
+
+        if ty == Ty::Void {
+            return Err(Error::new(ErrorKind::NoValue, expr.init.span));
+        }
+
+        self.vars[expr.var].ty = ty;
+        Ok(Ty::Void)
+    }
+
+    /// Checks the initializer of a variable, which unlike any other
+    /// expression may create an array or struct.
+    fn init(&mut self, expr: &mut Expr) -> Result<Ty, Error> {
+        let ty = match expr.kind {
+            ExprKind::Repeat(ref mut repeat) => {
+                let elem = self.element(&mut repeat.value)?;
+                Ty::Array(Box::new(elem), repeat.len.value)
+            }
+            ExprKind::Array(ref mut elems) => {
+                let (first, rest) = elems.split_first_mut().expect("empty array literal");
+                let elem = self.element(first)?;
+
+                // every element must have the type (and for nested arrays,
+                // the shape) of the first
+                for other in rest {
+                    let found = self.element(other)?;
+
+                    if found != elem {
+                        return Err(Error::new(
+                            ErrorKind::MismatchedTypes {
+                                expected: elem,
+                                found,
+                            },
+                            other.span,
+                        ));
+                    }
+                }
+
+                Ty::Array(Box::new(elem), elems.len())
+            }
+            ExprKind::Struct(ref mut lit) => self.struct_lit(lit, expr.span)?,
+            ExprKind::Tuple(ref mut elems) => {
+                let elems = elems
+                    .iter_mut()
+                    .map(|elem| self.element(elem))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ty::Tuple(Rc::new(Struct::tuple(elems)))
+            }
+            _ => return self.expr(expr),
+        };
+
+        expr.ty = ty.clone();
+        Ok(ty)
+    }
+
+    fn element(&mut self, expr: &mut Expr) -> Result<Ty, Error> {
+        let ty = self.init(expr)?;
+
+        // nested arrays and structs must be created in place, ones stored in
+        // variables can't be copied
+        let nested = matches!(
+            expr.kind,
+            ExprKind::Array(_) | ExprKind::Repeat(_) | ExprKind::Struct(_) | ExprKind::Tuple(_)
+        );
+        if !ty.is_scalar() && !nested {
+            return Err(Error::new(ErrorKind::InvalidElement(ty), expr.span));
+        }
+
+        Ok(ty)
+    }
+
+    fn struct_lit(&mut self, lit: &mut StructLit, span: Span) -> Result<Ty, Error> {
+        let ty = lit.ty.clone();
+
+        for (i, (name, _)) in lit.fields.iter().enumerate() {
+            if lit.fields[..i]
+                .iter()
+                .any(|(other, _)| other.value == name.value)
+            {
+                return Err(Error::new(ErrorKind::DuplicateField, name.span));
+            }
+        }
+
+        for (name, value) in &mut lit.fields {
+            let field = ty.field(&name.value).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::UnknownField {
+                        ty: Ty::Struct(ty.clone()),
+                        field: name.value.clone(),
+                    },
+                    name.span,
+                )
+            })?;
+
+            let found = self.element(value)?;
+            if found != field.ty {
+                return Err(Error::new(
+                    ErrorKind::MismatchedTypes {
+                        expected: field.ty.clone(),
+                        found,
+                    },
+                    value.span,
+                ));
+            }
+        }
+
+        if let Some(missing) = ty
+            .fields
+            .iter()
+            .find(|field| !lit.fields.iter().any(|(name, _)| name.value == field.name))
+        {
+            return Err(Error::new(
+                ErrorKind::MissingField(missing.name.clone()),
+                span,
+            ));
+        }
+
+        Ok(Ty::Struct(ty))
+    }
+
+    fn field(&mut self, expr: &mut FieldExpr) -> Result<Ty, Error> {
+        let ty = self.expr(&mut expr.base)?;
+        let fields = match ty {
+            Ty::Struct(ref fields) | Ty::Tuple(ref fields) => fields,
+            ty => return Err(Error::new(ErrorKind::NoFields(ty), expr.base.span)),
+        };
+
+        match fields.field(&expr.field.value) {
+            Some(field) => Ok(field.ty.clone()),
+            None => Err(Error::new(
+                ErrorKind::UnknownField {
+                    field: expr.field.value.clone(),
+                    ty,
+                },
+                expr.field.span,
+            )),
+        }
+    }
+
+    fn index(&mut self, expr: &mut Index) -> Result<Ty, Error> {
+        let (elem, len) = match self.expr(&mut expr.base)? {
+            Ty::Array(elem, len) => (*elem, Some(len)),
+            // a byte of a string, whose length is only known at runtime
+            Ty::Str => (Ty::Int, None),
+            ty => return Err(Error::new(ErrorKind::NotIndexable(ty), expr.base.span)),
+        };
+
+        self.expect(&mut expr.index, &Ty::Int)?;
+
+        if let (Some(index), Some(len)) = (const_int(&expr.index), len) {
+            if index < 0 || index as usize >= len {
+                return Err(Error::new(
+                    ErrorKind::OutOfBounds { index, len },
+                    expr.index.span,
+                ));
+            }
+        }
+
+        Ok(elem)
+    }
+
+    fn slice(&mut self, expr: &mut Slice) -> Result<Ty, Error> {
+        match self.expr(&mut expr.base)? {
+            Ty::Str => {}
+            ty => return Err(Error::new(ErrorKind::NotSliceable(ty), expr.base.span)),
+        }
+
+        for bound in expr.start.iter_mut().chain(&mut expr.end) {
+            self.expect(bound, &Ty::Int)?;
+        }
+
+        Ok(Ty::Str)
+    }
+}
+
+/// Parses the first argument of `print` as a format string, if it is one.
+pub fn format_string(expr: &Expr) -> Result<Option<Vec<Piece>>, Error> {
+    let ExprKind::Lit(WithSpan {
+        value: Lit::String(ref literal),
+        span,
+    }) = expr.kind
+    else {
+        return Ok(None);
+    };
+
+    if !format::is_format(literal) {
+        return Ok(None);
+    }
+
+    match format::parse(literal) {
+        Ok(pieces) => Ok(Some(pieces)),
+        Err(err) => Err(Error::new(
+            ErrorKind::Format(err.kind),
+            literal_span(span, &err.range),
+        )),
+    }
+}
+
+/// The span of a range of bytes in a string literal.
+fn literal_span(literal: Span, range: &Range<usize>) -> Span {
+    // skip the opening quote
+    let start = literal.start + 1;
+    Span::new(start + range.start..start + range.end)
+}
+
+/// Checks that an expression can be assigned to.
+fn assignable(expr: &Expr) -> Result<(), Error> {
+    match expr.kind {
+        ExprKind::Index(ref index) if index.base.ty == Ty::Str => {
+            Err(Error::new(ErrorKind::ImmutableStr, expr.span))
+        }
+        _ if is_place(expr) => Ok(()),
+        _ => Err(Error::new(ErrorKind::InvalidAssignment, expr.span)),
+    }
+}
+
+/// Whether an expression refers to a memory location.
+fn is_place(expr: &Expr) -> bool {
+    match expr.kind {
+        // strings are immutable, and their bytes can't be addressed
+        ExprKind::Index(ref index) => index.base.ty != Ty::Str,
+        ExprKind::Var(_) => true,
+        ExprKind::Field(ref expr) => is_place(&expr.base),
+        ExprKind::Unary(ref expr) => matches!(expr.op.value, UnaryOp::Deref),
+        _ => false,
+    }
+}
+
+/// Evaluates a constant integer expression.
+pub fn const_int(expr: &Expr) -> Option<i64> {
+    match expr.kind {
+        ExprKind::Lit(WithSpan {
+            value: Lit::Num(num),
+            ..
+        }) => Some(num as i64),
+        ExprKind::Binary(ref expr) => {
+            let (left, right) = (const_int(&expr.left)?, const_int(&expr.right)?);
+
+            match expr.op.value {
+                BinaryOp::Add => Some(left.wrapping_add(right)),
+                BinaryOp::Sub => Some(left.wrapping_sub(right)),
+                BinaryOp::Mul => Some(left.wrapping_mul(right)),
+                BinaryOp::Div => left.checked_div(right),
+                // comparisons produce booleans
+                _ => None,
+            }
+        }
+        ExprKind::Unary(ref expr) if expr.op.value == UnaryOp::Neg => {
+            Some(const_int(&expr.expr)?.wrapping_neg())
+        }
+        ExprKind::SizeOf(ref of) => Some(of.size() as i64),
+        // the length of an array is known at compile time
+        ExprKind::Call(Call {
+            builtin: Some(Builtin::Len),
+            ref args,
+            ..
+        }) => match args[0].ty {
+            Ty::Array(_, len) => Some(len as i64),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether an expression can be evaluated at compile time.
+pub fn is_const(expr: &Expr) -> bool {
+    match expr.kind {
+        ExprKind::Lit(_) | ExprKind::SizeOf(_) => true,
+        ExprKind::Binary(ref expr) => {
+            !matches!(expr.op.value, BinaryOp::Assign)
+                && is_const(&expr.left)
+                && is_const(&expr.right)
+        }
+        ExprKind::Unary(ref expr) => {
+            matches!(expr.op.value, UnaryOp::Neg | UnaryOp::Not) && is_const(&expr.expr)
+        }
+        _ => false,
+    }
+}
+
+/// Whether every path through an expression returns from the function.
+fn diverges(expr: &Expr) -> bool {
+    match expr.kind {
+        ExprKind::Return(_) => true,
+        ExprKind::Block(ref block) => block
+            .stmts
+            .iter()
+            .chain(block.value.as_deref())
+            .any(diverges),
+        ExprKind::If(ref expr) => {
+            diverges(&expr.then) && expr.otherwise.as_deref().is_some_and(diverges)
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Error {
+    fn new(kind: ErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ErrorKind {
+    ArgCount {
+        expected: usize,
+        found: usize,
+        variadic: bool,
+    },
+    NoValue,
+    /// A call that name resolution failed to resolve.
+    Unresolved,
+    /// A function that can reach the end of its body without returning a
+    /// value of its return type.
+    MissingReturn(Ty),
+    MismatchedTypes {
+        expected: Ty,
+        found: Ty,
+    },
+    NonConstDefault,
+    MissingDefault,
+    /// A match arm whose value doesn't fit in an `int`.
+    ArmOutOfRange(usize),
+    AggregateOutsideLet,
+    InvalidElement(Ty),
+    AggregateByValue(Ty),
+    /// A tuple returned by a function that doesn't fit in two registers.
+    TupleTooLarge(Ty),
+    NoFields(Ty),
+    UnknownField {
+        ty: Ty,
+        field: String,
+    },
+    DuplicateField,
+    MissingField(String),
+    NotIndexable(Ty),
+    NotSliceable(Ty),
+    OutOfBounds {
+        index: i64,
+        len: usize,
+    },
+    NotAddressable,
+    InvalidAssignment,
+    ImmutableStr,
+    InvalidDeref(Ty),
+    InvalidNeg(Ty),
+    InvalidOperands {
+        left: Ty,
+        right: Ty,
+    },
+    InvalidCast {
+        from: Ty,
+        to: Ty,
+    },
+    NoLength(Ty),
+    NotPrintable(Ty),
+    Format(format::ErrorKind),
+    MissingFormatArg,
+    UnusedFormatArg,
+    FormatSpec {
+        spec: Spec,
+        ty: Ty,
+    },
+    /// A variable that doesn't fit in the frame of its function, or of the
+    /// program.
+    FrameTooLarge,
+    /// Something that needs libc, such as `print a float`.
+    Freestanding(&'static str),
+}
+
+impl ErrorKind {
+    /// Whether the error involves the type of a variable that was never
+    /// inferred, or a call that was never resolved, because of an earlier
+    /// error.
+    fn is_cascade(&self) -> bool {
+        fn uninferred(ty: &Ty) -> bool {
+            match ty {
+                Ty::Infer => true,
+                Ty::Ptr(ty) | Ty::Array(ty, _) => uninferred(ty),
+                _ => false,
+            }
+        }
+
+        use ErrorKind::*;
+
+        match self {
+            MismatchedTypes {
+                expected: a,
+                found: b,
+            }
+            | InvalidOperands { left: a, right: b }
+            | InvalidCast { from: a, to: b } => uninferred(a) || uninferred(b),
+            InvalidElement(ty)
+            | AggregateByValue(ty)
+            | TupleTooLarge(ty)
+            | NoFields(ty)
+            | UnknownField { ty, .. }
+            | NotIndexable(ty)
+            | NotSliceable(ty)
+            | InvalidDeref(ty)
+            | InvalidNeg(ty)
+            | NoLength(ty)
+            | NotPrintable(ty)
+            | FormatSpec { ty, .. } => uninferred(ty),
+            Unresolved => true,
+            _ => false,
+        }
+    }
+}
+
+impl Spanned for Error {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        use ErrorKind::*;
+
+        match self.kind {
+            ArgCount { .. } => "E0301",
+            NoValue => "E0302",
+            Unresolved => "E0303",
+            MissingReturn(..) => "E0304",
+            MismatchedTypes { .. } => "E0305",
+            NonConstDefault => "E0306",
+            MissingDefault => "E0307",
+            ArmOutOfRange(..) => "E0308",
+            AggregateOutsideLet => "E0309",
+            InvalidElement(..) => "E0310",
+            AggregateByValue(..) => "E0311",
+            TupleTooLarge(..) => "E0312",
+            NoFields(..) => "E0313",
+            UnknownField { .. } => "E0314",
+            DuplicateField => "E0315",
+            MissingField(..) => "E0316",
+            NotIndexable(..) => "E0317",
+            NotSliceable(..) => "E0318",
+            OutOfBounds { .. } => "E0319",
+            NotAddressable => "E0320",
+            InvalidAssignment => "E0321",
+            ImmutableStr => "E0322",
+            InvalidDeref(..) => "E0323",
+            InvalidNeg(..) => "E0324",
+            InvalidOperands { .. } => "E0325",
+            InvalidCast { .. } => "E0326",
+            NoLength(..) => "E0327",
+            NotPrintable(..) => "E0328",
+            Format(..) => "E0329",
+            MissingFormatArg => "E0330",
+            UnusedFormatArg => "E0331",
+            FormatSpec { .. } => "E0332",
+            FrameTooLarge => "E0333",
+            Freestanding(..) => "E0334",
+        }
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        use ErrorKind::*;
+
+        match self.kind {
+            ArgCount {
+                expected,
+                found,
+                variadic,
+            } => format!(
+                "Expected {}{} argument(s), found {}",
+                if variadic { "at least " } else { "" },
+                expected,
+                found
+            ),
+            NoValue => "Expression does not produce a value".to_owned(),
+            Unresolved => "Cannot find the function being called".to_owned(),
+            MissingReturn(ref ty) => format!(
+                "Function can reach its end without returning a value of type '{}'",
+                ty
+            ),
+            NonConstDefault => "Default argument must be a constant".to_owned(),
+            ArmOutOfRange(value) => format!("Match arm {} does not fit in an 'int'", value),
+            MissingDefault => {
+                "Parameters following a default argument must have a default".to_owned()
+            }
+            AggregateOutsideLet => {
+                "Array, struct and tuple expressions can only be used to initialize a variable"
+                    .to_owned()
+            }
+            InvalidElement(ref ty) => format!("Cannot copy a value of type '{}'", ty),
+            AggregateByValue(ref ty) => {
+                format!("Cannot pass '{}' to a function by value, use a pointer", ty)
+            }
+            TupleTooLarge(ref ty) => format!(
+                "Cannot return '{}', only tuples of up to two scalars can be returned",
+                ty
+            ),
+            NoFields(ref ty) => format!("Type '{}' has no fields", ty),
+            UnknownField { ref ty, ref field } => match ty {
+                Ty::Tuple(_) => format!("Tuple '{}' has no field '{}'", ty, field),
+                _ => format!("Struct '{}' has no field '{}'", ty, field),
+            },
+            DuplicateField => "Field is initialized more than once".to_owned(),
+            MissingField(ref field) => format!("Missing field '{}'", field),
+            NotIndexable(ref ty) => format!("Cannot index into a value of type '{}'", ty),
+            NotSliceable(ref ty) => format!("Cannot slice a value of type '{}'", ty),
+            InvalidOperands {
+                ref left,
+                ref right,
+            } => format!("Operator cannot be applied to '{}' and '{}'", left, right),
+            InvalidCast { ref from, ref to } => {
+                format!("Cannot cast '{}' to '{}'", from, to)
+            }
+            InvalidAssignment => "Cannot assign to a temporary value".to_owned(),
+            ImmutableStr => "Cannot assign to a byte of a string, strings are immutable".to_owned(),
+            NoLength(ref ty) => format!("Values of type '{}' have no length", ty),
+            NotPrintable(ref ty) => format!("Cannot print a value of type '{}'", ty),
+            Format(kind) => format!("{}", kind),
+            MissingFormatArg => "Placeholder has no argument".to_owned(),
+            UnusedFormatArg => "Argument is not used by the format string".to_owned(),
+            FormatSpec { spec, ref ty } => {
+                format!("'{}' cannot print a value of type '{}'", spec, ty)
+            }
+            FrameTooLarge => format!(
+                "Variable does not fit on the stack, which holds at most {} bytes of variables",
+                MAX_FRAME
+            ),
+            Freestanding(feature) => {
+                format!("Cannot {} with '--freestanding'", feature)
+            }
+            NotAddressable => "Cannot take the address of a temporary value".to_owned(),
+            InvalidDeref(ref ty) => format!("Cannot dereference a value of type '{}'", ty),
+            InvalidNeg(ref ty) => format!("Cannot negate a value of type '{}'", ty),
+            OutOfBounds { index, len } => format!(
+                "Index {} is out of bounds for an array of length {}",
+                index, len
+            ),
+            MismatchedTypes {
+                ref expected,
+                ref found,
+            } => format!(
+                "Mismatched types: expected '{}', found '{}'",
+                expected, found
+            ),
+        }
+    }
+    fn failure(&self) -> Failure {
+        Failure::Semantic
+    }
+
+    fn note(&self) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::Format(format::ErrorKind::Unclosed | format::ErrorKind::Unmatched) => {
+                Some("write '{{' or '}}' to print a brace")
+            }
+            ErrorKind::Format(format::ErrorKind::UnknownSpec) => {
+                Some("placeholders are '{}', '{:x}', '{:c}' and '{:.N}'")
+            }
+            ErrorKind::Freestanding(_) => Some("freestanding programs are not linked against libc"),
+            _ => None,
+        }
+    }
+}
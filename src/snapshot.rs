@@ -0,0 +1,159 @@
+//! Snapshot tests of the compiler's output.
+//!
+//! Every `tests/codegen/<name>.rp` is compiled, and its assembly compared
+//! against `tests/codegen/<name>.s`. Every `tests/run/<name>.rp` is compiled
+//! and run, and what it prints and its exit status compared against
+//! `tests/run/<name>.out`. Running the tests with `RIPC_BLESS=1` writes the
+//! output to the snapshots instead, so that changes to the output show up in
+//! the diff of a commit.
+
+use crate::build_report::BuildReport;
+use crate::emit::{self, TempDir};
+use crate::error::Format;
+use crate::parse::Ast;
+use crate::{Codegen, DiagnosticHandler, Reporter, SourceMap};
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn codegen() {
+    snapshots("tests/codegen", "s", compile);
+}
+
+#[test]
+fn run() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    snapshots("tests/run", "out", |input| execute(input, dir.path()));
+}
+
+/// Compares the output of `f` for every `.rp` file in `dir` against the
+/// snapshot next to it with the extension `ext`.
+fn snapshots(dir: &str, ext: &str, f: impl Fn(&Path) -> String) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+    let bless = std::env::var_os("RIPC_BLESS").is_some();
+
+    let mut inputs = fs::read_dir(&dir)
+        .unwrap_or_else(|_| panic!("failed to read {}", dir.display()))
+        .map(|entry| entry.expect("failed to read test").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rp"))
+        .collect::<Vec<_>>();
+    inputs.sort();
+
+    let mut failures = Vec::new();
+    for input in &inputs {
+        let snapshot = input.with_extension(ext);
+        let actual = f(input);
+
+        if bless {
+            fs::write(&snapshot, actual).expect("failed to write snapshot");
+            continue;
+        }
+
+        match fs::read_to_string(&snapshot) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(mismatch(&snapshot, &expected, &actual)),
+            Err(_) => failures.push(format!("{}: missing snapshot", snapshot.display())),
+        }
+    }
+
+    assert!(!inputs.is_empty(), "no tests in {}", dir.display());
+    assert!(
+        failures.is_empty(),
+        "\n{}\n\nrun with RIPC_BLESS=1 to update the snapshots",
+        failures.join("\n")
+    );
+}
+
+/// Parses and checks a test, panicking if it doesn't compile.
+fn check(input: &Path) -> (SourceMap, Ast) {
+    let source = fs::read_to_string(input).expect("failed to read test");
+
+    // the name is relative, so snapshots don't depend on where the
+    // repository is checked out
+    let name = input.file_name().unwrap().to_string_lossy().into_owned();
+    let mut sources = SourceMap::new();
+    sources.add(name, None, source);
+
+    match crate::check(&mut sources, &crate::Options::default()) {
+        Ok((ast, _)) => (sources, ast),
+        Err(err) => {
+            let mut reporter = Reporter::new(std::io::stderr(), &sources).format(Format::Short);
+            err.emit(&mut reporter);
+            panic!("{} failed to compile", input.display());
+        }
+    }
+}
+
+/// Compiles a file to assembly in memory, with the source lines of each
+/// statement as comments.
+fn compile(input: &Path) -> String {
+    let (sources, ast) = check(input);
+
+    let mut out = Vec::new();
+    Codegen::builder(&sources)
+        .comments(true)
+        .entry(true)
+        .build(&mut out)
+        .write(&ast)
+        .expect("failed to generate code");
+
+    String::from_utf8(out).expect("generated code is not UTF-8")
+}
+
+/// Compiles a file to an executable in `dir` and runs it, returning what it
+/// printed to standard output followed by its exit status.
+fn execute(input: &Path, dir: &Path) -> String {
+    let (sources, ast) = check(input);
+
+    let output = dir.join(input.file_stem().unwrap());
+    let options = emit::Options {
+        output: Some(output.clone()),
+        temp: true,
+        ..emit::Options::default()
+    };
+
+    if let Err(err) = emit::emit(&ast, &sources, &options, &mut BuildReport::default()) {
+        let mut reporter = Reporter::new(std::io::stderr(), &sources).format(Format::Short);
+        reporter.on_error(&err);
+        panic!("{} failed to build", input.display());
+    }
+
+    let result = Command::new(&output)
+        .output()
+        .unwrap_or_else(|err| panic!("failed to run {}: {}", output.display(), err));
+
+    let mut out = String::from_utf8(result.stdout).expect("output is not UTF-8");
+    match result.status.code() {
+        Some(code) => out.push_str(&format!("[exit status: {}]\n", code)),
+        None => out.push_str(&format!("[{}]\n", result.status)),
+    }
+
+    out
+}
+
+/// Describes where a snapshot first differs from the output.
+fn mismatch(snapshot: &Path, expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => {}
+            (None, None) => break,
+            (e, a) => {
+                return format!(
+                    "{}:{}: snapshot differs\n  expected: {}\n    actual: {}",
+                    snapshot.display(),
+                    line,
+                    e.unwrap_or("<end of file>"),
+                    a.unwrap_or("<end of file>"),
+                );
+            }
+        }
+    }
+
+    // only the line endings differ
+    format!("{}: snapshot differs", snapshot.display())
+}
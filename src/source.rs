@@ -0,0 +1,151 @@
+use crate::Span;
+
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The source files making up a program.
+///
+/// Files are laid out one after another in a single range of offsets, so a
+/// `Span` on its own identifies the file it points into.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+pub struct SourceFile {
+    /// The name of the file in diagnostics.
+    pub name: String,
+    /// The canonical path of the file, or `None` for source passed on the
+    /// command line.
+    pub path: Option<PathBuf>,
+    pub source: Rc<str>,
+    /// The offset of the start of the file.
+    pub start: usize,
+    /// The offset of the start of every line, relative to the start of the
+    /// file.
+    lines: Vec<usize>,
+}
+
+impl SourceFile {
+    /// Returns the 1-based line and column of an offset relative to the
+    /// start of the file.
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.lines.partition_point(|&start| start <= offset);
+        let start = self.lines[line - 1];
+        let column = self.source[start..offset].chars().count() + 1;
+        (line, column)
+    }
+
+    /// Returns the text of a 1-based line, without the newline or the
+    /// carriage return before it.
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.lines[line - 1];
+        let end = self
+            .lines
+            .get(line)
+            .map(|&next| next - 1)
+            .unwrap_or(self.source.len());
+        let line = &self.source[start..end];
+        line.strip_suffix('\r').unwrap_or(line)
+    }
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, without the byte order mark it may start with.
+    pub fn add(&mut self, name: String, path: Option<PathBuf>, source: String) -> &SourceFile {
+        let source = match source.strip_prefix('\u{feff}') {
+            Some(source) => source.to_owned(),
+            None => source,
+        };
+
+        // leave a gap after every file, so that the end of one file is not
+        // the start of the next
+        let start = self
+            .files
+            .last()
+            .map(|file| file.start + file.source.len() + 1)
+            .unwrap_or(0);
+
+        let lines = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        self.files.push(SourceFile {
+            name,
+            path,
+            source: source.into(),
+            start,
+            lines,
+        });
+
+        self.files.last().unwrap()
+    }
+
+    /// The file passed to the compiler.
+    pub fn main(&self) -> &SourceFile {
+        &self.files[0]
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Returns the file containing the given offset.
+    pub fn file(&self, offset: usize) -> &SourceFile {
+        let i = self.files.partition_point(|file| file.start <= offset);
+        &self.files[i - 1]
+    }
+
+    /// Returns the file at the given canonical path.
+    pub fn find(&self, path: &Path) -> Option<&SourceFile> {
+        self.files
+            .iter()
+            .find(|file| file.path.as_deref() == Some(path))
+    }
+
+    /// Returns the file containing an offset, along with the 1-based line
+    /// and column of the offset in that file. EOF is the end of the main
+    /// file.
+    pub fn location(&self, span: Span) -> (&SourceFile, usize, usize) {
+        let (file, offset) = match span.range() {
+            Some(range) => {
+                let file = self.file(range.start);
+                (file, range.start - file.start)
+            }
+            None => {
+                let file = self.main();
+                (file, file.source.trim_end().len())
+            }
+        };
+
+        let (line, column) = file.line_column(offset);
+        (file, line, column)
+    }
+
+    /// Returns the 1-based line and column of the end of a span, which is
+    /// the same as its start for EOF.
+    pub fn end_location(&self, span: Span) -> (usize, usize) {
+        match span.range() {
+            Some(range) => {
+                let file = self.file(range.start);
+                file.line_column(range.end.min(file.start + file.source.len()) - file.start)
+            }
+            None => {
+                let (_, line, column) = self.location(span);
+                (line, column)
+            }
+        }
+    }
+
+    /// Returns the source text of a span, or `None` for EOF.
+    pub fn slice(&self, span: Span) -> Option<&str> {
+        let range = span.range()?;
+        let file = self.file(range.start);
+        file.source
+            .get(range.start - file.start..range.end - file.start)
+    }
+}
@@ -14,7 +14,7 @@ impl Span {
     }
 
     pub fn range(&self) -> Option<Range<usize>> {
-        (*self != Self::EOF).then(|| self.start..self.end)
+        (*self != Self::EOF).then_some(self.start..self.end)
     }
 }
 
@@ -30,6 +30,7 @@ pub trait Spanned {
     fn span(&self) -> Span;
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct WithSpan<T> {
     pub value: T,
     pub span: Span,
@@ -39,6 +40,11 @@ impl<T> WithSpan<T> {
     pub fn new(value: T, span: Span) -> Self {
         Self { value, span }
     }
+
+    /// Transforms the value, keeping the span.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithSpan<U> {
+        WithSpan::new(f(self.value), self.span)
+    }
 }
 
 impl<T> Spanned for WithSpan<T> {
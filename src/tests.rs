@@ -0,0 +1,1522 @@
+//! Tests of the compiler as a whole that don't fit a snapshot, such as
+//! inputs too large to check in.
+
+use crate::build_report::BuildReport;
+use crate::codegen::{Builder, Codegen, Syntax, Target};
+use crate::emit::{self, TempDir};
+use crate::error::Format;
+use crate::eval;
+use crate::lex::{self, StreamLexer, TokenKind};
+use crate::lint;
+use crate::{
+    DiagnosticHandler, Diagnostics, Failure, Lexer, Options, Report, Reporter, SourceMap, Span,
+    Spanned,
+};
+
+use std::io::Write;
+use std::os::unix::process::ExitStatusExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Checks `source`, returning how it failed, if it did.
+///
+/// The check runs on a thread with the stack of a main thread, which the
+/// default nesting limit is chosen for, rather than the smaller stack of a
+/// test thread.
+fn check(source: String) -> Option<Failure> {
+    let thread = std::thread::Builder::new()
+        .stack_size(8 << 20)
+        .spawn(move || {
+            let mut sources = SourceMap::new();
+            sources.add("<test>".to_owned(), None, source);
+
+            crate::check(&mut sources, &Options::default())
+                .err()
+                .map(|err| err.first().failure())
+        })
+        .expect("failed to spawn thread");
+
+    thread.join().expect("check panicked")
+}
+
+/// Chains of postfix operators and nested types are limited like any other
+/// nesting, rather than overflowing the stack.
+#[test]
+fn max_depth() {
+    let inputs = [
+        format!("let a = [0; 4]; let x = a{};", "[0]".repeat(1000)),
+        format!("let t = (1,); let x = t{};", ".0".repeat(1000)),
+        format!("let s = \"ab\"; let x = s{};", "[0..1]".repeat(1000)),
+        format!(
+            "extern fn f(a: {}int{});",
+            "[".repeat(1000),
+            "; 1]".repeat(1000)
+        ),
+        format!(
+            "extern fn f(a: {}int{});",
+            "(".repeat(1000),
+            ",)".repeat(1000)
+        ),
+        format!("extern fn f(a: {}int);", "*".repeat(1000)),
+    ];
+
+    for input in inputs {
+        assert_eq!(check(input), Some(Failure::Syntax));
+    }
+}
+
+/// Building the same input twice produces byte-identical output, even though
+/// the intermediates are written to a different temporary directory each
+/// time.
+#[test]
+fn reproducible() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+
+    let mut sources = SourceMap::new();
+    let source = "fn square(x: int) -> int { x * x }\nlet x = 4;\nprintln(square(x), 1.5);";
+    sources.add("reproducible.rp".to_owned(), None, source.to_owned());
+    let (ast, _) = crate::check(&mut sources, &Options::default())
+        .unwrap_or_else(|_| panic!("failed to check"));
+
+    for debug in [false, true] {
+        let build = |name: &str| {
+            let options = emit::Options {
+                output: Some(dir.path().join(name)),
+                temp: true,
+                debug,
+                ..emit::Options::default()
+            };
+
+            emit::emit(&ast, &sources, &options, &mut BuildReport::default())
+                .unwrap_or_else(|err| panic!("failed to build: {:?}", err));
+            std::fs::read(dir.path().join(name)).expect("failed to read output")
+        };
+
+        assert!(build("first") == build("second"), "debug: {}", debug);
+    }
+}
+
+/// Evaluates `source`, returning the printed value of its last statement.
+fn eval(source: &str) -> String {
+    let mut sources = SourceMap::new();
+    sources.add("<test>".to_owned(), None, source.to_owned());
+
+    let (ast, _) = crate::check(&mut sources, &Options::default())
+        .unwrap_or_else(|_| panic!("failed to check '{}'", source));
+    let value = eval::eval(&ast).unwrap_or_else(|err| panic!("failed to evaluate: {:?}", err));
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+/// Operands are evaluated left to right, along with their side effects.
+#[test]
+fn evaluation_order() {
+    let cases = [
+        ("let x = 1; x + (x = 10);", "11"),
+        ("let x = 2; (x = x + 3) * x;", "25"),
+        ("let y = 2; y * 10 + { y = 3; y };", "23"),
+        (
+            "let n = 1; let m = { n = n + 1; n } * 10 + { n = n * 10; n }; m;",
+            "40",
+        ),
+        ("let n = 0; let b = (n = 1) == 1 || (n = 5) == 5; n;", "1"),
+        ("let n = 0; let b = (n = 1) == 2 && (n = 5) == 5; n;", "1"),
+        ("let n = 0; let b = (n = 1) == 2 || (n = 5) == 5; n;", "5"),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(eval(source), expected, "{}", source);
+    }
+}
+
+/// A tolerant lexer turns integer literals that are too large into error
+/// tokens, including when the literal spans chunks of a stream.
+#[test]
+fn number_too_large() {
+    let source = format!("{}x = 99999999999999999999; 1", " ".repeat(4090));
+
+    let tokens = Lexer::new(&source, 0)
+        .tolerant()
+        .map(|token| token.unwrap().kind)
+        .filter(|kind| *kind != TokenKind::Whitespace)
+        .collect::<Vec<_>>();
+
+    let mut stream = StreamLexer::new(source.as_bytes()).tolerant();
+    let mut streamed = Vec::new();
+    while let Some(token) = stream.next_token() {
+        let token = token.unwrap();
+        if token.kind != TokenKind::Whitespace {
+            streamed.push(format!("{:?}", token.kind));
+        }
+    }
+
+    let expected = [
+        TokenKind::Ident("x"),
+        TokenKind::Assign,
+        TokenKind::Error(lex::ErrorKind::NumberTooLarge),
+        TokenKind::Semi,
+        TokenKind::Num(1),
+    ];
+    assert_eq!(tokens, expected);
+    assert_eq!(
+        streamed,
+        expected.map(|kind| format!("{:?}", kind)),
+        "streamed"
+    );
+}
+
+/// A reader that returns a byte at a time, and then fails if `error` is set.
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    error: bool,
+}
+
+impl std::io::Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.bytes.split_first() {
+            Some((&byte, rest)) => {
+                buf[0] = byte;
+                self.bytes = rest;
+                Ok(1)
+            }
+            None if self.error => Err(std::io::ErrorKind::BrokenPipe.into()),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Lexes everything `lexer` streams, keeping the kind and span of each token
+/// that isn't whitespace, and the error that ends the stream.
+fn stream<R: std::io::Read>(mut lexer: StreamLexer<R>) -> Vec<Result<(String, Span), String>> {
+    let mut tokens = Vec::new();
+
+    while let Some(token) = lexer.next_token() {
+        match token {
+            Ok(token) if token.kind == TokenKind::Whitespace => {}
+            Ok(token) => tokens.push(Ok((format!("{:?}", token.kind), token.span))),
+            Err(err) => tokens.push(Err(format!("{} at {:?}", err.kind, err.span))),
+        }
+    }
+
+    tokens
+}
+
+/// Streaming input a byte at a time produces the same tokens and spans as
+/// lexing it whole, including tokens that need lookahead and characters
+/// split across reads, and ends at an error.
+#[test]
+fn stream_lexer() {
+    let source = "fn f(a: int, ...) -> float { 1.5 }\nlet s = \"héllo → 世界\"; s[0..2] <= 10;";
+
+    let tokens = Lexer::new(source, 0)
+        .map(|token| token.unwrap())
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .map(|token| Ok((format!("{:?}", token.kind), token.span)))
+        .collect::<Vec<_>>();
+
+    let reader = Trickle {
+        bytes: source.as_bytes(),
+        error: false,
+    };
+    assert_eq!(stream(StreamLexer::new(reader)), tokens);
+
+    let reader = Trickle {
+        bytes: b"let x = 1;",
+        error: true,
+    };
+    let tokens = stream(StreamLexer::new(reader));
+    assert_eq!(tokens.len(), 6);
+    assert_eq!(tokens[4], Ok(("Semi".to_owned(), Span::new(9..10))));
+    assert_eq!(
+        tokens[5],
+        Err(format!(
+            "Failed to read input: broken pipe at {:?}",
+            Span::new(10..10)
+        ))
+    );
+
+    let reader = Trickle {
+        bytes: b"let x = 1 $ 2;",
+        error: false,
+    };
+    let tokens = stream(StreamLexer::new(reader));
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(
+        tokens[4],
+        Err(format!("Invalid character '$' at {:?}", Span::new(10..11)))
+    );
+}
+
+/// A tolerant lexer never fails: invalid characters and unterminated strings
+/// become error tokens covering them, and lexing carries on after them.
+#[test]
+fn tolerant_lexer() {
+    let source = "let x = 1 @ 2;\nlet s = \"abc";
+
+    let tokens = Lexer::new(source, 0)
+        .tolerant()
+        .map(|token| token.expect("a tolerant lexer failed"))
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .map(|token| (token.kind, token.span))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tokens[4..],
+        [
+            (
+                TokenKind::Error(lex::ErrorKind::InvalidCharacter('@')),
+                Span::new(10..11)
+            ),
+            (TokenKind::Num(2), Span::new(12..13)),
+            (TokenKind::Semi, Span::new(13..14)),
+            (TokenKind::Let, Span::new(15..18)),
+            (TokenKind::Ident("s"), Span::new(19..20)),
+            (TokenKind::Assign, Span::new(21..22)),
+            (
+                TokenKind::Error(lex::ErrorKind::UnexpectedEof),
+                Span::new(23..27)
+            ),
+        ]
+    );
+
+    let reader = Trickle {
+        bytes: source.as_bytes(),
+        error: false,
+    };
+    let streamed = stream(StreamLexer::new(reader).tolerant());
+    assert_eq!(
+        streamed,
+        tokens
+            .iter()
+            .map(|(kind, span)| Ok((format!("{:?}", kind), *span)))
+            .collect::<Vec<_>>()
+    );
+}
+
+/// A byte order mark is skipped and `\r\n` ends a line, so errors point at
+/// the line and column an editor shows, and spans don't count the mark.
+#[test]
+fn crlf_and_bom() {
+    let source = "\u{feff}let x = 1;\r\n\r\nlet y = x + true;\r\n";
+
+    let mut sources = SourceMap::new();
+    sources.add("<test>".to_owned(), None, source.to_owned());
+    let err = match crate::check(&mut sources, &Options::default()) {
+        Ok(_) => panic!("expected an error"),
+        Err(err) => err,
+    };
+
+    let (file, line, column) = sources.location(err.first().span());
+    assert_eq!((line, column), (3, 11));
+    assert_eq!(file.line(3), "let y = x + true;");
+    assert_eq!(file.line(1), "let x = 1;");
+
+    let reader = Trickle {
+        bytes: source.as_bytes(),
+        error: false,
+    };
+    let tokens = stream(StreamLexer::new(reader));
+    assert_eq!(tokens[0], Ok(("Let".to_owned(), Span::new(0..3))));
+    assert_eq!(tokens[5], Ok(("Let".to_owned(), Span::new(14..17))));
+}
+
+/// Identifiers can start with and contain underscores, and a lone `_` is a
+/// token of its own.
+#[test]
+fn underscore_identifiers() {
+    let tokens = Lexer::new("_ _x snake_case x_1 __", 0)
+        .map(|token| token.unwrap().kind)
+        .filter(|kind| *kind != TokenKind::Whitespace)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tokens,
+        [
+            TokenKind::Underscore,
+            TokenKind::Ident("_x"),
+            TokenKind::Ident("snake_case"),
+            TokenKind::Ident("x_1"),
+            TokenKind::Ident("__"),
+        ]
+    );
+    assert_eq!(
+        eval("let snake_case = 2; let _x = 3; snake_case * _x;"),
+        "6"
+    );
+}
+
+/// An argument ends at the comma after it, however much of an expression
+/// comes before, so calls see as many arguments as they are passed.
+#[test]
+fn call_arguments() {
+    let source = "extern fn f(a: int, b: int, c: int) -> int;
+f(1 + 2, f(3, 4 * 5, { 6 }), -7);";
+    assert_eq!(check(source.to_owned()), None);
+
+    assert_eq!(
+        error(
+            "extern fn f(a: int, b: int) -> int; f(1 + 2, 3, 4);",
+            &Options::default()
+        ),
+        ("Expected 2 argument(s), found 3".to_owned(), 37)
+    );
+}
+
+/// Tokens display as the source they were lexed from.
+#[test]
+fn token_display() {
+    let tokens = Lexer::new("x_1 - len(s) + 2", 0)
+        .map(|token| token.unwrap().kind)
+        .filter(|kind| *kind != TokenKind::Whitespace)
+        .map(|kind| kind.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(tokens, ["x_1", "-", "len", "(", "s", ")", "+", "2"]);
+}
+
+/// Calls to C functions are checked against their `extern` declaration.
+#[test]
+fn extern_declarations() {
+    let cases = [
+        (
+            "extern fn abs(n: int) -> int; abs(\"x\");",
+            "Mismatched types: expected 'int', found 'str'",
+            35,
+        ),
+        (
+            "extern fn abs(n: int) -> int; abs();",
+            "Expected 1 argument(s), found 0",
+            31,
+        ),
+        (
+            "extern fn srand(n: int); let x = srand(1);",
+            "Expression does not produce a value",
+            34,
+        ),
+        (
+            "extern fn abs(n: int) -> int; extern fn abs(n: int) -> int;",
+            "Function 'abs' is declared more than once",
+            41,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+}
+
+/// Defaults must be constants, and once a parameter has one every parameter
+/// after it must too.
+#[test]
+fn default_arguments() {
+    let cases = [
+        (
+            "fn f(a: int = 1, b: int) -> int { a }",
+            "Parameters following a default argument must have a default",
+            21,
+        ),
+        (
+            "let x = 1; fn f(a: int = x) -> int { a }",
+            "Default argument must be a constant",
+            26,
+        ),
+        (
+            "fn f(a: int = \"s\") -> int { a }",
+            "Mismatched types: expected 'int', found 'str'",
+            15,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+}
+
+/// Variadic functions take at least their fixed arguments, and only C
+/// functions can be variadic.
+#[test]
+fn variadic_calls() {
+    let cases = [
+        (
+            "extern fn printf(f: str, ...) -> int; printf();",
+            "Expected at least 1 argument(s), found 0",
+            39,
+        ),
+        (
+            "extern fn printf(f: str, ...) -> int; printf(1, 2);",
+            "Mismatched types: expected 'str', found 'int'",
+            46,
+        ),
+        (
+            "fn f(a: int, ...) -> int { a }",
+            "Only extern functions can be variadic",
+            5,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+}
+
+/// Calls are checked against functions and prototypes declared after them,
+/// as they are against those declared before.
+#[test]
+fn later_declarations() {
+    let cases = [
+        (
+            "f(1, 2);\nextern fn f(a: int) -> int;",
+            "Expected 1 argument(s), found 2",
+            1,
+        ),
+        (
+            "let x = g(true);\nfn g(n: int) -> int { n }",
+            "Mismatched types: expected 'int', found 'bool'",
+            11,
+        ),
+        (
+            "h();\nextern fn h() -> int;\nfn h() -> int { 1 }",
+            "Function 'h' is declared more than once",
+            4,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+}
+
+/// Every label a `goto` jumps to is defined exactly once, in the function
+/// of the `goto`.
+#[test]
+fn labels() {
+    let cases = [
+        ("let x = 1;\ngoto end;\nx;", "Use of undefined label", 1),
+        (
+            "again:\nlet x = 1;\n  again: x;",
+            "Label is defined more than once",
+            3,
+        ),
+        (
+            "fn f() -> int { goto out; 1 }\nout: 0;",
+            "Use of undefined label",
+            17,
+        ),
+        (
+            "out: 0;\nfn f() -> int { goto out; 1 }",
+            "Use of undefined label",
+            17,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+
+    // functions have labels of their own
+    let source = "fn f() -> int { out: 1 }\nfn g() -> int { goto out; out: 2 }\nout: f() + g();";
+    assert_eq!(check(source.to_owned()), None);
+}
+
+/// Lexing a string of escapes takes linear time, where lookahead used to
+/// walk the rest of the source and made it quadratic. Ignored, as timings are
+/// only meaningful in isolation, `cargo test -- --ignored escape_throughput`.
+#[test]
+#[ignore]
+fn escape_throughput() {
+    let source = format!("let s = \"{}\";", "\\n\\\"\\\\".repeat(100_000));
+
+    let start = Instant::now();
+    let tokens = Lexer::new(&source, 0)
+        .map(|token| token.unwrap().kind)
+        .filter(|kind| *kind != TokenKind::Whitespace)
+        .count();
+    let elapsed = start.elapsed();
+
+    assert_eq!(tokens, 5);
+    assert!(
+        elapsed < Duration::from_secs(1),
+        "lexing {} bytes of escapes took {:?}",
+        source.len(),
+        elapsed
+    );
+}
+
+/// A handler that keeps the messages of diagnostics, without rendering them.
+struct Messages<'a> {
+    sources: &'a SourceMap,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl DiagnosticHandler for Messages<'_> {
+    fn on_error(&mut self, err: &dyn Report) {
+        self.errors.push(err.message(self.sources));
+    }
+
+    fn on_warning(&mut self, warning: &dyn Report) {
+        self.warnings.push(warning.message(self.sources));
+    }
+}
+
+/// Diagnostics can be read as values by a handler other than `Reporter`.
+#[test]
+fn diagnostic_handler() {
+    let mut sources = SourceMap::new();
+    sources.add(
+        "<test>".to_owned(),
+        None,
+        "let x = 1 + true;\nlet y = 1.5 + 1;".to_owned(),
+    );
+
+    let err = match crate::check(&mut sources, &Options::default()) {
+        Ok(_) => panic!("expected errors"),
+        Err(err) => err,
+    };
+
+    let mut handler = Messages {
+        sources: &sources,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    err.emit(&mut handler);
+
+    assert_eq!(
+        handler.errors,
+        [
+            "Operator cannot be applied to 'int' and 'bool'",
+            "Operator cannot be applied to 'float' and 'int'",
+        ]
+    );
+}
+
+/// Checks `source` with `options`, returning the message of each warning
+/// along with the line it is on and the lines of its labels.
+fn warnings(source: &str, options: &Options) -> Vec<(String, usize, Vec<usize>)> {
+    let mut sources = SourceMap::new();
+    sources.add("<test>".to_owned(), None, source.to_owned());
+
+    let (_, warnings) = crate::check(&mut sources, options)
+        .unwrap_or_else(|_| panic!("failed to check '{}'", source));
+
+    warnings
+        .into_iter()
+        .map(|warning| {
+            // boxed, as diagnostics are passed around
+            let warning: Box<dyn Report> = Box::new(warning);
+            let line = |span| sources.location(span).1;
+            let labels = warning
+                .labels()
+                .iter()
+                .map(|&(span, _)| line(span))
+                .collect();
+            (warning.message(&sources), line(warning.span()), labels)
+        })
+        .collect()
+}
+
+/// `--warn=shadow` points at both the shadowing and the shadowed variable.
+#[test]
+fn shadow_lint() {
+    let source = "let x = 1;
+if x > 0 {
+    let x = 2;
+    let x = 3;
+    println(x);
+}
+fn f(n: int) -> int {
+    for (let n = 0; n < 2; n = n + 1) {}
+    n
+}
+println(x);";
+
+    assert_eq!(warnings(source, &Options::default()), []);
+
+    let options = Options {
+        lints: lint::Lints { shadow: true },
+        ..Options::default()
+    };
+    let shadows = |name| format!("'{}' shadows a variable of an enclosing block", name);
+    assert_eq!(
+        warnings(source, &options),
+        [(shadows("x"), 3, vec![1]), (shadows("n"), 8, vec![7])]
+    );
+}
+
+/// Conditions that are always true or always false are warned about, except
+/// by `--eval`.
+#[test]
+fn constant_condition_lint() {
+    let source = "let x = 1;
+let b = false;
+if b = true { println(x); }
+if 1 < 2 { println(1); }
+if x < 2 { println(2); }
+while 1 > 2 { println(3); }
+while true { break; }";
+
+    let always = |value| format!("This condition is always {}", value);
+    assert_eq!(
+        warnings(source, &Options::default()),
+        [
+            (always(true), 3, vec![]),
+            (always(true), 4, vec![]),
+            (always(false), 6, vec![]),
+        ]
+    );
+
+    let options = Options {
+        eval: Some(source.to_owned()),
+        ..Options::default()
+    };
+    assert_eq!(warnings(source, &options), []);
+}
+
+/// Checks `source` with `options`, returning the message and column of its
+/// first error.
+fn error(source: &str, options: &Options) -> (String, usize) {
+    let mut sources = SourceMap::new();
+    sources.add("<test>".to_owned(), None, source.to_owned());
+
+    match crate::check(&mut sources, options) {
+        Ok(_) => panic!("expected '{}' to fail", source),
+        Err(err) => {
+            let err = err.first();
+            (err.message(&sources), sources.location(err.span()).2)
+        }
+    }
+}
+
+/// Checks `source` and builds it into `dir` with `options`, returning the
+/// path of the output, which is named `name`.
+fn build(source: &str, name: &str, options: Options, dir: &TempDir) -> PathBuf {
+    let mut sources = SourceMap::new();
+    sources.add(format!("{}.rp", name), None, source.to_owned());
+    let (ast, _) = crate::check(&mut sources, &options)
+        .unwrap_or_else(|_| panic!("failed to check '{}'", source));
+
+    let output = dir.path().join(name);
+    let options = emit::Options {
+        output: Some(output.clone()),
+        temp: true,
+        ..options.emit
+    };
+    emit::emit(&ast, &sources, &options, &mut BuildReport::default())
+        .unwrap_or_else(|err| panic!("failed to build: {:?}", err));
+
+    output
+}
+
+/// `--freestanding` rejects what its runtime can't do without libc, pointing
+/// at what needs it.
+#[test]
+fn freestanding() {
+    let options = Options {
+        emit: emit::Options {
+            freestanding: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+
+    let cases = [
+        ("let x = 1; println(x, 1.5);", "print a float", 23),
+        ("print(2.0 * 3.0);", "print a float", 7),
+        ("let x = 1; println(&x);", "print a pointer", 20),
+        (
+            "let x = 1; println(\"x = {}\", x);",
+            "use a format string",
+            20,
+        ),
+    ];
+
+    for (source, what, column) in cases {
+        let message = format!("Cannot {} with '--freestanding'", what);
+        assert_eq!(error(source, &options), (message, column), "{}", source);
+
+        // and are fine when linked against libc
+        assert_eq!(warnings(source, &Options::default()), [], "{}", source);
+    }
+
+    // reading from standard input needs no libc
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let source = "let a = read_int(); let b = read_int(); let c = read_int(); println(a, b, c);";
+    let output = build(source, "freestanding", options, &dir);
+
+    let mut child = Command::new(&output)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run program");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"  12\n\t-305 x")
+        .expect("failed to write to program");
+    let result = child.wait_with_output().expect("failed to run program");
+    assert_eq!(String::from_utf8_lossy(&result.stdout), "12 -305 0\n");
+}
+
+/// With `--stack-protector`, a function that writes past its variables
+/// aborts before returning into what it overwrote, and one that doesn't
+/// returns as usual.
+#[test]
+fn stack_protector() {
+    let options = || Options {
+        emit: emit::Options {
+            stack_protector: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    let dir = TempDir::new().expect("failed to create temporary directory");
+
+    let source = "fn fill() -> int {
+    let a = [0; 2];
+    let p = &a[0];
+    let i = 0;
+    while i < 2 { *(p + i) = 0 - 1; i = i + 1; }
+    a[1]
+}
+fill() + 2;";
+    let output = build(source, "fits", options(), &dir);
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(result.status.code(), Some(1));
+
+    let output = build(&source.replace("i < 2", "i < 8"), "smash", options(), &dir);
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr),
+        "[panic]: stack smashing detected\n"
+    );
+    assert_eq!(result.status.signal(), Some(6));
+}
+
+/// With `--bounds-checks`, indexing past the end of an array panics with the
+/// index, the length and where it happened.
+#[test]
+fn bounds_checks() {
+    let options = || Options {
+        emit: emit::Options {
+            bounds_checks: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    let dir = TempDir::new().expect("failed to create temporary directory");
+
+    let source = "let a = [1, 2, 3];
+let i = 0;
+while i < 3 { a[i] = i; i = i + 1; }
+a[2];";
+    let output = build(source, "within", options(), &dir);
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(result.status.code(), Some(2));
+
+    let output = build(&source.replace("i < 3", "i < 5"), "past", options(), &dir);
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr),
+        "[panic]: index 3 is out of bounds for an array of length 3\n --> past.rp:3:17\n"
+    );
+    assert_eq!(result.status.code(), Some(101));
+}
+
+/// Match arms are compared against an `int`, so their values have to fit in
+/// one.
+#[test]
+fn match_arm_range() {
+    let source = "let x = 1; match x { 2147483647 => 1, 2147483648 => 2, _ => 3 };";
+    assert_eq!(
+        error(source, &Options::default()),
+        (
+            "Match arm 2147483648 does not fit in an 'int'".to_owned(),
+            39
+        )
+    );
+
+    let source = "let x = 1; match x { 0 => 1, 2147483647 => 2, _ => 3 };";
+    assert_eq!(check(source.to_owned()), None);
+}
+
+/// At `-O2`, a match with dense arms jumps through a table, and behaves as
+/// it does when every arm is compared.
+#[test]
+fn match_jump_table() {
+    let source =
+        "fn f(n: int) -> int { match n { 3 => 30, 4 => 40, 5 => 50, 7 => 70, _ => 0 - 1 } }
+for (let n = 0 - 1; n <= 9; n = n + 1) { print(f(n)); print(\" \"); }
+0;";
+
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    for opt_level in [0, 2] {
+        let mut sources = SourceMap::new();
+        sources.add("<test>".to_owned(), None, source.to_owned());
+        let (ast, _) = crate::check(&mut sources, &Options::default())
+            .unwrap_or_else(|_| panic!("failed to check '{}'", source));
+
+        let mut asm = Vec::new();
+        Codegen::builder(&sources)
+            .opt_level(opt_level)
+            .build(&mut asm)
+            .write(&ast)
+            .expect("failed to generate code");
+        let asm = String::from_utf8(asm).unwrap();
+        assert_eq!(asm.contains("jmp *%rax"), opt_level == 2, "-O{}", opt_level);
+
+        let options = Options {
+            emit: emit::Options {
+                opt_level,
+                ..emit::Options::default()
+            },
+            ..Options::default()
+        };
+        let output = build(source, &format!("match{}", opt_level), options, &dir);
+        let result = Command::new(&output)
+            .output()
+            .expect("failed to run program");
+        assert_eq!(
+            String::from_utf8_lossy(&result.stdout),
+            "-1 -1 -1 -1 30 40 50 -1 70 -1 -1 ",
+            "-O{}",
+            opt_level
+        );
+    }
+}
+
+/// `break` and `continue` outside of a loop are reported at the keyword,
+/// including after a loop has ended and in a function called from one.
+#[test]
+fn break_outside_loop() {
+    let cases = [
+        ("break;", "`break` outside of a loop", (1, 1), (1, 6)),
+        (
+            "let i = 0;\nwhile i < 2 { i = i + 1; }\n  continue;",
+            "`continue` outside of a loop",
+            (3, 3),
+            (3, 11),
+        ),
+        (
+            "fn f() -> int { break; 1 }\nwhile true { f(); }",
+            "`break` outside of a loop",
+            (1, 17),
+            (1, 22),
+        ),
+    ];
+
+    for (source, message, start, end) in cases {
+        let mut sources = SourceMap::new();
+        sources.add("<test>".to_owned(), None, source.to_owned());
+
+        let err = match crate::check(&mut sources, &Options::default()) {
+            Ok(_) => panic!("expected '{}' to fail", source),
+            Err(err) => err,
+        };
+        let err = err.first();
+        let (_, line, column) = sources.location(err.span());
+
+        assert_eq!(err.message(&sources), message, "{}", source);
+        assert_eq!((line, column), start, "{}", source);
+        assert_eq!(sources.end_location(err.span()), end, "{}", source);
+        assert_eq!(
+            sources.slice(err.span()),
+            message.split('`').nth(1),
+            "{}",
+            source
+        );
+    }
+}
+
+/// Only AT&T syntax for x86-64 can be generated, other syntaxes and targets
+/// are rejected by name.
+#[test]
+fn syntax_and_target() {
+    assert_eq!(Syntax::from_name("att"), Some(Syntax::Att));
+    assert_eq!(Syntax::from_name("intel"), None);
+    assert_eq!(Target::from_name("x86_64"), Some(Target::X86_64));
+    assert_eq!(Target::from_name("x86-64"), Some(Target::X86_64));
+    assert_eq!(Target::from_name("aarch64"), None);
+    assert_eq!(Target::from_name("i686"), None);
+
+    let mut sources = SourceMap::new();
+    sources.add("<test>".to_owned(), None, "println(1 + 2);".to_owned());
+    let (ast, _) = crate::check(&mut sources, &Options::default())
+        .unwrap_or_else(|_| panic!("failed to check"));
+
+    let generate = |builder: Builder<'_>| {
+        let mut asm = Vec::new();
+        builder
+            .build(&mut asm)
+            .write(&ast)
+            .expect("failed to generate code");
+        asm
+    };
+
+    assert_eq!(
+        generate(
+            Codegen::builder(&sources)
+                .syntax(Syntax::Att)
+                .target(Target::X86_64)
+        ),
+        generate(Codegen::builder(&sources))
+    );
+}
+
+/// Variables too large to address from the frame pointer are rejected
+/// rather than failing to assemble.
+#[test]
+fn frame_too_large() {
+    let message =
+        "Variable does not fit on the stack, which holds at most 1073741824 bytes of variables";
+    let cases = [
+        ("let a = [0; 100000000000]; 0;", 5),
+        ("let a = [0; 200000000];\nlet b = [0; 200000000]; 0;", 5),
+        ("fn f() -> int { let a = [1.5; 200000000]; 0 }", 21),
+        ("let a = [[0; 1000000000000]; 1000000000000]; 0;", 5),
+    ];
+
+    for (source, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+
+    // the frames of the program and its functions are separate
+    let source = "fn f() -> int { let a = [0; 200000000]; 0 }\nlet b = [0; 200000000]; f();";
+    assert_eq!(warnings(source, &Options::default()), []);
+}
+
+/// Errors resolving names are reported along with type errors, in the order
+/// of their spans, without repeating errors about what failed to resolve.
+#[test]
+fn resolve_errors() {
+    let mut sources = SourceMap::new();
+    sources.add(
+        "<test>".to_owned(),
+        None,
+        "let x = 1 + true;
+foo(x);
+let y = bar() + 1;
+println(y * 2, 1.5 + 1);
+fn g() -> int { 1 }
+fn g() -> int { 2 }"
+            .to_owned(),
+    );
+
+    let err = match crate::check(&mut sources, &Options::default()) {
+        Ok(_) => panic!("expected errors"),
+        Err(err) => err,
+    };
+
+    let mut handler = Messages {
+        sources: &sources,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    err.emit(&mut handler);
+
+    assert_eq!(
+        handler.errors,
+        [
+            "Operator cannot be applied to 'int' and 'bool'",
+            "Cannot find 'foo' in this scope",
+            "Cannot find 'bar' in this scope",
+            "Function 'g' is declared more than once",
+        ]
+    );
+}
+
+/// Paths through modules report which segment failed to resolve, items that
+/// aren't public, and names that more than one glob import brings in.
+#[test]
+fn module_paths() {
+    let cases = [
+        (
+            "mod m { fn f() -> int { 1 } } m::f();",
+            "'f' is private",
+            34,
+        ),
+        (
+            "mod m { } m::f();",
+            "Cannot find 'f' in module 'm'",
+            14,
+        ),
+        ("n::f();", "Cannot find module 'n'", 1),
+        (
+            "mod m { pub fn f() -> int { 1 } } m::n::f();",
+            "Cannot find 'n' in module 'm'",
+            38,
+        ),
+        (
+            "mod a { pub fn f() -> int { 1 } } mod b { pub fn f() -> int { 2 } } use a::*; use b::*; f();",
+            "'f' is ambiguous, it could refer to 'a::f' or 'b::f'",
+            89,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+}
+
+/// Imports are read relative to the importing file, a file imported twice is
+/// only merged once, and cycles are reported along with the chain of imports.
+#[test]
+fn imports() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let files = [
+        ("util.rp", "fn one() -> int { 1 }"),
+        (
+            "two.rp",
+            "import \"util.rp\";\nfn two() -> int { one() + one() }",
+        ),
+        ("a.rp", "import \"b.rp\";"),
+        ("b.rp", "import \"c.rp\";"),
+        ("c.rp", "import \"a.rp\";"),
+    ];
+
+    for (name, source) in files {
+        std::fs::write(dir.path().join(name), source).expect("failed to write import");
+    }
+
+    let check = |source: &str| {
+        let path = dir.path().join("main.rp");
+        let mut sources = SourceMap::new();
+        sources.add(path.display().to_string(), Some(path), source.to_owned());
+
+        crate::check(&mut sources, &Options::default())
+            .map(|_| ())
+            .map_err(|err| {
+                let err = err.first();
+                (err.message(&sources), sources.location(err.span()).2)
+            })
+    };
+
+    // merging 'util.rp' twice would declare 'one' twice
+    let source = "import \"util.rp\";\nimport \"two.rp\";\none() + two();";
+    assert_eq!(check(source), Ok(()));
+
+    let cycle = ["a.rp", "b.rp", "c.rp", "a.rp"]
+        .map(|name| dir.path().join(name).display().to_string())
+        .join(" -> ");
+    assert_eq!(
+        check("import \"a.rp\";"),
+        Err((format!("Import cycle: {}", cycle), 8))
+    );
+
+    let (message, column) = check("let x = 1; import \"missing.rp\";").unwrap_err();
+    assert!(
+        message.starts_with(&format!(
+            "Failed to import '{}': ",
+            dir.path().join("missing.rp").display()
+        )),
+        "{}",
+        message
+    );
+    assert_eq!(column, 19);
+}
+
+/// An error at a span, for testing how diagnostics are combined.
+#[derive(Debug)]
+struct TestError(&'static str, Span);
+
+impl Spanned for TestError {
+    fn span(&self) -> Span {
+        self.1
+    }
+}
+
+impl Report for TestError {
+    fn code(&self) -> &'static str {
+        "E9999"
+    }
+
+    fn message(&self, _: &SourceMap) -> String {
+        self.0.to_owned()
+    }
+
+    fn failure(&self) -> Failure {
+        Failure::Syntax
+    }
+}
+
+/// Merging diagnostics drops errors that are already present, as creating
+/// them does, and keeps them in the order of their spans.
+#[test]
+fn merge_diagnostics() {
+    let sources = SourceMap::new();
+    let a = || TestError("a", Span::new(1..2));
+    let b = || TestError("b", Span::new(5..6));
+    let c = || TestError("c", Span::new(3..4));
+
+    let first = Diagnostics::new(vec![b(), a(), b()]);
+    assert_eq!(first.count(), 2);
+
+    let merged = first.merge(Diagnostics::new(vec![c(), b()]));
+    let merged = merged.merge(Diagnostics::from(a()));
+
+    let mut handler = Messages {
+        sources: &sources,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    merged.emit(&mut handler);
+    assert_eq!(handler.errors, ["a", "c", "b"]);
+
+    // the same message at another span is a different error
+    let merged = merged.merge(Diagnostics::from(TestError("a", Span::new(7..8))));
+    assert_eq!(merged.count(), 4);
+}
+
+/// The short format puts each diagnostic on one line, with the code of its
+/// kind.
+#[test]
+fn short_format() {
+    let cases = [
+        (
+            "let x = 1 + true;",
+            "main.rp:1:11: error[E0325]: Operator cannot be applied to 'int' and 'bool'\n",
+        ),
+        (
+            "let x = 1;\nfoo(x);",
+            "main.rp:2:1: error[E0201]: Cannot find 'foo' in this scope\n",
+        ),
+        (
+            "let x = 1;\n  x = #;",
+            "main.rp:2:7: error[E0002]: Invalid character '#'\n",
+        ),
+    ];
+
+    for (source, expected) in cases {
+        let mut sources = SourceMap::new();
+        sources.add("main.rp".to_owned(), None, source.to_owned());
+
+        let err = match crate::check(&mut sources, &Options::default()) {
+            Ok(_) => panic!("expected '{}' to fail", source),
+            Err(err) => err,
+        };
+
+        let mut out = Vec::new();
+        err.emit(&mut Reporter::new(&mut out, &sources).format(Format::Short));
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
+
+/// The listing is written next to the output, named after it.
+#[test]
+fn listing() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let options = Options {
+        emit: emit::Options {
+            listing: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    build("println(1);", "program", options, &dir);
+
+    let listing =
+        std::fs::read_to_string(dir.path().join("program.lst")).expect("failed to read listing");
+    assert!(listing.contains("println(1);"), "{}", listing);
+}
+
+/// `--instrument=profile` counts the calls of each function and the
+/// iterations of each loop.
+#[test]
+fn profile() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let source = "fn fib(n: int) -> int {
+    if n < 2 { return n; }
+    fib(n - 1) + fib(n - 2)
+}
+let total = 0;
+for (let i = 0; i < 5; i = i + 1) {
+    total = total + fib(i);
+}
+while total > 3 { total = total - 1; }
+total;";
+    let options = Options {
+        emit: emit::Options {
+            profile: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    let output = build(source, "profile", options, &dir);
+
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr),
+        "[profile]
+1 main
+5 loop (profile.rp:6:1)
+4 loop (profile.rp:9:1)
+19 fn fib (profile.rp:1:4)
+"
+    );
+}
+
+/// `-g` describes the parameters and variables of every function, along
+/// with those of the program.
+#[test]
+fn debug_info() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let source = "fn scale(x: int, factor: float) -> float {
+    let scaled = x as float * factor;
+    scaled
+}
+let total = 3;
+println(scale(total, 1.5));";
+    let options = Options {
+        emit: emit::Options {
+            debug: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    let output = build(source, "debug", options, &dir);
+
+    let result = Command::new("readelf")
+        .arg("--debug-dump=info")
+        .arg(&output)
+        .output()
+        .expect("failed to run readelf");
+    let info = String::from_utf8_lossy(&result.stdout);
+
+    // the tag of each entry, followed by its name
+    let mut entries = Vec::new();
+    let mut tag = "";
+    for line in info.lines() {
+        if let Some((_, rest)) = line.split_once("(DW_TAG_") {
+            tag = rest.trim_end_matches(')');
+        } else if let Some((_, name)) = line.split_once("DW_AT_name") {
+            let name = name.rsplit(' ').next().unwrap();
+            entries.push(format!("DW_TAG_{} {}", tag, name));
+        }
+    }
+
+    let expected = [
+        "DW_TAG_subprogram ripc_main",
+        "DW_TAG_variable total",
+        "DW_TAG_subprogram ripc.scale",
+        "DW_TAG_formal_parameter x",
+        "DW_TAG_formal_parameter factor",
+        "DW_TAG_variable scaled",
+    ];
+    for entry in expected {
+        assert!(entries.iter().any(|e| e == entry), "{}\n{}", entry, info);
+    }
+}
+
+/// Exported functions can be called from C, which passes and receives C
+/// strings and expects the registers it saves to be preserved.
+#[test]
+fn export_to_c() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let source = "export fn add(a: int, b: int) -> int { a + b }
+export fn scale(x: float, by: float) -> float { x * by }
+export fn greet(name: str) -> str { \"hello, \" + name }";
+    let options = Options {
+        emit: emit::Options {
+            obj: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    let object = build(source, "export.o", options, &dir);
+
+    let main = dir.path().join("main.c");
+    std::fs::write(
+        &main,
+        "#include <stdio.h>
+int add(int, int);
+double scale(double, double);
+const char *greet(const char *);
+int main(void) {
+    printf(\"%d %g %s\\n\", add(40, 2), scale(1.5, 2.0), greet(\"world\"));
+    return 0;
+}",
+    )
+    .expect("failed to write C program");
+
+    let output = dir.path().join("main");
+    let status = Command::new("cc")
+        .arg(&main)
+        .arg(&object)
+        .arg("-o")
+        .arg(&output)
+        .status()
+        .expect("failed to run cc");
+    assert!(status.success());
+
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout),
+        "42 3 hello, world\n"
+    );
+}
+
+/// Exported functions are named after themselves, so they must be unique
+/// across modules.
+#[test]
+fn duplicate_export() {
+    let source = "export fn f() {}\nmod m { export fn f() {} }";
+    assert_eq!(
+        error(source, &Options::default()),
+        ("Function 'f' is exported more than once".to_owned(), 19)
+    );
+}
+
+/// `--emit=header` declares the exported functions of a program for C, which
+/// can then call them through the header alone.
+#[test]
+fn header() {
+    let dir = TempDir::new().expect("failed to create temporary directory");
+    let source = "struct Point { x: int, y: int }
+export fn add(a: int, b: int) -> int { a + b }
+export fn greet(name: str) -> str { \"hello, \" + name }
+export fn origin(p: *Point, row: *[int; 4], int: *str) -> bool { true }
+export fn tick() {}
+fn private(x: int) -> int { x }";
+    let options = Options {
+        emit: emit::Options {
+            obj: true,
+            header: true,
+            ..emit::Options::default()
+        },
+        ..Options::default()
+    };
+    let object = build(source, "lib.o", options, &dir);
+
+    let header = std::fs::read_to_string(dir.path().join("lib.h")).expect("failed to read header");
+    assert_eq!(
+        header,
+        "#ifndef LIB_H
+#define LIB_H
+
+#include <stdbool.h>
+
+struct Point;
+struct ripc_str;
+
+int add(int a, int b);
+const char *greet(const char *name);
+bool origin(struct Point *p, int (*row)[4], struct ripc_str **);
+void tick(void);
+
+#endif /* LIB_H */
+"
+    );
+
+    let main = dir.path().join("main.c");
+    std::fs::write(
+        &main,
+        "#include <stdio.h>
+#include \"lib.h\"
+int main(void) {
+    tick();
+    printf(\"%d %s\\n\", add(40, 2), greet(\"world\"));
+    return 0;
+}",
+    )
+    .expect("failed to write C program");
+
+    let output = dir.path().join("main");
+    let status = Command::new("cc")
+        .args(["-std=c99", "-Wall", "-Werror"])
+        .arg(&main)
+        .arg(&object)
+        .arg("-o")
+        .arg(&output)
+        .status()
+        .expect("failed to run cc");
+    assert!(status.success());
+
+    let result = Command::new(&output)
+        .output()
+        .expect("failed to run program");
+    assert_eq!(String::from_utf8_lossy(&result.stdout), "42 hello, world\n");
+}
+
+/// Functions return tuples in %rax and %rdx, so only tuples of up to two
+/// scalars can be returned, and only to the program.
+#[test]
+fn tuple_return() {
+    let cases = [
+        (
+            "fn f() -> (int, int, int) { (1, 2, 3) }",
+            "Cannot return '(int, int, int)', only tuples of up to two scalars can be returned",
+            11,
+        ),
+        (
+            "fn f() -> ((int, int), int) { f() }",
+            "Cannot return '((int, int), int)', only tuples of up to two scalars can be returned",
+            11,
+        ),
+        (
+            "export fn f() -> (int, int) { (1, 2) }",
+            "Cannot pass '(int, int)' to a function by value, use a pointer",
+            18,
+        ),
+        (
+            "fn f() -> (int, int) { (1, 2.5) }",
+            "Mismatched types: expected '(int, int)', found '(int, float)'",
+            24,
+        ),
+    ];
+
+    for (source, message, column) in cases {
+        assert_eq!(
+            error(source, &Options::default()),
+            (message.to_owned(), column),
+            "{}",
+            source
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Ty {
+    Int,
+    /// A 64-bit floating point number.
+    Float,
+    Bool,
+    /// A pointer to the length of a string and the address of its bytes,
+    /// which are converted to a NUL-terminated string when passed to C.
+    Str,
+    /// A pointer, `*int`.
+    Ptr(Box<Ty>),
+    /// A fixed-size array, `[int; 16]`.
+    Array(Box<Ty>, usize),
+    /// A struct, `Point`.
+    Struct(Rc<Struct>),
+    /// A tuple, `(int, float)`, laid out as a struct with fields named `0`,
+    /// `1`, and so on.
+    Tuple(Rc<Struct>),
+    /// The type of expressions that don't produce a value.
+    Void,
+    /// A type that has not been inferred yet.
+    Infer,
+}
+
+impl Ty {
+    pub fn from_name(name: &str) -> Option<Ty> {
+        match name {
+            "int" => Some(Ty::Int),
+            "float" => Some(Ty::Float),
+            "bool" => Some(Ty::Bool),
+            "str" => Some(Ty::Str),
+            _ => None,
+        }
+    }
+
+    /// The size of a value of this type in bytes, or `usize::MAX` if it is too
+    /// large to represent, which sema rejects.
+    pub fn size(&self) -> usize {
+        match self {
+            Ty::Int => 4,
+            Ty::Bool => 1,
+            Ty::Float | Ty::Str | Ty::Ptr(_) => 8,
+            Ty::Array(elem, len) => elem.size().saturating_mul(*len),
+            Ty::Struct(s) | Ty::Tuple(s) => s.size,
+            Ty::Void => 0,
+            Ty::Infer => unreachable!("size of uninferred type"),
+        }
+    }
+
+    /// The alignment of a value of this type in bytes.
+    pub fn align(&self) -> usize {
+        match self {
+            Ty::Array(elem, _) => elem.align(),
+            Ty::Struct(s) | Ty::Tuple(s) => s.align,
+            Ty::Void => 1,
+            ty => ty.size(),
+        }
+    }
+
+    /// Whether values of this type fit in a register.
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, Ty::Int | Ty::Float | Ty::Bool | Ty::Str | Ty::Ptr(_))
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Int => write!(f, "int"),
+            Ty::Float => write!(f, "float"),
+            Ty::Bool => write!(f, "bool"),
+            Ty::Str => write!(f, "str"),
+            Ty::Ptr(ty) => write!(f, "*{}", ty),
+            Ty::Array(elem, len) => write!(f, "[{}; {}]", elem, len),
+            Ty::Struct(s) | Ty::Tuple(s) => write!(f, "{}", s.name),
+            Ty::Void => write!(f, "()"),
+            Ty::Infer => write!(f, "_"),
+        }
+    }
+}
+
+/// A struct definition along with its memory layout.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub size: usize,
+    pub align: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub ty: Ty,
+    /// The offset of this field from the start of the struct.
+    pub offset: usize,
+}
+
+impl Struct {
+    /// Lays out the given fields in order, padding each to its alignment.
+    pub fn new(name: String, fields: Vec<(String, Ty)>) -> Struct {
+        let (mut size, mut align) = (0, 1);
+        let fields = fields
+            .into_iter()
+            .map(|(name, ty)| {
+                let offset = align_to(size, ty.align());
+                size = offset.saturating_add(ty.size());
+                align = align.max(ty.align());
+                Field { name, ty, offset }
+            })
+            .collect();
+
+        Struct {
+            name,
+            fields,
+            size: align_to(size, align),
+            align,
+        }
+    }
+
+    /// Lays out the elements of a tuple, named after their types.
+    pub fn tuple(elems: Vec<Ty>) -> Struct {
+        let names = elems.iter().map(Ty::to_string).collect::<Vec<_>>();
+        let name = match names.len() {
+            1 => format!("({},)", names[0]),
+            _ => format!("({})", names.join(", ")),
+        };
+
+        let fields = elems
+            .into_iter()
+            .enumerate()
+            .map(|(i, ty)| (i.to_string(), ty))
+            .collect();
+
+        Struct::new(name, fields)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// Rounds `n` up to a multiple of `align`, or to `usize::MAX` if there is none.
+pub fn align_to(n: usize, align: usize) -> usize {
+    n.div_ceil(align).saturating_mul(align)
+}
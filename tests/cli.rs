@@ -0,0 +1,277 @@
+//! Tests of the `ripc` command line, run against the built binary in a
+//! directory of their own, where it writes its output.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A directory for a single test, removed along with its contents when
+/// dropped.
+struct Dir(PathBuf);
+
+impl Dir {
+    fn new(test: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("ripc-cli-{}-{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("failed to create a temporary directory");
+        Self(path)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn write(&self, name: &str, source: &str) {
+        std::fs::write(self.0.join(name), source).expect("failed to write input");
+    }
+
+    fn read(&self, name: &str) -> String {
+        std::fs::read_to_string(self.0.join(name)).expect("failed to read output")
+    }
+
+    /// Runs `ripc` in the directory.
+    fn ripc(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_ripc"))
+            .args(args)
+            .current_dir(&self.0)
+            .output()
+            .expect("failed to run ripc")
+    }
+
+    /// Runs a program built in the directory.
+    fn run(&self, name: &str) -> Output {
+        Command::new(self.0.join(name))
+            .current_dir(&self.0)
+            .output()
+            .expect("failed to run program")
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// `--fix` applies suggestions one at a time until the input compiles, and
+/// stops at the first error without one, keeping the fixes before it.
+#[test]
+fn fix() {
+    let dir = Dir::new("fix");
+
+    dir.write("fix.rp", "let x = 1\nlet y = x + 2\ny;\n");
+    let output = dir.ripc(&["--fix", "fix.rp"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stderr(&output).matches("fixed: insert ';'").count(), 2);
+    assert_eq!(dir.read("fix.rp"), "let x = 1;\nlet y = x + 2;\ny;\n");
+    assert_eq!(dir.run("out").status.code(), Some(3));
+
+    dir.write("type.rp", "let x = 1 + true\n");
+    let output = dir.ripc(&["--fix", "type.rp"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("Operator cannot be applied to 'int' and 'bool'"));
+    assert_eq!(dir.read("type.rp"), "let x = 1 + true;\n");
+
+    dir.write("paren.rp", "let x = (1 + 2;\n");
+    let output = dir.ripc(&["--fix", "paren.rp"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(!stderr(&output).contains("fixed"));
+    assert_eq!(dir.read("paren.rp"), "let x = (1 + 2;\n");
+}
+
+/// Each kind of failure exits with its own code, so that a program that is
+/// wrong can be told apart from a broken toolchain.
+#[test]
+fn exit_codes() {
+    let dir = Dir::new("exit_codes");
+
+    let cases: [(&str, &[&str], i32); 8] = [
+        ("1;", &[], 0),
+        ("1;", &["extra.rp"], 2),
+        ("let x = 1 $ 2;", &[], 3),
+        ("let = 1;", &[], 3),
+        ("let x = 1 + true;", &[], 4),
+        ("let a = [1, 2]; let b = a; 0;", &[], 5),
+        ("1;", &["-o", "missing/out"], 6),
+        ("import \"missing.rp\";", &[], 6),
+    ];
+
+    for (source, args, code) in cases {
+        dir.write("input.rp", source);
+        let output = dir.ripc(&[&["input.rp"], args].concat());
+        assert_eq!(output.status.code(), Some(code), "{}", source);
+    }
+}
+
+/// Arguments after `--` are passed to the program as they are, including
+/// ones that look like flags of `ripc`.
+#[test]
+fn run_arguments() {
+    let dir = Dir::new("run_arguments");
+    dir.write(
+        "args.rp",
+        "extern fn printf(format: str, ...) -> int;
+let i = 0;
+while i < argc() { printf(\"%s\\n\", argv(i)); i = i + 1; }
+argc();",
+    );
+
+    let output = dir.ripc(&["run", "args.rp", "--", "a", "b c", "--fix", "-O9"]);
+    assert_eq!(stdout(&output), "./out\na\nb c\n--fix\n-O9\n");
+    assert_eq!(output.status.code(), Some(5));
+
+    // flags before `--` are still the compiler's
+    let output = dir.ripc(&["run", "args.rp", "-O9", "--", "a"]);
+    assert_eq!(stderr(&output), "invalid optimization level '9'\n");
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = dir.ripc(&["run", "args.rp", "--emit=obj"]);
+    assert_eq!(
+        stderr(&output),
+        "'ripc run' can't be used with '--emit=obj'\n"
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+/// `ripc run` exits as the program does, with 128 plus the signal that
+/// killed it, or with 124 once it runs for longer than `--timeout`.
+#[test]
+fn run_status() {
+    let dir = Dir::new("run_status");
+
+    dir.write("seven.rp", "7;");
+    let output = dir.ripc(&["run", "seven.rp"]);
+    assert_eq!(output.status.code(), Some(7));
+
+    dir.write("abort.rp", "extern fn abort() -> int; abort();");
+    let output = dir.ripc(&["run", "abort.rp"]);
+    assert_eq!(stderr(&output), "[error]: killed by SIGABRT\n");
+    assert_eq!(output.status.code(), Some(134));
+
+    dir.write("loop.rp", "let x = 1; while x > 0 { x = x * 1; } 0;");
+    let start = std::time::Instant::now();
+    let output = dir.ripc(&["run", "--timeout=0.2", "loop.rp"]);
+    assert!(start.elapsed().as_secs() < 10, "the program wasn't killed");
+    assert_eq!(stderr(&output), "[error]: timed out after 200ms\n");
+    assert_eq!(output.status.code(), Some(124));
+
+    let output = dir.ripc(&["run", "--timeout=soon", "seven.rp"]);
+    assert_eq!(stderr(&output), "invalid timeout 'soon'\n");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+/// `--eval` prints the value of an expression without building anything,
+/// and reports errors in it as it would in a file.
+#[test]
+fn eval() {
+    let dir = Dir::new("eval");
+
+    let cases = [
+        ("1 + 2 * 3", "7\n"),
+        ("let x = 3; x * x", "9\n"),
+        ("1.5 * 2.0", "3.000000\n"),
+        ("1 < 2", "true\n"),
+    ];
+
+    for (source, value) in cases {
+        let output = dir.ripc(&["--eval", source]);
+        assert_eq!(stdout(&output), value, "{}", source);
+        assert!(output.status.success(), "{}", source);
+    }
+
+    // nothing is assembled or linked
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    let cases: [(&[&str], &str, i32); 4] = [
+        (&["--eval", "1 / 0"], "[error]: Division by zero\n", 4),
+        (
+            &["--eval", "1 +"],
+            "[error]: Expected expression, found ';'\n",
+            3,
+        ),
+        (&["--eval", "1", "input.rp"], "invalid arguments\n", 2),
+        (&["--eval"], "expected an expression after '--eval'\n", 2),
+    ];
+
+    for (args, message, code) in cases {
+        let output = dir.ripc(args);
+        assert!(stderr(&output).starts_with(message), "{:?}", args);
+        assert_eq!(output.status.code(), Some(code), "{:?}", args);
+    }
+}
+
+/// `--emit=obj` stops after assembling, writing `out.o` or the path given
+/// with `-o`, and has no `_start` for `--entry` to pick what it calls.
+#[test]
+fn emit_obj() {
+    let dir = Dir::new("emit_obj");
+    dir.write("lib.rp", "export fn triple(x: int) -> int { x * 3 }");
+
+    for (args, object) in [(&[][..], "out.o"), (&["-o", "lib.o"][..], "lib.o")] {
+        let output = dir.ripc(&[&["--emit=obj", "lib.rp"], args].concat());
+        assert!(output.status.success(), "{}", stderr(&output));
+
+        // a relocatable ELF file
+        let bytes = std::fs::read(dir.path().join(object)).expect("failed to read object");
+        assert_eq!(bytes[..4], *b"\x7fELF", "{}", object);
+        assert_eq!(bytes[16], 1, "{}", object);
+    }
+
+    assert!(!dir.path().join("out").exists());
+
+    let output = dir.ripc(&["--emit=obj", "--entry", "main", "lib.rp"]);
+    assert_eq!(
+        stderr(&output),
+        "'--entry' requires '_start', which '--no-start' and '--emit=obj' leave out\n"
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+/// `ripc gen-test` prints the same program for the same seed, and the
+/// programs it prints compile.
+#[test]
+fn gen_test() {
+    let dir = Dir::new("gen_test");
+    let generate = |seed: &str| {
+        let output = dir.ripc(&["gen-test", "--seed", seed]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        stdout(&output)
+    };
+
+    assert_eq!(generate("42"), generate("42"));
+    assert_ne!(generate("42"), generate("43"));
+
+    for seed in 0..4 {
+        dir.write("gen.rp", &generate(&seed.to_string()));
+        let output = dir.ripc(&["gen.rp"]);
+        assert!(
+            output.status.success(),
+            "seed {}: {}",
+            seed,
+            stderr(&output)
+        );
+        assert!(dir.run("out").status.success(), "seed {}", seed);
+    }
+
+    let cases: [(&[&str], &str); 3] = [
+        (&["gen-test", "--seed", "x"], "invalid seed 'x'\n"),
+        (&["gen-test", "gen.rp"], "invalid arguments\n"),
+        (
+            &["--seed", "1", "gen.rp"],
+            "'--seed' is only used by 'ripc gen-test'\n",
+        ),
+    ];
+
+    for (args, message) in cases {
+        let output = dir.ripc(args);
+        assert_eq!(stderr(&output), message, "{:?}", args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+}
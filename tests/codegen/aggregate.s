@@ -0,0 +1,75 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $48, %rsp
+	# aggregate.rp:2: let p = Point { x: 1, y: 2.5 };
+	mov $1, %eax
+	mov %eax, -16(%rbp)
+	movabs $0x4004000000000000, %rax
+	mov %rax, -8(%rbp)
+	# aggregate.rp:3: let arr = [1, 2, 3];
+	lea .LA0(%rip), %rsi
+	lea -28(%rbp), %rdi
+	mov $12, %rcx
+	rep movsb
+	# aggregate.rp:4: let i = 2;
+	mov $2, %eax
+	mov %eax, -32(%rbp)
+	# aggregate.rp:5: arr[i] = p.x;
+	lea -16(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	lea -28(%rbp), %rax
+	push %rax
+	mov -32(%rbp), %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov %rax, %rdi
+	pop %rax
+	mov %eax, (%rdi)
+	# aggregate.rp:6: let q = &p;
+	lea -16(%rbp), %rax
+	mov %rax, -40(%rbp)
+	# aggregate.rp:7: println(arr[i]);
+	lea -28(%rbp), %rax
+	push %rax
+	mov -32(%rbp), %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .rodata
+.p2align 2
+.LA0:
+	.long 1, 2, 3
+.section .note.GNU-stack,"",@progbits
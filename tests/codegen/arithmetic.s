@@ -0,0 +1,62 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# arithmetic.rp:1: let x = 6;
+	mov $6, %eax
+	mov %eax, -4(%rbp)
+	# arithmetic.rp:2: let y = x * 7 - 2;
+	mov -4(%rbp), %eax
+	push %rax
+	mov $7, %eax
+	pop %rbx
+	imul %ebx, %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	mov %eax, -8(%rbp)
+	# arithmetic.rp:3: x = y / 3;
+	mov -8(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cltd
+	idiv %ebx
+	mov %eax, -4(%rbp)
+	# arithmetic.rp:4: println(x + y);
+	mov -4(%rbp), %eax
+	push %rax
+	mov -8(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .note.GNU-stack,"",@progbits
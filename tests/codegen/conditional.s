@@ -0,0 +1,233 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# conditional.rp:1: let x = 3;
+	mov $3, %eax
+	mov %eax, -4(%rbp)
+	# conditional.rp:2: if x > 2 {
+	# conditional.rp:3:     println("big");
+	# conditional.rp:4: } else {
+	# conditional.rp:5:     println("small");
+	# conditional.rp:6: }
+	mov -4(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setg %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L1
+	lea .LT0(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	jmp .L2
+.L1:
+	lea .LT1(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	.L2:
+	# conditional.rp:7: let y = if x == 1 { 10 } else if x == 3 { 30 } else { 0 };
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L3
+	mov $10, %eax
+	jmp .L4
+.L3:
+	mov -4(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L5
+	mov $30, %eax
+	jmp .L6
+.L5:
+	mov $0, %eax
+	.L6:
+	.L4:
+	mov %eax, -8(%rbp)
+	# conditional.rp:8: println(y);
+	mov -8(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# conditional.rp:9: if x < 0 { println("neg"); }
+	mov -4(%rbp), %eax
+	push %rax
+	mov $0, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L7
+	lea .LT2(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	jmp .L8
+.L7:
+	.L8:
+	# conditional.rp:10: let s = if y > 5 { "yes" } else { "no" };
+	mov -8(%rbp), %eax
+	push %rax
+	mov $5, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setg %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L9
+	lea .LT3(%rip), %rax
+	jmp .L10
+.L9:
+	lea .LT4(%rip), %rax
+	.L10:
+	mov %rax, -16(%rbp)
+	# conditional.rp:11: println(s, if false { 1.5 } else { 2.5 });
+	mov -16(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	cmp $0, %al
+	je .L11
+	movabs $0x3ff8000000000000, %rax
+	jmp .L12
+.L11:
+	movabs $0x4004000000000000, %rax
+	.L12:
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_str
+	mov $32, %edi
+	call ripc_print_char
+	movq 0(%rsp), %xmm0
+	call ripc_print_float
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	# conditional.rp:12: { if true { x = 7; } }
+	mov $1, %eax
+	cmp $0, %al
+	je .L13
+	mov $7, %eax
+	mov %eax, -4(%rbp)
+	jmp .L14
+.L13:
+	.L14:
+	# conditional.rp:13: println(x);
+	mov -4(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "big"
+.LTE0:
+	.byte 0
+.p2align 3
+.LT1:
+	.quad .LTE1 - .LTB1
+	.quad .LTB1
+.LTB1:
+	.ascii "small"
+.LTE1:
+	.byte 0
+.p2align 3
+.LT2:
+	.quad .LTE2 - .LTB2
+	.quad .LTB2
+.LTB2:
+	.ascii "neg"
+.LTE2:
+	.byte 0
+.p2align 3
+.LT3:
+	.quad .LTE3 - .LTB3
+	.quad .LTB3
+.LTB3:
+	.ascii "yes"
+.LTE3:
+	.byte 0
+.p2align 3
+.LT4:
+	.quad .LTE4 - .LTB4
+	.quad .LTB4
+.LTB4:
+	.ascii "no"
+.LTE4:
+	.byte 0
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,71 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# control.rp:1: let n = 0;
+	mov $0, %eax
+	mov %eax, -4(%rbp)
+	# control.rp:2: start:
+	.L.start:
+	# control.rp:3: n = n + 1;
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -4(%rbp)
+	# control.rp:4: match n {
+	# control.rp:5:     3 => goto end,
+	# control.rp:6:     _ => goto start,
+	# control.rp:7: };
+	mov -4(%rbp), %eax
+	cmp $3, %eax
+	je .L1
+	jmp .L2
+.L1:
+	jmp .L.end
+	jmp .L3
+.L2:
+	jmp .L.start
+	.L3:
+	# control.rp:8: end:
+	.L.end:
+	# control.rp:9: {
+	# control.rp:10:     let m = n * 2;
+	# control.rp:11:     println(m);
+	# control.rp:12: }
+	mov -4(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	pop %rbx
+	imul %ebx, %eax
+	mov %eax, -8(%rbp)
+	mov -8(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,255 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $48, %rsp
+	# evaluation_order.rp:1: let x = 1;
+	mov $1, %eax
+	mov %eax, -4(%rbp)
+	# evaluation_order.rp:2: println(x + (x = 10));
+	mov -4(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	mov %eax, -4(%rbp)
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# evaluation_order.rp:3: let y = 2;
+	mov $2, %eax
+	mov %eax, -8(%rbp)
+	# evaluation_order.rp:4: println(y * 10 + { y = 3; y });
+	mov -8(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	pop %rbx
+	imul %ebx, %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, -8(%rbp)
+	mov -8(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# evaluation_order.rp:5: let a = [0, 0, 0];
+	lea .LA0(%rip), %rsi
+	lea -20(%rbp), %rdi
+	mov $12, %rcx
+	rep movsb
+	# evaluation_order.rp:6: let i = 0;
+	mov $0, %eax
+	mov %eax, -24(%rbp)
+	# evaluation_order.rp:7: a[i] = { i = 2; 7 };
+	mov $2, %eax
+	mov %eax, -24(%rbp)
+	mov $7, %eax
+	push %rax
+	lea -20(%rbp), %rax
+	push %rax
+	mov -24(%rbp), %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov %rax, %rdi
+	pop %rax
+	mov %eax, (%rdi)
+	# evaluation_order.rp:8: println(a[0], a[1], a[2]);
+	lea -20(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %eax
+	push %rax
+	lea -20(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %eax
+	push %rax
+	lea -20(%rbp), %rax
+	push %rax
+	mov $2, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 16(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 8(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $24, %rsp
+	# evaluation_order.rp:9: let n = 0;
+	mov $0, %eax
+	mov %eax, -28(%rbp)
+	# evaluation_order.rp:10: println({ n = n + 1; n }, { n = n * 10; n }, n);
+	mov -28(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -28(%rbp)
+	mov -28(%rbp), %eax
+	push %rax
+	mov -28(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	pop %rbx
+	imul %ebx, %eax
+	mov %eax, -28(%rbp)
+	mov -28(%rbp), %eax
+	push %rax
+	mov -28(%rbp), %eax
+	push %rax
+	mov 16(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 8(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $24, %rsp
+	# evaluation_order.rp:11: println(min({ n = 100; n }, { n = n + 1; n }));
+	mov $100, %eax
+	mov %eax, -28(%rbp)
+	mov -28(%rbp), %eax
+	push %rax
+	mov -28(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -28(%rbp)
+	mov -28(%rbp), %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	mov 0(%rsp), %rsi
+	call min
+	add $16, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# evaluation_order.rp:13: let p = P { y: { n = n * 10; n }, x: { n = n + 1; n } };
+	mov -28(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	pop %rbx
+	imul %ebx, %eax
+	mov %eax, -28(%rbp)
+	mov -28(%rbp), %eax
+	mov %eax, -32(%rbp)
+	mov -28(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -28(%rbp)
+	mov -28(%rbp), %eax
+	mov %eax, -36(%rbp)
+	# evaluation_order.rp:14: println(p.x, p.y);
+	lea -36(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	lea -36(%rbp), %rax
+	add $4, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .rodata
+.p2align 2
+.LA0:
+	.long 0, 0, 0
+.section .note.GNU-stack,"",@progbits
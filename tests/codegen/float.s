@@ -0,0 +1,60 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# float.rp:1: let r = 2.5;
+	movabs $0x4004000000000000, %rax
+	mov %rax, -8(%rbp)
+	# float.rp:2: let area = 3.14159 * r * r;
+	movabs $0x400921f9f01b866e, %rax
+	push %rax
+	mov -8(%rbp), %rax
+	movq %rax, %xmm1
+	pop %rax
+	movq %rax, %xmm0
+	mulsd %xmm1, %xmm0
+	movq %xmm0, %rax
+	push %rax
+	mov -8(%rbp), %rax
+	movq %rax, %xmm1
+	pop %rax
+	movq %rax, %xmm0
+	mulsd %xmm1, %xmm0
+	movq %xmm0, %rax
+	mov %rax, -16(%rbp)
+	# float.rp:3: println(sqrt(area));
+	sub $8, %rsp
+	mov -16(%rbp), %rax
+	push %rax
+	movq 0(%rsp), %xmm0
+	call sqrt
+	movq %xmm0, %rax
+	add $16, %rsp
+	push %rax
+	movq 0(%rsp), %xmm0
+	sub $8, %rsp
+	call ripc_print_float
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .note.GNU-stack,"",@progbits
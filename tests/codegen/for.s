@@ -0,0 +1,237 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	# for.rp:1: let sum = 0;
+	mov $0, %eax
+	mov %eax, -4(%rbp)
+	# for.rp:2: for (let i = 0; i < 10; i = i + 1) {
+	# for.rp:3:     if i == 2 { continue; }
+	# for.rp:4:     if i == 7 { break; }
+	# for.rp:5:     sum = sum + i;
+	# for.rp:6: }
+	mov $0, %eax
+	mov %eax, -8(%rbp)
+	.L1:
+	mov -8(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L2
+	mov -8(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L4
+	jmp .L3
+	jmp .L5
+.L4:
+	.L5:
+	mov -8(%rbp), %eax
+	push %rax
+	mov $7, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L6
+	jmp .L2
+	jmp .L7
+.L6:
+	.L7:
+	mov -4(%rbp), %eax
+	push %rax
+	mov -8(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -4(%rbp)
+	.L3:
+	mov -8(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -8(%rbp)
+	jmp .L1
+.L2:
+	# for.rp:7: println(sum);
+	mov -4(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# for.rp:8: let n = 0;
+	mov $0, %eax
+	mov %eax, -12(%rbp)
+	# for.rp:9: for (; n < 3;) { n = n + 1; }
+	.L8:
+	mov -12(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L9
+	mov -12(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -12(%rbp)
+	jmp .L8
+.L9:
+	# for.rp:10: println(n);
+	mov -12(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# for.rp:11: for (let i = 0; ; i = i + 1) {
+	# for.rp:12:     if i > 2 { break; }
+	# for.rp:13:     println("i = {}", i);
+	# for.rp:14: }
+	mov $0, %eax
+	mov %eax, -16(%rbp)
+	.L10:
+	mov $1, %eax
+	cmp $0, %al
+	je .L11
+	mov -16(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setg %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L13
+	jmp .L11
+	jmp .L14
+.L13:
+	.L14:
+	sub $8, %rsp
+	mov -16(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rsi
+	lea .LS0(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $16, %rsp
+	.L12:
+	mov -16(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -16(%rbp)
+	jmp .L10
+.L11:
+	# for.rp:15: for (let i = 0; i < 2; i = i + 1) {
+	# for.rp:16:     for (let j = 0; j < 2; j = j + 1) { println(i, j); }
+	# for.rp:17: }
+	mov $0, %eax
+	mov %eax, -20(%rbp)
+	.L15:
+	mov -20(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L16
+	mov $0, %eax
+	mov %eax, -24(%rbp)
+	.L18:
+	mov -24(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L19
+	mov -20(%rbp), %eax
+	push %rax
+	mov -24(%rbp), %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	.L20:
+	mov -24(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -24(%rbp)
+	jmp .L18
+.L19:
+	.L17:
+	mov -20(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -20(%rbp)
+	jmp .L15
+.L16:
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .rodata
+.LS0:
+	.string "i = %d\n"
+.section .note.GNU-stack,"",@progbits
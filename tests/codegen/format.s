@@ -0,0 +1,242 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	# format.rp:1: let x = 3;
+	mov $3, %eax
+	mov %eax, -4(%rbp)
+	# format.rp:2: let y = 2.5;
+	movabs $0x4004000000000000, %rax
+	mov %rax, -16(%rbp)
+	# format.rp:3: let s = "hi";
+	lea .LT0(%rip), %rax
+	mov %rax, -24(%rbp)
+	# format.rp:4: println("x = {}, y = {}, s = '{}', b = {}", x, y, s, x > 2);
+	sub $8, %rsp
+	mov -4(%rbp), %eax
+	push %rax
+	mov -16(%rbp), %rax
+	push %rax
+	mov -24(%rbp), %rax
+	mov %rax, %rbx
+	mov (%rbx), %rax
+	push %rax
+	mov 8(%rbx), %rax
+	push %rax
+	mov -4(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setg %al
+	movzbl %al, %eax
+	test %al, %al
+	lea .LS1(%rip), %rax
+	lea .LS0(%rip), %rcx
+	cmovnz %rcx, %rax
+	push %rax
+	mov 32(%rsp), %rsi
+	movq 24(%rsp), %xmm0
+	mov 16(%rsp), %rdx
+	mov 8(%rsp), %rcx
+	mov 0(%rsp), %r8
+	lea .LS2(%rip), %rdi
+	mov $1, %eax
+	call printf
+	add $48, %rsp
+	# format.rp:5: println("{:x} {:c} {:.2} 100% {{}}", 255, 65, 3.14159);
+	sub $8, %rsp
+	mov $255, %eax
+	push %rax
+	mov $65, %eax
+	push %rax
+	movabs $0x400921f9f01b866e, %rax
+	push %rax
+	mov 16(%rsp), %rsi
+	mov 8(%rsp), %rdx
+	movq 0(%rsp), %xmm0
+	lea .LS3(%rip), %rdi
+	mov $1, %eax
+	call printf
+	add $32, %rsp
+	# format.rp:6: println("{} {} {} {} {} {} {} {} {} {}", 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0);
+	movabs $0x3ff0000000000000, %rax
+	push %rax
+	movabs $0x4000000000000000, %rax
+	push %rax
+	movabs $0x4008000000000000, %rax
+	push %rax
+	movabs $0x4010000000000000, %rax
+	push %rax
+	movabs $0x4014000000000000, %rax
+	push %rax
+	movabs $0x4018000000000000, %rax
+	push %rax
+	movabs $0x401c000000000000, %rax
+	push %rax
+	movabs $0x4020000000000000, %rax
+	push %rax
+	movabs $0x4022000000000000, %rax
+	push %rax
+	movabs $0x4024000000000000, %rax
+	push %rax
+	movq 72(%rsp), %xmm0
+	movq 64(%rsp), %xmm1
+	movq 56(%rsp), %xmm2
+	movq 48(%rsp), %xmm3
+	movq 40(%rsp), %xmm4
+	movq 32(%rsp), %xmm5
+	movq 24(%rsp), %xmm6
+	movq 16(%rsp), %xmm7
+	pushq 0(%rsp)
+	pushq 16(%rsp)
+	lea .LS4(%rip), %rdi
+	mov $8, %eax
+	call printf
+	add $96, %rsp
+	# format.rp:7: println("{} {} {} {} {} {} {} {} {}", 1, 2, 3, s, 5, 6, 7, 8, "end");
+	sub $8, %rsp
+	mov $1, %eax
+	push %rax
+	mov $2, %eax
+	push %rax
+	mov $3, %eax
+	push %rax
+	mov -24(%rbp), %rax
+	mov %rax, %rbx
+	mov (%rbx), %rax
+	push %rax
+	mov 8(%rbx), %rax
+	push %rax
+	mov $5, %eax
+	push %rax
+	mov $6, %eax
+	push %rax
+	mov $7, %eax
+	push %rax
+	mov $8, %eax
+	push %rax
+	lea .LT1(%rip), %rax
+	mov %rax, %rbx
+	mov (%rbx), %rax
+	push %rax
+	mov 8(%rbx), %rax
+	push %rax
+	mov 80(%rsp), %rsi
+	mov 72(%rsp), %rdx
+	mov 64(%rsp), %rcx
+	mov 56(%rsp), %r8
+	mov 48(%rsp), %r9
+	pushq 0(%rsp)
+	pushq 16(%rsp)
+	pushq 32(%rsp)
+	pushq 48(%rsp)
+	pushq 64(%rsp)
+	pushq 80(%rsp)
+	lea .LS5(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $144, %rsp
+	# format.rp:8: print("{}\t{}\n", s[0..1], len(s));
+	sub $8, %rsp
+	mov -24(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	sub $8, %rsp
+	call ripc_str_slice
+	add $8, %rsp
+	mov %rax, %rbx
+	mov (%rbx), %rax
+	push %rax
+	mov 8(%rbx), %rax
+	push %rax
+	mov -24(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 16(%rsp), %rsi
+	mov 8(%rsp), %rdx
+	mov 0(%rsp), %rcx
+	lea .LS6(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $32, %rsp
+	# format.rp:9: println("{}", x == 4);
+	sub $8, %rsp
+	mov -4(%rbp), %eax
+	push %rax
+	mov $4, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	test %al, %al
+	lea .LS1(%rip), %rax
+	lea .LS0(%rip), %rcx
+	cmovnz %rcx, %rax
+	push %rax
+	mov 0(%rsp), %rsi
+	lea .LS7(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $16, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "hi"
+.LTE0:
+	.byte 0
+.p2align 3
+.LT1:
+	.quad .LTE1 - .LTB1
+	.quad .LTB1
+.LTB1:
+	.ascii "end"
+.LTE1:
+	.byte 0
+.section .rodata
+.LS0:
+	.string "true"
+.LS1:
+	.string "false"
+.LS2:
+	.string "x = %d, y = %f, s = '%.*s', b = %s\n"
+.LS3:
+	.string "%x %c %.2f 100%% {}\n"
+.LS4:
+	.string "%f %f %f %f %f %f %f %f %f %f\n"
+.LS5:
+	.string "%d %d %d %.*s %d %d %d %d %.*s\n"
+.LS6:
+	.string "%.*s\t%d\n"
+.LS7:
+	.string "%s\n"
+.section .note.GNU-stack,"",@progbits
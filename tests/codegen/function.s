@@ -0,0 +1,481 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# function.rp:35: let x = 3;
+	mov $3, %eax
+	mov %eax, -4(%rbp)
+	# function.rp:36: println(add(x, 4), fact(5), half(5.0), fib(10), sign(0 - 7), looped());
+	mov -4(%rbp), %eax
+	push %rax
+	mov $4, %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	mov 0(%rsp), %rsi
+	call ripc.add
+	add $16, %rsp
+	push %rax
+	mov $5, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.fact
+	add $8, %rsp
+	push %rax
+	sub $8, %rsp
+	movabs $0x4014000000000000, %rax
+	push %rax
+	movq 0(%rsp), %xmm0
+	call ripc.half
+	movq %xmm0, %rax
+	add $16, %rsp
+	push %rax
+	mov $10, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.fib
+	add $8, %rsp
+	push %rax
+	sub $8, %rsp
+	mov $0, %eax
+	push %rax
+	mov $7, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.sign
+	add $16, %rsp
+	push %rax
+	sub $8, %rsp
+	call ripc.looped
+	add $8, %rsp
+	push %rax
+	mov 40(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 32(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	movq 24(%rsp), %xmm0
+	call ripc_print_float
+	mov $32, %edi
+	call ripc_print_char
+	mov 16(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 8(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $48, %rsp
+	# function.rp:37: greet("world");
+	sub $8, %rsp
+	lea .LT0(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.greet
+	add $16, %rsp
+	# function.rp:38: println(many(1, 2, 3, 4, 5, 6, 7, 2.5, 9));
+	sub $8, %rsp
+	mov $1, %eax
+	push %rax
+	mov $2, %eax
+	push %rax
+	mov $3, %eax
+	push %rax
+	mov $4, %eax
+	push %rax
+	mov $5, %eax
+	push %rax
+	mov $6, %eax
+	push %rax
+	mov $7, %eax
+	push %rax
+	movabs $0x4004000000000000, %rax
+	push %rax
+	mov $9, %eax
+	push %rax
+	mov 64(%rsp), %rdi
+	mov 56(%rsp), %rsi
+	mov 48(%rsp), %rdx
+	mov 40(%rsp), %rcx
+	mov 32(%rsp), %r8
+	mov 24(%rsp), %r9
+	movq 8(%rsp), %xmm0
+	pushq 0(%rsp)
+	pushq 24(%rsp)
+	call ripc.many
+	add $96, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# function.rp:39: println(math::square(9));
+	sub $8, %rsp
+	mov $9, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.math.square
+	add $16, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# function.rp:40: top:
+	.L.top:
+	# function.rp:41: println("done");
+	lea .LT1(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.add:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov %rsi, %rax
+	mov %eax, -8(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov -8(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	.L1:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.fact:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setle %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L3
+	mov $1, %eax
+	jmp .L2
+	jmp .L4
+.L3:
+	.L4:
+	mov -4(%rbp), %eax
+	push %rax
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.fact
+	add $8, %rsp
+	pop %rbx
+	imul %ebx, %eax
+	.L2:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.greet:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %rax, -8(%rbp)
+	mov -8(%rbp), %rax
+	mov %rax, %rbx
+	mov (%rbx), %rax
+	push %rax
+	mov 8(%rbx), %rax
+	push %rax
+	mov 8(%rsp), %rsi
+	mov 0(%rsp), %rdx
+	lea .LS0(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $16, %rsp
+	.L5:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.half:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	movq %xmm0, %rax
+	mov %rax, -8(%rbp)
+	mov -8(%rbp), %rax
+	push %rax
+	movabs $0x4000000000000000, %rax
+	movq %rax, %xmm1
+	pop %rax
+	movq %rax, %xmm0
+	divsd %xmm1, %xmm0
+	movq %xmm0, %rax
+	.L6:
+	movq %rax, %xmm0
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.many:
+	push %rbp
+	mov %rsp, %rbp
+	sub $48, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov %rsi, %rax
+	mov %eax, -8(%rbp)
+	mov %rdx, %rax
+	mov %eax, -12(%rbp)
+	mov %rcx, %rax
+	mov %eax, -16(%rbp)
+	mov %r8, %rax
+	mov %eax, -20(%rbp)
+	mov %r9, %rax
+	mov %eax, -24(%rbp)
+	mov 16(%rbp), %rax
+	mov %eax, -28(%rbp)
+	movq %xmm0, %rax
+	mov %rax, -40(%rbp)
+	mov 24(%rbp), %rax
+	mov %eax, -44(%rbp)
+	mov -40(%rbp), %rax
+	push %rax
+	movq 0(%rsp), %xmm0
+	sub $8, %rsp
+	call ripc_print_float
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov -4(%rbp), %eax
+	push %rax
+	mov -8(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov -12(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov -16(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov -20(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov -24(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov -28(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov -44(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	.L7:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.fib:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov $0, %eax
+	mov %eax, -8(%rbp)
+	mov $1, %eax
+	mov %eax, -12(%rbp)
+	mov $0, %eax
+	mov %eax, -16(%rbp)
+	.L9:
+	mov -16(%rbp), %eax
+	push %rax
+	mov -4(%rbp), %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L10
+	mov -8(%rbp), %eax
+	push %rax
+	mov -12(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -20(%rbp)
+	mov -12(%rbp), %eax
+	mov %eax, -8(%rbp)
+	mov -20(%rbp), %eax
+	mov %eax, -12(%rbp)
+	.L11:
+	mov -16(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -16(%rbp)
+	jmp .L9
+.L10:
+	mov -8(%rbp), %eax
+	.L8:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.sign:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov $0, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L13
+	mov $0, %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	jmp .L12
+	jmp .L14
+.L13:
+	mov -4(%rbp), %eax
+	push %rax
+	mov $0, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setg %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L15
+	mov $1, %eax
+	jmp .L12
+	jmp .L16
+.L15:
+	mov $0, %eax
+	jmp .L12
+	.L16:
+	.L14:
+	.L12:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.looped:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	.L.ripc.looped.top:
+	mov $5, %eax
+	mov %eax, -4(%rbp)
+	jmp .L.ripc.looped.end
+	.L.ripc.looped.end:
+	mov -4(%rbp), %eax
+	.L17:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.math.square:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov -4(%rbp), %eax
+	pop %rbx
+	imul %ebx, %eax
+	.L18:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "world"
+.LTE0:
+	.byte 0
+.p2align 3
+.LT1:
+	.quad .LTE1 - .LTB1
+	.quad .LTB1
+.LTB1:
+	.ascii "done"
+.LTE1:
+	.byte 0
+.section .rodata
+.LS0:
+	.string "hello, %.*s\n"
+.section .note.GNU-stack,"",@progbits
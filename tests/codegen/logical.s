@@ -0,0 +1,303 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# logical.rp:3: println(yes(1) && no(2));
+	sub $8, %rsp
+	mov $1, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.yes
+	add $16, %rsp
+	cmp $0, %al
+	je .L1
+	sub $8, %rsp
+	mov $2, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.no
+	add $16, %rsp
+	.L1:
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# logical.rp:4: println(no(3) && yes(4));
+	sub $8, %rsp
+	mov $3, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.no
+	add $16, %rsp
+	cmp $0, %al
+	je .L2
+	sub $8, %rsp
+	mov $4, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.yes
+	add $16, %rsp
+	.L2:
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# logical.rp:5: println(yes(5) || no(6));
+	sub $8, %rsp
+	mov $5, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.yes
+	add $16, %rsp
+	cmp $0, %al
+	jne .L3
+	sub $8, %rsp
+	mov $6, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.no
+	add $16, %rsp
+	.L3:
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# logical.rp:6: println(no(7) || yes(8));
+	sub $8, %rsp
+	mov $7, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.no
+	add $16, %rsp
+	cmp $0, %al
+	jne .L4
+	sub $8, %rsp
+	mov $8, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.yes
+	add $16, %rsp
+	.L4:
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# logical.rp:7: println(no(9) || no(10) && yes(11));
+	sub $8, %rsp
+	mov $9, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.no
+	add $16, %rsp
+	cmp $0, %al
+	jne .L5
+	sub $8, %rsp
+	mov $10, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.no
+	add $16, %rsp
+	cmp $0, %al
+	je .L6
+	sub $8, %rsp
+	mov $11, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.yes
+	add $16, %rsp
+	.L6:
+	.L5:
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# logical.rp:8: let x = 3;
+	mov $3, %eax
+	mov %eax, -4(%rbp)
+	# logical.rp:9: if x > 1 && x < 5 || x == 10 { println("in range"); }
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setg %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L10
+	mov -4(%rbp), %eax
+	push %rax
+	mov $5, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	.L10:
+	cmp $0, %al
+	jne .L9
+	mov -4(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	.L9:
+	cmp $0, %al
+	je .L7
+	lea .LT0(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	jmp .L8
+.L7:
+	.L8:
+	# logical.rp:10: let y = 0;
+	mov $0, %eax
+	mov %eax, -8(%rbp)
+	# logical.rp:11: if x == 3 || (y = 1) == 0 { println(y); }
+	mov -4(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	jne .L13
+	mov $1, %eax
+	mov %eax, -8(%rbp)
+	push %rax
+	mov $0, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	.L13:
+	cmp $0, %al
+	je .L11
+	mov -8(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	jmp .L12
+.L11:
+	.L12:
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.yes:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	sub $8, %rsp
+	mov -4(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rsi
+	lea .LS0(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $16, %rsp
+	mov $1, %eax
+	.L14:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.no:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	sub $8, %rsp
+	mov -4(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rsi
+	lea .LS1(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $16, %rsp
+	mov $0, %eax
+	.L15:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "in range"
+.LTE0:
+	.byte 0
+.section .rodata
+.LS0:
+	.string "yes %d\n"
+.LS1:
+	.string "no %d\n"
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,489 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	# native_string.rp:4: let s = "a\0b";
+	lea .LT0(%rip), %rax
+	mov %rax, -8(%rbp)
+	# native_string.rp:5: println(len(s));
+	mov -8(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:6: println(s);
+	mov -8(%rbp), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:7: println(s[2]);
+	mov -8(%rbp), %rax
+	push %rax
+	mov $2, %eax
+	movslq %eax, %rax
+	pop %rbx
+	add 8(%rbx), %rax
+	movzbl (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:8: let t = "hello" + ", " + "world";
+	lea .LT1(%rip), %rax
+	push %rax
+	lea .LT2(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_concat
+	push %rax
+	lea .LT3(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_concat
+	mov %rax, -16(%rbp)
+	# native_string.rp:9: println(t, len(t));
+	mov -16(%rbp), %rax
+	push %rax
+	mov -16(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_str
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	# native_string.rp:10: println("abc" < "abd", "ab" < "abc", "b" > "abc", "abc" == "abc", "abc" != "ab");
+	lea .LT4(%rip), %rax
+	push %rax
+	lea .LT5(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_str_cmp
+	cmp $0, %eax
+	setl %al
+	movzbl %al, %eax
+	push %rax
+	lea .LT6(%rip), %rax
+	push %rax
+	lea .LT4(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	sub $8, %rsp
+	call ripc_str_cmp
+	add $8, %rsp
+	cmp $0, %eax
+	setl %al
+	movzbl %al, %eax
+	push %rax
+	lea .LT7(%rip), %rax
+	push %rax
+	lea .LT4(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_str_cmp
+	cmp $0, %eax
+	setg %al
+	movzbl %al, %eax
+	push %rax
+	lea .LT4(%rip), %rax
+	push %rax
+	lea .LT4(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	sub $8, %rsp
+	call ripc_str_cmp
+	add $8, %rsp
+	cmp $0, %eax
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	lea .LT4(%rip), %rax
+	push %rax
+	lea .LT6(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_str_cmp
+	cmp $0, %eax
+	setne %al
+	movzbl %al, %eax
+	push %rax
+	mov 32(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 24(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 16(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 8(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $40, %rsp
+	# native_string.rp:11: let sl = t[7..];
+	mov -16(%rbp), %rax
+	push %rax
+	mov $7, %eax
+	movslq %eax, %rax
+	push %rax
+	mov 8(%rsp), %rax
+	mov (%rax), %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	mov %rax, -24(%rbp)
+	# native_string.rp:12: println(sl == "world");
+	mov -24(%rbp), %rax
+	push %rax
+	lea .LT3(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_str_cmp
+	cmp $0, %eax
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:13: puts(t[0..5]);
+	sub $8, %rsp
+	mov -16(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	push %rax
+	mov $5, %eax
+	movslq %eax, %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	sub $8, %rsp
+	call ripc_str_slice
+	add $8, %rsp
+	mov %rax, %rdi
+	sub $8, %rsp
+	call ripc_str_to_c
+	add $8, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	call puts
+	add $16, %rsp
+	# native_string.rp:14: println(strlen(t[1..3]));
+	sub $8, %rsp
+	mov -16(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	push %rax
+	mov $3, %eax
+	movslq %eax, %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	sub $8, %rsp
+	call ripc_str_slice
+	add $8, %rsp
+	mov %rax, %rdi
+	sub $8, %rsp
+	call ripc_str_to_c
+	add $8, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	call strlen
+	add $16, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:15: println(getenv("HOME") == "");
+	sub $8, %rsp
+	lea .LT8(%rip), %rax
+	mov %rax, %rdi
+	sub $8, %rsp
+	call ripc_str_to_c
+	add $8, %rsp
+	push %rax
+	mov 0(%rsp), %rdi
+	call getenv
+	add $16, %rsp
+	mov %rax, %rdi
+	call ripc_str_from_c
+	push %rax
+	lea .LT9(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_str_cmp
+	cmp $0, %eax
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:16: println(len("tab\there"));
+	lea .LT10(%rip), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# native_string.rp:17: println(true, false);
+	mov $1, %eax
+	push %rax
+	mov $0, %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_bool
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_bool
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	# native_string.rp:18: println(argc(), argv(0)[2..]);
+	mov .Largc(%rip), %eax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	mov .Largv(%rip), %rbx
+	mov (%rbx, %rax, 8), %rdi
+	sub $8, %rsp
+	call ripc_str_from_c
+	add $8, %rsp
+	push %rax
+	mov $2, %eax
+	movslq %eax, %rax
+	push %rax
+	mov 8(%rsp), %rax
+	mov (%rax), %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	sub $8, %rsp
+	call ripc_str_slice
+	add $8, %rsp
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_str
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	# native_string.rp:19: panic("bad" + "\0!");
+	lea .LT11(%rip), %rax
+	push %rax
+	lea .LT12(%rip), %rax
+	mov %rax, %rsi
+	pop %rdi
+	call ripc_concat
+	mov %rax, %rdi
+	lea .LS0(%rip), %rsi
+	call ripc_panic
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "a\0b"
+.LTE0:
+	.byte 0
+.p2align 3
+.LT1:
+	.quad .LTE1 - .LTB1
+	.quad .LTB1
+.LTB1:
+	.ascii "hello"
+.LTE1:
+	.byte 0
+.p2align 3
+.LT2:
+	.quad .LTE2 - .LTB2
+	.quad .LTB2
+.LTB2:
+	.ascii ", "
+.LTE2:
+	.byte 0
+.p2align 3
+.LT3:
+	.quad .LTE3 - .LTB3
+	.quad .LTB3
+.LTB3:
+	.ascii "world"
+.LTE3:
+	.byte 0
+.p2align 3
+.LT4:
+	.quad .LTE4 - .LTB4
+	.quad .LTB4
+.LTB4:
+	.ascii "abc"
+.LTE4:
+	.byte 0
+.p2align 3
+.LT5:
+	.quad .LTE5 - .LTB5
+	.quad .LTB5
+.LTB5:
+	.ascii "abd"
+.LTE5:
+	.byte 0
+.p2align 3
+.LT6:
+	.quad .LTE6 - .LTB6
+	.quad .LTB6
+.LTB6:
+	.ascii "ab"
+.LTE6:
+	.byte 0
+.p2align 3
+.LT7:
+	.quad .LTE7 - .LTB7
+	.quad .LTB7
+.LTB7:
+	.ascii "b"
+.LTE7:
+	.byte 0
+.p2align 3
+.LT8:
+	.quad .LTE8 - .LTB8
+	.quad .LTB8
+.LTB8:
+	.ascii "HOME"
+.LTE8:
+	.byte 0
+.p2align 3
+.LT9:
+	.quad .LTE9 - .LTB9
+	.quad .LTB9
+.LTB9:
+	.ascii ""
+.LTE9:
+	.byte 0
+.p2align 3
+.LT10:
+	.quad .LTE10 - .LTB10
+	.quad .LTB10
+.LTB10:
+	.ascii "tab\there"
+.LTE10:
+	.byte 0
+.p2align 3
+.LT11:
+	.quad .LTE11 - .LTB11
+	.quad .LTB11
+.LTB11:
+	.ascii "bad"
+.LTE11:
+	.byte 0
+.p2align 3
+.LT12:
+	.quad .LTE12 - .LTB12
+	.quad .LTB12
+.LTB12:
+	.ascii "\0!"
+.LTE12:
+	.byte 0
+.section .rodata
+.LS0:
+	.string "native_string.rp:19:1"
+.section .note.GNU-stack,"",@progbits
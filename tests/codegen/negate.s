@@ -0,0 +1,199 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	# negate.rp:2: let x = 7;
+	mov $7, %eax
+	mov %eax, -4(%rbp)
+	# negate.rp:3: let f = 2.5;
+	movabs $0x4004000000000000, %rax
+	mov %rax, -16(%rbp)
+	# negate.rp:4: println(-x, -f, - -x, -x * 3, 2 - -x, -(x + 1), -f * 2.0, -0.0);
+	mov -4(%rbp), %eax
+	neg %eax
+	push %rax
+	mov -16(%rbp), %rax
+	btc $63, %rax
+	push %rax
+	mov -4(%rbp), %eax
+	neg %eax
+	neg %eax
+	push %rax
+	mov -4(%rbp), %eax
+	neg %eax
+	push %rax
+	mov $3, %eax
+	pop %rbx
+	imul %ebx, %eax
+	push %rax
+	mov $2, %eax
+	push %rax
+	mov -4(%rbp), %eax
+	neg %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	push %rax
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	neg %eax
+	push %rax
+	mov -16(%rbp), %rax
+	btc $63, %rax
+	push %rax
+	movabs $0x4000000000000000, %rax
+	movq %rax, %xmm1
+	pop %rax
+	movq %rax, %xmm0
+	mulsd %xmm1, %xmm0
+	movq %xmm0, %rax
+	push %rax
+	movabs $0x0, %rax
+	btc $63, %rax
+	push %rax
+	mov 56(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	movq 48(%rsp), %xmm0
+	call ripc_print_float
+	mov $32, %edi
+	call ripc_print_char
+	mov 40(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 32(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 24(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 16(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	movq 8(%rsp), %xmm0
+	call ripc_print_float
+	mov $32, %edi
+	call ripc_print_char
+	movq 0(%rsp), %xmm0
+	call ripc_print_float
+	mov $10, %edi
+	call ripc_print_char
+	add $64, %rsp
+	# negate.rp:5: println(scale(4), -scale(-3, 5), -x as float);
+	mov $4, %eax
+	push %rax
+	mov $2, %eax
+	neg %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	mov 0(%rsp), %rsi
+	call ripc.scale
+	add $16, %rsp
+	push %rax
+	sub $8, %rsp
+	mov $3, %eax
+	neg %eax
+	push %rax
+	mov $5, %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	mov 0(%rsp), %rsi
+	call ripc.scale
+	add $24, %rsp
+	neg %eax
+	push %rax
+	mov -4(%rbp), %eax
+	neg %eax
+	cvtsi2sd %eax, %xmm0
+	movq %xmm0, %rax
+	push %rax
+	mov 16(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 8(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	movq 0(%rsp), %xmm0
+	sub $8, %rsp
+	call ripc_print_float
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $24, %rsp
+	# negate.rp:6: let y = -2147483647 - 1;
+	mov $2147483647, %eax
+	neg %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	mov %eax, -20(%rbp)
+	# negate.rp:7: println(-y);
+	mov -20(%rbp), %eax
+	neg %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.scale:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov %rsi, %rax
+	mov %eax, -8(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov -8(%rbp), %eax
+	pop %rbx
+	imul %ebx, %eax
+	.L1:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .note.GNU-stack,"",@progbits
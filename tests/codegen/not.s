@@ -0,0 +1,235 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	# not.rp:3: let done = false;
+	mov $0, %eax
+	mov %al, -1(%rbp)
+	# not.rp:4: println(!done, !!done, !odd(3), !(1 < 2) || true, flag());
+	movzbl -1(%rbp), %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	movzbl -1(%rbp), %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	sub $8, %rsp
+	mov $3, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.odd
+	add $16, %rsp
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	mov $1, %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	jne .L1
+	mov $1, %eax
+	.L1:
+	push %rax
+	sub $8, %rsp
+	mov $0, %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.flag
+	add $16, %rsp
+	push %rax
+	mov 32(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 24(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 16(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 8(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $32, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $40, %rsp
+	# not.rp:5: let i = 0;
+	mov $0, %eax
+	mov %eax, -8(%rbp)
+	# not.rp:6: while !(i == 3) { i = i + 1; }
+	.L2:
+	mov -8(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L3
+	mov -8(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -8(%rbp)
+	jmp .L2
+.L3:
+	# not.rp:7: if !done && !odd(i - 1) { println("even", i - 1); }
+	movzbl -1(%rbp), %eax
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L6
+	sub $8, %rsp
+	mov -8(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	call ripc.odd
+	add $16, %rsp
+	cmp $0, %al
+	sete %al
+	movzbl %al, %eax
+	.L6:
+	cmp $0, %al
+	je .L4
+	lea .LT0(%rip), %rax
+	push %rax
+	mov -8(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	mov %eax, %ebx
+	pop %rax
+	sub %ebx, %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_str
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	jmp .L5
+.L4:
+	.L5:
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.odd:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %eax, -4(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cltd
+	idiv %ebx
+	push %rax
+	mov $2, %eax
+	pop %rbx
+	imul %ebx, %eax
+	push %rax
+	mov -4(%rbp), %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setne %al
+	movzbl %al, %eax
+	.L7:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+ripc.flag:
+	push %rbp
+	mov %rsp, %rbp
+	sub $16, %rsp
+	mov %rdi, %rax
+	mov %al, -1(%rbp)
+	movzbl -1(%rbp), %eax
+	.L8:
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "even"
+.LTE0:
+	.byte 0
+.section .note.GNU-stack,"",@progbits
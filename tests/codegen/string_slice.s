@@ -0,0 +1,290 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	# string_slice.rp:1: let s = "hello, world";
+	lea .LT0(%rip), %rax
+	mov %rax, -8(%rbp)
+	# string_slice.rp:2: println(s[0]);
+	mov -8(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	pop %rbx
+	add 8(%rbx), %rax
+	movzbl (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:3: println(s[1]);
+	mov -8(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	pop %rbx
+	add 8(%rbx), %rax
+	movzbl (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:4: println(s[7..]);
+	mov -8(%rbp), %rax
+	push %rax
+	mov $7, %eax
+	movslq %eax, %rax
+	push %rax
+	mov 8(%rsp), %rax
+	mov (%rax), %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:5: println(s[..5]);
+	mov -8(%rbp), %rax
+	push %rax
+	xor %eax, %eax
+	push %rax
+	mov $5, %eax
+	movslq %eax, %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:6: println(s[7..10]);
+	mov -8(%rbp), %rax
+	push %rax
+	mov $7, %eax
+	movslq %eax, %rax
+	push %rax
+	mov $10, %eax
+	movslq %eax, %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:7: println(s[..]);
+	mov -8(%rbp), %rax
+	push %rax
+	xor %eax, %eax
+	push %rax
+	mov 8(%rsp), %rax
+	mov (%rax), %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:8: let i = 3;
+	mov $3, %eax
+	mov %eax, -12(%rbp)
+	# string_slice.rp:9: println(s[i..i + 2]);
+	mov -8(%rbp), %rax
+	push %rax
+	mov -12(%rbp), %eax
+	movslq %eax, %rax
+	push %rax
+	mov -12(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	pop %rbx
+	add %ebx, %eax
+	movslq %eax, %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:10: let words = ["abc", "xyz"];
+	lea .LT1(%rip), %rax
+	mov %rax, -32(%rbp)
+	lea .LT2(%rip), %rax
+	mov %rax, -24(%rbp)
+	# string_slice.rp:11: println(words[1][2]);
+	lea -32(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	imul $8, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %rax
+	push %rax
+	mov $2, %eax
+	movslq %eax, %rax
+	pop %rbx
+	add 8(%rbx), %rax
+	movzbl (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:12: println(words[0][1..]);
+	lea -32(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	imul $8, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	push %rax
+	mov 8(%rsp), %rax
+	mov (%rax), %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# string_slice.rp:13: println("tail"[2..]);
+	lea .LT3(%rip), %rax
+	push %rax
+	mov $2, %eax
+	movslq %eax, %rax
+	push %rax
+	mov 8(%rsp), %rax
+	mov (%rax), %rax
+	mov %rax, %rdx
+	pop %rsi
+	pop %rdi
+	call ripc_str_slice
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "hello, world"
+.LTE0:
+	.byte 0
+.p2align 3
+.LT1:
+	.quad .LTE1 - .LTB1
+	.quad .LTB1
+.LTB1:
+	.ascii "abc"
+.LTE1:
+	.byte 0
+.p2align 3
+.LT2:
+	.quad .LTE2 - .LTB2
+	.quad .LTB2
+.LTB2:
+	.ascii "xyz"
+.LTE2:
+	.byte 0
+.p2align 3
+.LT3:
+	.quad .LTE3 - .LTB3
+	.quad .LTB3
+.LTB3:
+	.ascii "tail"
+.LTE3:
+	.byte 0
+.section .note.GNU-stack,"",@progbits
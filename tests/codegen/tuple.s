@@ -0,0 +1,446 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $144, %rsp
+	# tuple.rp:1: let a = 1;
+	mov $1, %eax
+	mov %eax, -4(%rbp)
+	# tuple.rp:2: let b = 2;
+	mov $2, %eax
+	mov %eax, -8(%rbp)
+	# tuple.rp:3: a, b = (b, a);
+	mov -8(%rbp), %eax
+	push %rax
+	mov -4(%rbp), %eax
+	push %rax
+	mov 8(%rsp), %rax
+	mov %eax, -4(%rbp)
+	mov 0(%rsp), %rax
+	mov %eax, -8(%rbp)
+	add $16, %rsp
+	# tuple.rp:4: println(a);
+	mov -4(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:5: println(b);
+	mov -8(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:6: let t = (3, 4.5, true);
+	mov $3, %eax
+	mov %eax, -32(%rbp)
+	movabs $0x4012000000000000, %rax
+	mov %rax, -24(%rbp)
+	mov $1, %eax
+	mov %al, -16(%rbp)
+	# tuple.rp:7: let x = 0;
+	mov $0, %eax
+	mov %eax, -36(%rbp)
+	# tuple.rp:8: let y = 0.0;
+	movabs $0x0, %rax
+	mov %rax, -48(%rbp)
+	# tuple.rp:9: let z = false;
+	mov $0, %eax
+	mov %al, -49(%rbp)
+	# tuple.rp:10: x, y, z = t;
+	lea -32(%rbp), %rax
+	mov %rax, %rsi
+	mov 0(%rsi), %eax
+	push %rax
+	mov 8(%rsi), %rax
+	push %rax
+	movzbl 16(%rsi), %eax
+	push %rax
+	mov 16(%rsp), %rax
+	mov %eax, -36(%rbp)
+	mov 8(%rsp), %rax
+	mov %rax, -48(%rbp)
+	mov 0(%rsp), %rax
+	mov %al, -49(%rbp)
+	add $24, %rsp
+	# tuple.rp:11: println(x);
+	mov -36(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:12: println(y);
+	mov -48(%rbp), %rax
+	push %rax
+	movq 0(%rsp), %xmm0
+	sub $8, %rsp
+	call ripc_print_float
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:13: println(z);
+	movzbl -49(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_bool
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:14: println(t.0);
+	lea -32(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:15: println(t.1);
+	lea -32(%rbp), %rax
+	add $8, %rax
+	mov (%rax), %rax
+	push %rax
+	movq 0(%rsp), %xmm0
+	sub $8, %rsp
+	call ripc_print_float
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:16: let n = ((1, 2), 3);
+	mov $1, %eax
+	mov %eax, -64(%rbp)
+	mov $2, %eax
+	mov %eax, -60(%rbp)
+	mov $3, %eax
+	mov %eax, -56(%rbp)
+	# tuple.rp:17: println(n.0.1);
+	lea -64(%rbp), %rax
+	add $4, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:18: println(n.1);
+	lea -64(%rbp), %rax
+	add $8, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:19: let arr = [(1, 2), (3, 4)];
+	mov $1, %eax
+	mov %eax, -80(%rbp)
+	mov $2, %eax
+	mov %eax, -76(%rbp)
+	mov $3, %eax
+	mov %eax, -72(%rbp)
+	mov $4, %eax
+	mov %eax, -68(%rbp)
+	# tuple.rp:20: let p = 0;
+	mov $0, %eax
+	mov %eax, -84(%rbp)
+	# tuple.rp:21: let q = 0;
+	mov $0, %eax
+	mov %eax, -88(%rbp)
+	# tuple.rp:22: p, q = arr[1];
+	lea -80(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	imul $8, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov %rax, %rsi
+	mov 0(%rsi), %eax
+	push %rax
+	mov 4(%rsi), %eax
+	push %rax
+	mov 8(%rsp), %rax
+	mov %eax, -84(%rbp)
+	mov 0(%rsp), %rax
+	mov %eax, -88(%rbp)
+	add $16, %rsp
+	# tuple.rp:23: println(p);
+	mov -84(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:24: println(q);
+	mov -88(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:25: let one = (7,);
+	mov $7, %eax
+	mov %eax, -92(%rbp)
+	# tuple.rp:26: println(one.0);
+	lea -92(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:27: let xs = [0, 0];
+	lea .LA0(%rip), %rsi
+	lea -100(%rbp), %rdi
+	mov $8, %rcx
+	rep movsb
+	# tuple.rp:28: xs[0], xs[1] = (10, 20);
+	mov $10, %eax
+	push %rax
+	mov $20, %eax
+	push %rax
+	lea -100(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov %rax, %rdi
+	mov 8(%rsp), %rax
+	mov %eax, (%rdi)
+	lea -100(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov %rax, %rdi
+	mov 0(%rsp), %rax
+	mov %eax, (%rdi)
+	add $16, %rsp
+	# tuple.rp:29: println(xs[0] + xs[1]);
+	lea -100(%rbp), %rax
+	push %rax
+	mov $0, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %eax
+	push %rax
+	lea -100(%rbp), %rax
+	push %rax
+	mov $1, %eax
+	movslq %eax, %rax
+	imul $4, %rax
+	pop %rbx
+	add %rbx, %rax
+	mov (%rax), %eax
+	pop %rbx
+	add %ebx, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:30: t.0 = 9;
+	mov $9, %eax
+	push %rax
+	lea -32(%rbp), %rax
+	mov %rax, %rdi
+	pop %rax
+	mov %eax, (%rdi)
+	# tuple.rp:31: println(t.0);
+	lea -32(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:33: let s = Seg { a: (1, 2), b: (0.5, true) };
+	mov $1, %eax
+	mov %eax, -128(%rbp)
+	mov $2, %eax
+	mov %eax, -124(%rbp)
+	movabs $0x3fe0000000000000, %rax
+	mov %rax, -120(%rbp)
+	mov $1, %eax
+	mov %al, -112(%rbp)
+	# tuple.rp:34: println(s.a.1);
+	lea -128(%rbp), %rax
+	add $4, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:35: println(s.b.0);
+	lea -128(%rbp), %rax
+	add $8, %rax
+	mov (%rax), %rax
+	push %rax
+	movq 0(%rsp), %xmm0
+	sub $8, %rsp
+	call ripc_print_float
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:36: println(sizeof(s));
+	mov $24, %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:37: let ptr = &s.a;
+	lea -128(%rbp), %rax
+	mov %rax, -136(%rbp)
+	# tuple.rp:38: (*ptr).0, (*ptr).1 = ((*ptr).1, 5);
+	mov -136(%rbp), %rax
+	add $4, %rax
+	mov (%rax), %eax
+	push %rax
+	mov $5, %eax
+	push %rax
+	mov -136(%rbp), %rax
+	mov %rax, %rdi
+	mov 8(%rsp), %rax
+	mov %eax, (%rdi)
+	mov -136(%rbp), %rax
+	add $4, %rax
+	mov %rax, %rdi
+	mov 0(%rsp), %rax
+	mov %eax, (%rdi)
+	add $16, %rsp
+	# tuple.rp:39: println(s.a.0);
+	lea -128(%rbp), %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	# tuple.rp:40: println(s.a.1);
+	lea -128(%rbp), %rax
+	add $4, %rax
+	mov (%rax), %eax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_int
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .rodata
+.p2align 2
+.LA0:
+	.long 0, 0
+.section .note.GNU-stack,"",@progbits
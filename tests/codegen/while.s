@@ -0,0 +1,223 @@
+.text
+.lcomm .Largc, 8
+.lcomm .Largv, 8
+.global _start
+_start:
+	xor %ebp, %ebp
+	mov (%rsp), %rax
+	mov %rax, .Largc(%rip)
+	lea 8(%rsp), %rax
+	mov %rax, .Largv(%rip)
+	call ripc_main
+	mov %eax, %edi
+	call exit
+	ripc_main:
+	push %rbp
+	mov %rsp, %rbp
+	sub $32, %rsp
+	# while.rp:1: let i = 0;
+	mov $0, %eax
+	mov %eax, -4(%rbp)
+	# while.rp:2: let sum = 0;
+	mov $0, %eax
+	mov %eax, -8(%rbp)
+	# while.rp:3: while i < 10 {
+	# while.rp:4:     i = i + 1;
+	# while.rp:5:     if i == 3 { continue; }
+	# while.rp:6:     if i == 8 { break; }
+	# while.rp:7:     sum = sum + i;
+	# while.rp:8: }
+	.L1:
+	mov -4(%rbp), %eax
+	push %rax
+	mov $10, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L2
+	mov -4(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -4(%rbp)
+	mov -4(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L3
+	jmp .L1
+	jmp .L4
+.L3:
+	.L4:
+	mov -4(%rbp), %eax
+	push %rax
+	mov $8, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	sete %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L5
+	jmp .L2
+	jmp .L6
+.L5:
+	.L6:
+	mov -8(%rbp), %eax
+	push %rax
+	mov -4(%rbp), %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -8(%rbp)
+	jmp .L1
+.L2:
+	# while.rp:9: println(i, sum);
+	mov -4(%rbp), %eax
+	push %rax
+	mov -8(%rbp), %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	# while.rp:10: let n = 0;
+	mov $0, %eax
+	mov %eax, -12(%rbp)
+	# while.rp:11: while (n < 3) { println("n = {}", n); n = n + 1; }
+	.L7:
+	mov -12(%rbp), %eax
+	push %rax
+	mov $3, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L8
+	sub $8, %rsp
+	mov -12(%rbp), %eax
+	push %rax
+	mov 0(%rsp), %rsi
+	lea .LS0(%rip), %rdi
+	mov $0, %eax
+	call printf
+	add $16, %rsp
+	mov -12(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -12(%rbp)
+	jmp .L7
+.L8:
+	# while.rp:12: while false { println("never"); }
+	.L9:
+	mov $0, %eax
+	cmp $0, %al
+	je .L10
+	lea .LT0(%rip), %rax
+	push %rax
+	mov 0(%rsp), %rdi
+	sub $8, %rsp
+	call ripc_print_str
+	add $8, %rsp
+	mov $10, %edi
+	sub $8, %rsp
+	call ripc_print_char
+	add $8, %rsp
+	add $8, %rsp
+	jmp .L9
+.L10:
+	# while.rp:13: let j = 0;
+	mov $0, %eax
+	mov %eax, -16(%rbp)
+	# while.rp:14: while j < 2 {
+	# while.rp:15:     let k = 0;
+	# while.rp:16:     while k < 2 { println(j, k); k = k + 1; }
+	# while.rp:17:     j = j + 1;
+	# while.rp:18: }
+	.L11:
+	mov -16(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L12
+	mov $0, %eax
+	mov %eax, -20(%rbp)
+	.L13:
+	mov -20(%rbp), %eax
+	push %rax
+	mov $2, %eax
+	mov %eax, %ebx
+	pop %rax
+	cmp %ebx, %eax
+	setl %al
+	movzbl %al, %eax
+	cmp $0, %al
+	je .L14
+	mov -16(%rbp), %eax
+	push %rax
+	mov -20(%rbp), %eax
+	push %rax
+	mov 8(%rsp), %rdi
+	call ripc_print_int
+	mov $32, %edi
+	call ripc_print_char
+	mov 0(%rsp), %rdi
+	call ripc_print_int
+	mov $10, %edi
+	call ripc_print_char
+	add $16, %rsp
+	mov -20(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -20(%rbp)
+	jmp .L13
+.L14:
+	mov -16(%rbp), %eax
+	push %rax
+	mov $1, %eax
+	pop %rbx
+	add %ebx, %eax
+	mov %eax, -16(%rbp)
+	jmp .L11
+.L12:
+	mov $0, %eax
+	mov %rbp, %rsp
+	pop %rbp
+	ret
+.section .data.rel.ro
+.p2align 3
+.LT0:
+	.quad .LTE0 - .LTB0
+	.quad .LTB0
+.LTB0:
+	.ascii "never"
+.LTE0:
+	.byte 0
+.section .rodata
+.LS0:
+	.string "n = %d\n"
+.section .note.GNU-stack,"",@progbits